@@ -1,110 +1,90 @@
+use my_macro_lib::{ensures, Character};
+use serde::{Deserialize, Serialize};
+
 use super::weapons::{Mace, Staff, Sword, Weapon};
+use crate::register_type;
+use crate::registry::{Registry, Tagged, TypeTag};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum CharacterClass {
+    Warrior,
+    Mage,
+    Healer,
+}
 
-pub trait Character {
+pub trait Character: CharacterClone + Tagged {
+    fn class(&self) -> CharacterClass;
+    fn health(&self) -> u8;
+    fn max_health(&self) -> u8;
+    fn strength(&self) -> u8;
+    fn intelligent(&self) -> u8;
+    fn weapon(&self) -> &dyn Weapon;
     fn health_increase(&mut self, value: u8);
     fn health_decrease(&mut self, value: u8);
 }
 
+crate::trait_object!(Character, CharacterClone, register_characters);
+
+#[derive(Clone, Serialize, Deserialize, Character)]
 pub struct Warrior {
+    #[character(health, max = 100)]
     pub health: u8,
+    #[character(strength, default = 10)]
     strength: u8,
+    #[character(intelligent)]
     intelligent: u8,
+    #[character(weapon, default = Box::new(Sword))]
     pub weapon: Box<dyn Weapon>,
 }
 
-impl Warrior {
-    pub fn new() -> Self {
-        Self {
-            health: 100,
-            strength: 10,
-            intelligent: 0,
-            weapon: Box::new(Sword),
-        }
-    }
-}
-
-impl Character for Warrior {
-    fn health_increase(&mut self, value: u8) {
-        if self.health + value > 100 {
-            self.health = 100;
-        } else {
-            self.health += value;
-        }
-    }
-
-    fn health_decrease(&mut self, value: u8) {
-        self.health = self.health.saturating_sub(value);
-    }
+impl TypeTag for Warrior {
+    const TYPE_TAG: &'static str = "Warrior";
 }
 
+#[derive(Clone, Serialize, Deserialize, Character)]
 pub struct Mage {
+    #[character(health, max = 100)]
     pub health: u8,
+    #[character(strength)]
     strength: u8,
+    #[character(intelligent, default = 10)]
     intelligent: u8,
+    #[character(weapon, default = Box::new(Staff))]
     pub weapon: Box<dyn Weapon>,
 }
 
-impl Mage {
-    pub fn new() -> Self {
-        Self {
-            health: 100,
-            strength: 0,
-            intelligent: 10,
-            weapon: Box::new(Staff),
-        }
-    }
-}
-
-impl Character for Mage {
-    fn health_increase(&mut self, value: u8) {
-        if self.health + value > 100 {
-            self.health = 100;
-        } else {
-            self.health += value;
-        }
-    }
-
-    fn health_decrease(&mut self, value: u8) {
-        self.health = self.health.saturating_sub(value);
-    }
+impl TypeTag for Mage {
+    const TYPE_TAG: &'static str = "Mage";
 }
 
+#[derive(Clone, Serialize, Deserialize, Character)]
 pub struct Healer {
+    #[character(health, max = 100)]
     pub health: u8,
+    #[character(strength, default = 5)]
     strength: u8,
+    #[character(intelligent, default = 5)]
     intelligent: u8,
+    #[character(weapon, default = Box::new(Mace))]
     pub weapon: Box<dyn Weapon>,
 }
 
-impl Healer {
-    pub fn new() -> Self {
-        Self {
-            health: 100,
-            strength: 5,
-            intelligent: 5,
-            weapon: Box::new(Mace),
-        }
-    }
+impl TypeTag for Healer {
+    const TYPE_TAG: &'static str = "Healer";
 }
 
-impl Character for Healer {
-    fn health_increase(&mut self, value: u8) {
-        if self.health + value > 100 {
-            self.health = 100;
-        } else {
-            self.health += value;
-        }
-    }
-
-    fn health_decrease(&mut self, value: u8) {
-        self.health = self.health.saturating_sub(value);
-    }
+pub fn register_characters(registry: &mut Registry<dyn Character>) {
+    register_type!(registry, Warrior);
+    register_type!(registry, Mage);
+    register_type!(registry, Healer);
 }
 
 pub fn special_attack(weapon: Box<dyn Weapon>) {
     weapon.attack();
 }
 
+// healing is capped, whatever the potion
+#[ensures(|_| character.health() <= character.max_health())]
 pub fn health_increase<T: Character>(character:&mut T, value: u8) {
   character.health_increase(value)
 }
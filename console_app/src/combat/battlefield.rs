@@ -0,0 +1,182 @@
+use std::collections::HashMap;
+use std::fmt;
+
+use super::engine::{CombatantId, Target};
+use crate::basic_oop::weapons::Weapon;
+use crate::maps::grid::{Position, Shape, TileMap};
+use crate::skills::ability::{Ability, Targeting};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Unit {
+    pub position: Position,
+    pub action_points: u32,
+    pub max_action_points: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MoveError {
+    UnknownUnit(CombatantId),
+    Occupied(Position),
+    Unreachable(Position),
+    NotEnoughActionPoints { needed: u32, left: u32 },
+}
+
+impl fmt::Display for MoveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MoveError::UnknownUnit(id) => write!(f, "combatant {} is not on the battlefield", id),
+            MoveError::Occupied(position) => write!(f, "{:?} is occupied", position),
+            MoveError::Unreachable(position) => write!(f, "{:?} cannot be reached", position),
+            MoveError::NotEnoughActionPoints { needed, left } => {
+                write!(f, "needs {} action points, {} left", needed, left)
+            }
+        }
+    }
+}
+
+// Where every combatant stands on the tile map during tactical combat
+#[derive(Debug, Clone)]
+pub struct Battlefield {
+    pub map: TileMap,
+    units: HashMap<CombatantId, Unit>,
+}
+
+impl Battlefield {
+    pub fn new(map: TileMap) -> Self {
+        Self {
+            map,
+            units: HashMap::new(),
+        }
+    }
+
+    pub fn place(
+        &mut self,
+        id: CombatantId,
+        position: Position,
+        action_points: u32,
+    ) -> Result<(), MoveError> {
+        if self
+            .map
+            .get(position)
+            .and_then(|tile| tile.move_cost())
+            .is_none()
+        {
+            return Err(MoveError::Unreachable(position));
+        }
+        if self.occupant(position).is_some_and(|other| other != id) {
+            return Err(MoveError::Occupied(position));
+        }
+
+        self.units.insert(
+            id,
+            Unit {
+                position,
+                action_points,
+                max_action_points: action_points,
+            },
+        );
+        Ok(())
+    }
+
+    pub fn remove(&mut self, id: CombatantId) {
+        self.units.remove(&id);
+    }
+
+    pub fn unit(&self, id: CombatantId) -> Option<&Unit> {
+        self.units.get(&id)
+    }
+
+    pub fn position(&self, id: CombatantId) -> Option<Position> {
+        self.unit(id).map(|unit| unit.position)
+    }
+
+    pub fn occupant(&self, position: Position) -> Option<CombatantId> {
+        self.units
+            .iter()
+            .find(|(_, unit)| unit.position == position)
+            .map(|(id, _)| *id)
+    }
+
+    pub fn start_turn(&mut self, id: CombatantId) {
+        if let Some(unit) = self.units.get_mut(&id) {
+            unit.action_points = unit.max_action_points;
+        }
+    }
+
+    // free tiles the unit can end its move on, with their cost
+    pub fn reachable(&self, id: CombatantId) -> HashMap<Position, u32> {
+        let unit = match self.unit(id) {
+            Some(unit) => unit,
+            None => return HashMap::new(),
+        };
+
+        let mut range = self.map.movement_range(unit.position, unit.action_points);
+        range.retain(|position, _| self.occupant(*position).is_none_or(|other| other == id));
+        range
+    }
+
+    // returns the action points spent
+    pub fn move_unit(&mut self, id: CombatantId, to: Position) -> Result<u32, MoveError> {
+        let unit = *self.unit(id).ok_or(MoveError::UnknownUnit(id))?;
+        if self.occupant(to).is_some_and(|other| other != id) {
+            return Err(MoveError::Occupied(to));
+        }
+
+        // a search without the action point limit tells "too far" apart from "walled off"
+        let all_reachable = self.map.movement_range(unit.position, u32::MAX);
+        let needed = *all_reachable.get(&to).ok_or(MoveError::Unreachable(to))?;
+        if needed > unit.action_points {
+            return Err(MoveError::NotEnoughActionPoints {
+                needed,
+                left: unit.action_points,
+            });
+        }
+
+        let unit = self.units.get_mut(&id).unwrap();
+        unit.position = to;
+        unit.action_points -= needed;
+        Ok(needed)
+    }
+
+    pub fn can_see(&self, from: CombatantId, to: CombatantId) -> bool {
+        match (self.position(from), self.position(to)) {
+            (Some(from), Some(to)) => self.map.line_of_sight(from, to),
+            _ => false,
+        }
+    }
+
+    pub fn in_weapon_range(
+        &self,
+        attacker: CombatantId,
+        target: CombatantId,
+        weapon: &dyn Weapon,
+    ) -> bool {
+        match (self.position(attacker), self.position(target)) {
+            (Some(from), Some(to)) => {
+                from.distance(to) <= weapon.range() && self.map.line_of_sight(from, to)
+            }
+            _ => false,
+        }
+    }
+
+    pub fn units_in(&self, origin: Position, shape: Shape) -> Vec<CombatantId> {
+        let mut ids: Vec<CombatantId> = self
+            .map
+            .area(origin, shape)
+            .into_iter()
+            .filter_map(|position| self.occupant(position))
+            .collect();
+        ids.sort();
+        ids
+    }
+
+    // who an area ability aimed at `center` hits, Combat::cast_on resolves a Target::Area with it
+    pub fn area_target(&self, ability: &Ability, center: Position) -> Option<Target> {
+        match ability.targeting {
+            Targeting::Area { radius } => Some(Target::Group(
+                self.units_in(center, Shape::Circle { radius }),
+            )),
+            _ => None,
+        }
+    }
+}
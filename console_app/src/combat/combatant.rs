@@ -0,0 +1,122 @@
+use my_macro_lib::{invariant, requires};
+
+use super::status::{ActiveStatus, Status};
+use crate::basic_oop::characters::Character;
+use crate::skills::ability::{Cost, Learner};
+use crate::skills::catalog::{self, Unlock};
+use crate::skills::skill_book::SkillBook;
+
+// A character taking part in combat, with the resources abilities spend
+#[derive(Debug, Clone)]
+pub struct Combatant {
+    pub name: String,
+    pub level: u32,
+    pub health: u32,
+    pub max_health: u32,
+    pub mana: u32,
+    pub max_mana: u32,
+    pub stamina: u32,
+    pub max_stamina: u32,
+    pub statuses: Vec<ActiveStatus>,
+    pub skills: SkillBook,
+}
+
+impl Combatant {
+    pub fn new(name: &str, learner: Learner, health: u32, mana: u32, stamina: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            level: 1,
+            health,
+            max_health: health,
+            mana,
+            max_mana: mana,
+            stamina,
+            max_stamina: stamina,
+            statuses: Vec::new(),
+            skills: SkillBook::new(learner),
+        }
+    }
+
+    // intelligent feeds mana, strength feeds stamina,
+    // and every level 1 ability of the class is learned right away
+    pub fn from_character<T: Character>(name: &str, character: &T, catalog: &[Unlock]) -> Self {
+        let mut combatant = Self::new(
+            name,
+            Learner::Class(character.class()),
+            character.health() as u32,
+            20 + character.intelligent() as u32 * 10,
+            20 + character.strength() as u32 * 10,
+        );
+        combatant.learn_unlocked(catalog);
+        combatant
+    }
+
+    pub fn learner(&self) -> &Learner {
+        self.skills.learner()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.health > 0
+    }
+
+    pub fn has_status(&self, status: &Status) -> bool {
+        self.statuses
+            .iter()
+            .any(|active| active.is_same_kind(status))
+    }
+
+    pub fn can_afford(&self, cost: &Cost) -> bool {
+        self.mana >= cost.mana && self.stamina >= cost.stamina
+    }
+
+    // check can_afford first, the resources would wrap around below zero
+    #[requires(self.can_afford(cost))]
+    pub fn spend(&mut self, cost: &Cost) {
+        self.mana -= cost.mana;
+        self.stamina -= cost.stamina;
+    }
+
+    pub fn take_damage(&mut self, damage: u32) -> u32 {
+        let taken = damage.min(self.health);
+        self.health -= taken;
+        taken
+    }
+
+    #[invariant(self.health <= self.max_health)]
+    pub fn heal(&mut self, amount: u32) -> u32 {
+        let healed = amount.min(self.max_health - self.health);
+        self.health += healed;
+        healed
+    }
+
+    // reapplying a status refreshes its duration instead of stacking
+    pub fn apply_status(&mut self, status: Status, turns: u32) {
+        self.statuses.retain(|active| !active.is_same_kind(&status));
+        self.statuses.push(ActiveStatus::new(status, turns));
+    }
+
+    // returns the names of the abilities learned on the way up
+    pub fn level_up(&mut self, catalog: &[Unlock]) -> Vec<String> {
+        self.level += 1;
+        self.max_health += 10;
+        self.max_mana += 5;
+        self.max_stamina += 5;
+        self.health = self.max_health;
+        self.mana = self.max_mana;
+        self.stamina = self.max_stamina;
+        self.learn_unlocked(catalog)
+    }
+
+    fn learn_unlocked(&mut self, catalog: &[Unlock]) -> Vec<String> {
+        let mut learned = Vec::new();
+        for level in 1..=self.level {
+            for ability in catalog::unlocked_at(catalog, self.learner(), level) {
+                let name = ability.name.clone();
+                if self.skills.learn(ability).is_ok() {
+                    learned.push(name);
+                }
+            }
+        }
+        learned
+    }
+}
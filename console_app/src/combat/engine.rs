@@ -0,0 +1,384 @@
+use std::fmt;
+
+use super::battlefield::Battlefield;
+use super::combatant::Combatant;
+use super::status::Status;
+use crate::maps::grid::Position;
+use crate::skills::ability::{Ability, Effect, Targeting};
+use crate::skills::skill_book::SkillError;
+
+pub type CombatantId = usize;
+
+// Who the caster picked when casting
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Target {
+    Caster,
+    One(CombatantId),
+    // the combatants an area ability hits in a fight without a battlefield
+    Group(Vec<CombatantId>),
+    // where an area ability is aimed on a battlefield, it hits everyone inside its radius
+    Area(Position),
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CastError {
+    NoSuchCombatant(CombatantId),
+    CasterDown(String),
+    Stunned(String),
+    Skill(SkillError),
+    NotEnoughResources(String),
+    InvalidTarget(String),
+}
+
+impl fmt::Display for CastError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            CastError::NoSuchCombatant(id) => write!(f, "no combatant with id {}", id),
+            CastError::CasterDown(name) => write!(f, "{} is down", name),
+            CastError::Stunned(name) => write!(f, "{} is stunned", name),
+            CastError::Skill(error) => write!(f, "{}", error),
+            CastError::NotEnoughResources(name) => {
+                write!(f, "not enough resources to cast {}", name)
+            }
+            CastError::InvalidTarget(name) => write!(f, "invalid target for {}", name),
+        }
+    }
+}
+
+impl From<SkillError> for CastError {
+    fn from(error: SkillError) -> Self {
+        CastError::Skill(error)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Outcome {
+    Damaged { target: CombatantId, amount: u32 },
+    Healed { target: CombatantId, amount: u32 },
+    StatusApplied { target: CombatantId, status: Status },
+    StatusExpired { target: CombatantId, status: Status },
+    Defeated { target: CombatantId },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CastReport {
+    pub caster: CombatantId,
+    pub ability: String,
+    pub outcomes: Vec<Outcome>,
+}
+
+// Turn based combat between any number of combatants
+#[derive(Debug, Default)]
+pub struct Combat {
+    pub combatants: Vec<Combatant>,
+    pub turn: u32,
+}
+
+impl Combat {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn join(&mut self, combatant: Combatant) -> CombatantId {
+        self.combatants.push(combatant);
+        self.combatants.len() - 1
+    }
+
+    pub fn get(&self, id: CombatantId) -> Option<&Combatant> {
+        self.combatants.get(id)
+    }
+
+    pub fn cast(
+        &mut self,
+        caster: CombatantId,
+        ability_name: &str,
+        target: Target,
+    ) -> Result<CastReport, CastError> {
+        self.cast_with(None, caster, ability_name, target)
+    }
+
+    // like cast, for a fight on a battlefield, where area abilities take a Target::Area
+    pub fn cast_on(
+        &mut self,
+        field: &Battlefield,
+        caster: CombatantId,
+        ability_name: &str,
+        target: Target,
+    ) -> Result<CastReport, CastError> {
+        self.cast_with(Some(field), caster, ability_name, target)
+    }
+
+    fn cast_with(
+        &mut self,
+        field: Option<&Battlefield>,
+        caster: CombatantId,
+        ability_name: &str,
+        target: Target,
+    ) -> Result<CastReport, CastError> {
+        let combatant = self
+            .combatants
+            .get(caster)
+            .ok_or(CastError::NoSuchCombatant(caster))?;
+
+        if !combatant.is_alive() {
+            return Err(CastError::CasterDown(combatant.name.clone()));
+        }
+        if combatant.has_status(&Status::Stunned) {
+            return Err(CastError::Stunned(combatant.name.clone()));
+        }
+
+        let ability = combatant.skills.ready(ability_name)?.clone();
+        if !combatant.can_afford(&ability.cost) {
+            return Err(CastError::NotEnoughResources(ability.name));
+        }
+
+        let targets = self.resolve_targets(field, caster, &ability, target)?;
+
+        let combatant = &mut self.combatants[caster];
+        combatant.spend(&ability.cost);
+        combatant.skills.start_cooldown(&ability.name);
+
+        let mut outcomes = Vec::new();
+        for target in targets {
+            for effect in ability.effects.iter() {
+                self.apply(target, effect, &mut outcomes);
+            }
+        }
+
+        Ok(CastReport {
+            caster,
+            ability: ability.name,
+            outcomes,
+        })
+    }
+
+    // damage and heal over time, then statuses and cooldowns count down
+    pub fn end_turn(&mut self) -> Vec<Outcome> {
+        let mut outcomes = Vec::new();
+
+        for (id, combatant) in self.combatants.iter_mut().enumerate() {
+            if !combatant.is_alive() {
+                continue;
+            }
+
+            for active in combatant.statuses.clone() {
+                match active.status {
+                    Status::Poisoned { damage } => {
+                        let amount = combatant.take_damage(damage);
+                        outcomes.push(Outcome::Damaged { target: id, amount });
+                    }
+                    Status::Regenerating { heal } => {
+                        let amount = combatant.heal(heal);
+                        outcomes.push(Outcome::Healed { target: id, amount });
+                    }
+                    Status::Stunned => {}
+                }
+            }
+            if !combatant.is_alive() {
+                outcomes.push(Outcome::Defeated { target: id });
+            }
+
+            for active in combatant.statuses.iter_mut() {
+                active.turns_left = active.turns_left.saturating_sub(1);
+                if active.turns_left == 0 {
+                    outcomes.push(Outcome::StatusExpired {
+                        target: id,
+                        status: active.status,
+                    });
+                }
+            }
+            combatant.statuses.retain(|active| active.turns_left > 0);
+            combatant.skills.tick();
+        }
+
+        self.turn += 1;
+        outcomes
+    }
+
+    // every target once, in the order they were picked
+    fn resolve_targets(
+        &self,
+        field: Option<&Battlefield>,
+        caster: CombatantId,
+        ability: &Ability,
+        target: Target,
+    ) -> Result<Vec<CombatantId>, CastError> {
+        let invalid = || CastError::InvalidTarget(ability.name.clone());
+
+        let picked = match (&ability.targeting, field, target) {
+            (Targeting::SelfOnly, _, Target::Caster) => vec![caster],
+            // naming the caster by id does not get around Target::Caster being refused
+            (Targeting::Single, _, Target::One(id)) if id != caster => vec![id],
+            // without a battlefield there is no radius to hold the group to
+            (Targeting::Area { .. }, None, Target::Group(ids)) => ids,
+            (Targeting::Area { .. }, Some(field), Target::Area(center)) => {
+                match field.area_target(ability, center) {
+                    // whoever is already down in the area is passed over
+                    Some(Target::Group(ids)) => ids
+                        .into_iter()
+                        .filter(|id| self.combatants.get(*id).is_some_and(Combatant::is_alive))
+                        .collect(),
+                    _ => return Err(invalid()),
+                }
+            }
+            _ => return Err(invalid()),
+        };
+
+        let mut targets = Vec::new();
+        for id in picked {
+            if !targets.contains(&id) {
+                targets.push(id);
+            }
+        }
+
+        for id in targets.iter() {
+            match self.combatants.get(*id) {
+                Some(combatant) if combatant.is_alive() => {}
+                Some(_) => return Err(invalid()),
+                None => return Err(CastError::NoSuchCombatant(*id)),
+            }
+        }
+
+        Ok(targets)
+    }
+
+    fn apply(&mut self, target: CombatantId, effect: &Effect, outcomes: &mut Vec<Outcome>) {
+        let combatant = &mut self.combatants[target];
+        if !combatant.is_alive() {
+            return;
+        }
+
+        match *effect {
+            Effect::Damage(damage) => {
+                let amount = combatant.take_damage(damage);
+                outcomes.push(Outcome::Damaged { target, amount });
+                if !combatant.is_alive() {
+                    outcomes.push(Outcome::Defeated { target });
+                }
+            }
+            Effect::Heal(heal) => {
+                let amount = combatant.heal(heal);
+                outcomes.push(Outcome::Healed { target, amount });
+            }
+            Effect::ApplyStatus { status, turns } => {
+                combatant.apply_status(status, turns);
+                outcomes.push(Outcome::StatusApplied { target, status });
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_oop::characters::CharacterClass;
+    use crate::maps::grid::TileMap;
+    use crate::skills::ability::Learner;
+
+    // three mages with a single target bolt and an area blast of radius 1
+    fn combat() -> Combat {
+        let mut combat = Combat::new();
+        for name in ["A", "B", "C"] {
+            let mut combatant =
+                Combatant::new(name, Learner::Class(CharacterClass::Mage), 100, 100, 100);
+            let bolt = Ability::new("Bolt", Targeting::Single).effect(Effect::Damage(10));
+            let blast =
+                Ability::new("Blast", Targeting::Area { radius: 1 }).effect(Effect::Damage(10));
+            combatant.skills.learn(bolt).unwrap();
+            combatant.skills.learn(blast).unwrap();
+            combat.join(combatant);
+        }
+        combat
+    }
+
+    fn health(combat: &Combat) -> Vec<u32> {
+        combat
+            .combatants
+            .iter()
+            .map(|combatant| combatant.health)
+            .collect()
+    }
+
+    #[test]
+    fn a_group_hits_every_combatant_once() {
+        let mut combat = combat();
+        combat
+            .cast(0, "Blast", Target::Group(vec![1, 1, 2, 1]))
+            .unwrap();
+        assert_eq!(health(&combat), vec![100, 90, 90]);
+    }
+
+    #[test]
+    fn a_single_target_ability_can_not_target_the_caster() {
+        let mut combat = combat();
+        assert_eq!(
+            combat.cast(0, "Bolt", Target::Caster),
+            Err(CastError::InvalidTarget("Bolt".to_string()))
+        );
+        assert_eq!(
+            combat.cast(0, "Bolt", Target::One(0)),
+            Err(CastError::InvalidTarget("Bolt".to_string()))
+        );
+        combat.cast(0, "Bolt", Target::One(1)).unwrap();
+        assert_eq!(health(&combat), vec![100, 90, 100]);
+    }
+
+    #[test]
+    fn a_cooldown_lasts_its_full_number_of_turns() {
+        let mut combat = combat();
+        let zap = Ability::new("Zap", Targeting::Single)
+            .cooldown(1)
+            .effect(Effect::Damage(1));
+        combat.combatants[0].skills.learn(zap).unwrap();
+
+        combat.cast(0, "Zap", Target::One(1)).unwrap();
+        combat.end_turn();
+        // a cooldown of 1 sits out the next turn
+        assert_eq!(
+            combat.cast(0, "Zap", Target::One(1)),
+            Err(CastError::Skill(SkillError::OnCooldown {
+                name: "Zap".to_string(),
+                turns_left: 1
+            }))
+        );
+        combat.end_turn();
+        combat.cast(0, "Zap", Target::One(1)).unwrap();
+    }
+
+    #[test]
+    fn without_a_cooldown_an_ability_can_be_cast_again_next_turn() {
+        let mut combat = combat();
+        combat.cast(0, "Bolt", Target::One(1)).unwrap();
+        combat.end_turn();
+        combat.cast(0, "Bolt", Target::One(1)).unwrap();
+        assert_eq!(health(&combat), vec![100, 80, 100]);
+    }
+
+    #[test]
+    fn an_area_on_a_battlefield_hits_only_inside_its_radius() {
+        let mut combat = combat();
+        let mut field = Battlefield::new(TileMap::new(10, 10));
+        field.place(0, Position::new(0, 0), 4).unwrap();
+        field.place(1, Position::new(5, 5), 4).unwrap();
+        field.place(2, Position::new(8, 8), 4).unwrap();
+
+        // a group could name anyone, on a battlefield the radius decides
+        assert_eq!(
+            combat.cast_on(&field, 0, "Blast", Target::Group(vec![1, 2])),
+            Err(CastError::InvalidTarget("Blast".to_string()))
+        );
+        combat
+            .cast_on(&field, 0, "Blast", Target::Area(Position::new(5, 6)))
+            .unwrap();
+        assert_eq!(health(&combat), vec![100, 90, 100]);
+    }
+
+    #[test]
+    fn an_area_needs_a_battlefield() {
+        let mut combat = combat();
+        assert_eq!(
+            combat.cast(0, "Blast", Target::Area(Position::new(0, 0))),
+            Err(CastError::InvalidTarget("Blast".to_string()))
+        );
+    }
+}
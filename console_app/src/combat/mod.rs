@@ -0,0 +1,4 @@
+pub mod battlefield;
+pub mod combatant;
+pub mod engine;
+pub mod status;
@@ -0,0 +1,30 @@
+// Status effects applied by abilities, they last for a number of turns
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Status {
+    // cannot cast abilities while stunned
+    Stunned,
+    // lose health at the end of every turn
+    Poisoned { damage: u32 },
+    // gain health at the end of every turn
+    Regenerating { heal: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ActiveStatus {
+    pub status: Status,
+    pub turns_left: u32,
+}
+
+impl ActiveStatus {
+    pub fn new(status: Status, turns: u32) -> Self {
+        Self {
+            status,
+            turns_left: turns,
+        }
+    }
+
+    // same kind of status, ignoring its payload
+    pub fn is_same_kind(&self, status: &Status) -> bool {
+        std::mem::discriminant(&self.status) == std::mem::discriminant(status)
+    }
+}
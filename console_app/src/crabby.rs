@@ -0,0 +1,63 @@
+use my_macro_lib::StateEnum;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Crabby {
+    pub name: String,
+    pub health: u8,
+    pub state: CrabbyState,
+}
+
+impl Crabby {
+    pub fn take_damage(&mut self, damage: u8) {
+        self.health = self.health.saturating_sub(damage);
+        self.state = CrabbyState::Fighting;
+    }
+
+    // health read from a save file can be anything, healing never goes past 100
+    pub fn heal(&mut self, health: u8) {
+        self.health = self.health.saturating_add(health).min(100);
+        self.state = CrabbyState::Resting;
+    }
+
+    pub fn collecting(&mut self, amount: u32) {
+        self.state = CrabbyState::Collecting(amount);
+    }
+
+    pub fn state_represent(&self) {
+        match self.state {
+            CrabbyState::Resting => println!("Crabby is resting"),
+            CrabbyState::Fighting => println!("Crabby is fighting"),
+            CrabbyState::Collecting(amount) => {
+                println!("Crabby is collecting {} treasures", amount)
+            }
+            CrabbyState::Defending => println!("Crabby is defending"),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, StateEnum)]
+pub enum CrabbyState {
+    Resting,
+    Fighting,
+    #[display("Collecting {0}")]
+    Collecting(u32),
+    Defending,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn healing_a_loaded_crabby_caps_at_100() {
+        let mut crabby = Crabby {
+            name: "Crabby".to_string(),
+            health: 200,
+            state: CrabbyState::Fighting,
+        };
+        crabby.heal(100);
+        assert_eq!(crabby.health, 100);
+        assert_eq!(crabby.state, CrabbyState::Resting);
+    }
+}
@@ -0,0 +1,111 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EconomyError {
+    InsufficientFunds { needed: Gold, available: Gold },
+    UnknownVendor(String),
+    NotForSale { vendor: String, item: String },
+    OutOfStock { item: String, available: u32 },
+    MissingItems { item: String, owned: u32 },
+    // the ledger does not explain how an account got to its balance
+    AuditFailed { account: String, reason: String },
+    // the amount does not fit in a Gold
+    Overflow,
+}
+
+impl fmt::Display for EconomyError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EconomyError::InsufficientFunds { needed, available } => {
+                write!(f, "needs {} but only {} available", needed, available)
+            }
+            EconomyError::UnknownVendor(vendor) => write!(f, "no vendor named {}", vendor),
+            EconomyError::NotForSale { vendor, item } => {
+                write!(f, "{} does not trade {}", vendor, item)
+            }
+            EconomyError::OutOfStock { item, available } => {
+                write!(f, "only {} {} left in stock", available, item)
+            }
+            EconomyError::MissingItems { item, owned } => {
+                write!(f, "only {} {} owned", owned, item)
+            }
+            EconomyError::AuditFailed { account, reason } => {
+                write!(f, "audit of {} failed: {}", account, reason)
+            }
+            EconomyError::Overflow => write!(f, "more than {} in one place", Gold(u32::MAX)),
+        }
+    }
+}
+
+impl std::error::Error for EconomyError {}
+
+// An amount of gold that can never go negative, nor silently stop growing.
+// There are no Add or Sub impls on purpose, gold moves through `deposit` and `spend`,
+// which can fail.
+#[derive(
+    Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize,
+)]
+#[serde(transparent)]
+pub struct Gold(u32);
+
+impl Gold {
+    pub const ZERO: Gold = Gold(0);
+
+    pub const fn new(amount: u32) -> Self {
+        Gold(amount)
+    }
+
+    pub fn amount(&self) -> u32 {
+        self.0
+    }
+
+    pub fn checked_add(self, other: Gold) -> Option<Gold> {
+        self.0.checked_add(other.0).map(Gold)
+    }
+
+    pub fn checked_sub(self, other: Gold) -> Option<Gold> {
+        self.0.checked_sub(other.0).map(Gold)
+    }
+
+    // puts `amount` in, leaves the balance untouched when it would not fit
+    pub fn deposit(&mut self, amount: Gold) -> Result<(), EconomyError> {
+        *self = self.checked_add(amount).ok_or(EconomyError::Overflow)?;
+        Ok(())
+    }
+
+    // takes `amount` out, leaves the balance untouched when it is not enough
+    pub fn spend(&mut self, amount: Gold) -> Result<(), EconomyError> {
+        match self.checked_sub(amount) {
+            Some(left) => {
+                *self = left;
+                Ok(())
+            }
+            None => Err(EconomyError::InsufficientFunds {
+                needed: amount,
+                available: *self,
+            }),
+        }
+    }
+
+    // scales by a ratio in basis points, 10_000 is 100%, rounding to the nearest coin
+    pub fn scale(self, basis_points: u32) -> Gold {
+        let scaled = (self.0 as u64 * basis_points as u64 + 5_000) / 10_000;
+        Gold(scaled.min(u32::MAX as u64) as u32)
+    }
+
+    // the price of `quantity` units at this price each
+    pub fn times(self, quantity: u32) -> Result<Gold, EconomyError> {
+        self.0
+            .checked_mul(quantity)
+            .map(Gold)
+            .ok_or(EconomyError::Overflow)
+    }
+}
+
+impl fmt::Display for Gold {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}g", self.0)
+    }
+}
@@ -0,0 +1,121 @@
+use serde::{Deserialize, Serialize};
+
+use super::currency::{EconomyError, Gold};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum TransactionKind {
+    // a customer bought from a vendor
+    Purchase,
+    // a customer sold to a vendor
+    Sale,
+    // the customer, a supplier, refilled a vendor's shelves, no gold changes hands
+    Restock,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Transaction {
+    pub id: u64,
+    pub tick: u64,
+    pub kind: TransactionKind,
+    pub customer: String,
+    pub vendor: String,
+    pub item: String,
+    pub quantity: u32,
+    pub unit_price: Gold,
+    pub total: Gold,
+}
+
+// Append only record of every trade, written by the Market only
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Ledger {
+    entries: Vec<Transaction>,
+}
+
+impl Ledger {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    pub(super) fn record(
+        &mut self,
+        tick: u64,
+        kind: TransactionKind,
+        customer: &str,
+        vendor: &str,
+        item: &str,
+        quantity: u32,
+        unit_price: Gold,
+    ) -> Result<&Transaction, EconomyError> {
+        let total = unit_price.times(quantity)?;
+        self.entries.push(Transaction {
+            id: self.entries.len() as u64,
+            tick,
+            kind,
+            customer: customer.to_string(),
+            vendor: vendor.to_string(),
+            item: item.to_string(),
+            quantity,
+            unit_price,
+            total,
+        });
+        Ok(self.entries.last().unwrap())
+    }
+
+    pub fn entries(&self) -> &[Transaction] {
+        &self.entries
+    }
+
+    // every transaction a customer or a vendor took part in
+    pub fn for_account<'a>(&'a self, account: &'a str) -> impl Iterator<Item = &'a Transaction> {
+        self.entries
+            .iter()
+            .filter(move |entry| entry.customer == account || entry.vendor == account)
+    }
+
+    // gold gained minus gold spent by an account over the whole ledger
+    pub fn net_flow(&self, account: &str) -> i64 {
+        self.for_account(account)
+            .map(|entry| {
+                let total = entry.total.amount() as i64;
+                let customer_side = match entry.kind {
+                    TransactionKind::Purchase => -total,
+                    TransactionKind::Sale => total,
+                    TransactionKind::Restock => 0,
+                };
+                if entry.customer == account {
+                    customer_side
+                } else {
+                    -customer_side
+                }
+            })
+            .sum()
+    }
+
+    // Checks the ledger was not tampered with and that it explains
+    // how `account` went from `opening` to `current`
+    pub fn audit(&self, account: &str, opening: Gold, current: Gold) -> Result<(), EconomyError> {
+        let failed = |reason: String| EconomyError::AuditFailed {
+            account: account.to_string(),
+            reason,
+        };
+
+        for (index, entry) in self.entries.iter().enumerate() {
+            if entry.id != index as u64 {
+                return Err(failed(format!("entry {} is out of order", entry.id)));
+            }
+            if entry.unit_price.times(entry.quantity) != Ok(entry.total) {
+                return Err(failed(format!("entry {} total does not add up", entry.id)));
+            }
+        }
+
+        let expected = opening.amount() as i64 + self.net_flow(account);
+        if expected != current.amount() as i64 {
+            return Err(failed(format!(
+                "ledger explains {}g but the balance is {}",
+                expected, current
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,259 @@
+use std::collections::BTreeMap;
+
+use super::currency::{EconomyError, Gold};
+use super::ledger::{Ledger, Transaction, TransactionKind};
+use super::vendor::Vendor;
+use crate::inventory::Inventory;
+
+// All vendors of a town, every trade with them is written to the ledger
+#[derive(Debug, Clone, Default)]
+pub struct Market {
+    vendors: BTreeMap<String, Vendor>,
+    ledger: Ledger,
+    tick: u64,
+}
+
+impl Market {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_vendor(&mut self, vendor: Vendor) {
+        self.vendors.insert(vendor.name.clone(), vendor);
+    }
+
+    pub fn vendor(&self, name: &str) -> Option<&Vendor> {
+        self.vendors.get(name)
+    }
+
+    pub fn ledger(&self) -> &Ledger {
+        &self.ledger
+    }
+
+    pub fn current_tick(&self) -> u64 {
+        self.tick
+    }
+
+    // the customer pays the vendor's sell price for every unit
+    pub fn buy(
+        &mut self,
+        customer: &str,
+        purse: &mut Inventory,
+        vendor_name: &str,
+        item: &str,
+        quantity: u32,
+    ) -> Result<Transaction, EconomyError> {
+        let vendor = self
+            .vendors
+            .get_mut(vendor_name)
+            .ok_or_else(|| EconomyError::UnknownVendor(vendor_name.to_string()))?;
+        let unit_price = vendor
+            .sell_price(item)
+            .ok_or_else(|| EconomyError::NotForSale {
+                vendor: vendor_name.to_string(),
+                item: item.to_string(),
+            })?;
+
+        let stock = vendor.stock.get_mut(item).unwrap();
+        if stock.quantity < quantity {
+            return Err(EconomyError::OutOfStock {
+                item: item.to_string(),
+                available: stock.quantity,
+            });
+        }
+
+        // nothing moves unless both sides of the trade can take it
+        let total = unit_price.times(quantity)?;
+        let vendor_gold = vendor
+            .gold
+            .checked_add(total)
+            .ok_or(EconomyError::Overflow)?;
+        purse.gold.spend(total)?;
+        vendor.gold = vendor_gold;
+        stock.quantity -= quantity;
+        stock.sold_recently = stock.sold_recently.saturating_add(quantity);
+        purse.add(item, quantity);
+
+        Ok(self
+            .ledger
+            .record(
+                self.tick,
+                TransactionKind::Purchase,
+                customer,
+                vendor_name,
+                item,
+                quantity,
+                unit_price,
+            )?
+            .clone())
+    }
+
+    // the vendor pays its buy price, as long as it has the gold for it
+    pub fn sell(
+        &mut self,
+        customer: &str,
+        purse: &mut Inventory,
+        vendor_name: &str,
+        item: &str,
+        quantity: u32,
+    ) -> Result<Transaction, EconomyError> {
+        let vendor = self
+            .vendors
+            .get_mut(vendor_name)
+            .ok_or_else(|| EconomyError::UnknownVendor(vendor_name.to_string()))?;
+        let unit_price = vendor
+            .buy_price(item)
+            .ok_or_else(|| EconomyError::NotForSale {
+                vendor: vendor_name.to_string(),
+                item: item.to_string(),
+            })?;
+
+        let owned = purse.count(item);
+        if owned < quantity {
+            return Err(EconomyError::MissingItems {
+                item: item.to_string(),
+                owned,
+            });
+        }
+
+        let total = unit_price.times(quantity)?;
+        let purse_gold = purse
+            .gold
+            .checked_add(total)
+            .ok_or(EconomyError::Overflow)?;
+        let stock = vendor.stock.get_mut(item).unwrap();
+        let vendor_quantity = stock
+            .quantity
+            .checked_add(quantity)
+            .ok_or(EconomyError::Overflow)?;
+        vendor.gold.spend(total)?;
+        purse.gold = purse_gold;
+        purse.remove(item, quantity);
+        stock.quantity = vendor_quantity;
+
+        Ok(self
+            .ledger
+            .record(
+                self.tick,
+                TransactionKind::Sale,
+                customer,
+                vendor_name,
+                item,
+                quantity,
+                unit_price,
+            )?
+            .clone())
+    }
+
+    // advances restock timers, refills are logged like any other transaction
+    // with the vendor's supplier on the other side
+    pub fn tick(&mut self) {
+        self.tick += 1;
+        for vendor in self.vendors.values_mut() {
+            for (item, quantity) in vendor.tick() {
+                self.ledger
+                    .record(
+                        self.tick,
+                        TransactionKind::Restock,
+                        &vendor.supplier,
+                        &vendor.name,
+                        &item,
+                        quantity,
+                        Gold::ZERO,
+                    )
+                    .expect("a restock is free");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn a_trade_that_would_overflow_moves_nothing() {
+        let mut market = Market::new();
+        market.add_vendor(Vendor::new("Alchemist", Gold::new(u32::MAX), 4).with_item(
+            "potion",
+            5,
+            Gold::new(10),
+        ));
+        let mut purse = Inventory::new(Gold::new(1000));
+
+        let result = market.buy("Hero", &mut purse, "Alchemist", "potion", 1);
+
+        assert_eq!(result, Err(EconomyError::Overflow));
+        assert_eq!(purse.gold, Gold::new(1000));
+        assert_eq!(purse.count("potion"), 0);
+        assert_eq!(
+            market.vendor("Alchemist").unwrap().gold,
+            Gold::new(u32::MAX)
+        );
+        assert!(market.ledger().entries().is_empty());
+    }
+
+    #[test]
+    fn a_huge_stock_prices_and_trades_without_overflowing() {
+        let mut market = Market::new();
+        market.add_vendor(Vendor::new("Guild", Gold::new(u32::MAX / 2), 4).with_item(
+            "arrow",
+            u32::MAX - 10,
+            Gold::new(0),
+        ));
+        let vendor = market.vendor("Guild").unwrap();
+        // a full shelf and no demand yet
+        assert_eq!(vendor.market_factor("arrow"), 10_000);
+
+        let mut purse = Inventory::new(Gold::new(0));
+        market
+            .buy("Hero", &mut purse, "Guild", "arrow", u32::MAX - 20)
+            .unwrap();
+        market.buy("Hero", &mut purse, "Guild", "arrow", 5).unwrap();
+        let stock = &market.vendor("Guild").unwrap().stock["arrow"];
+        assert_eq!(stock.sold_recently, u32::MAX - 15);
+        // an almost empty shelf and as much demand as counts
+        assert_eq!(
+            market.vendor("Guild").unwrap().market_factor("arrow"),
+            20_000
+        );
+
+        // the vendor can not take more than it can count
+        market.add_vendor(Vendor::new("Fletcher", Gold::new(0), 4).with_item(
+            "arrow",
+            u32::MAX,
+            Gold::new(0),
+        ));
+        purse.add("arrow", 1);
+        assert_eq!(
+            market.sell("Hero", &mut purse, "Fletcher", "arrow", 1),
+            Err(EconomyError::Overflow)
+        );
+        assert_eq!(
+            market.vendor("Fletcher").unwrap().stock["arrow"].quantity,
+            u32::MAX
+        );
+    }
+
+    #[test]
+    fn restocks_are_logged_against_the_supplier() {
+        let mut market = Market::new();
+        market.add_vendor(
+            Vendor::new("Alchemist", Gold::new(100), 1)
+                .with_supplier("Herbalists")
+                .with_item("potion", 5, Gold::new(10)),
+        );
+        let mut purse = Inventory::new(Gold::new(1000));
+        market
+            .buy("Hero", &mut purse, "Alchemist", "potion", 2)
+            .unwrap();
+
+        market.tick();
+
+        let restock = market.ledger().entries().last().unwrap();
+        assert_eq!(restock.kind, TransactionKind::Restock);
+        assert_eq!(restock.customer, "Herbalists");
+        assert_eq!(restock.vendor, "Alchemist");
+        assert_eq!(restock.total, Gold::ZERO);
+    }
+}
@@ -0,0 +1,5 @@
+pub mod currency;
+pub mod ledger;
+pub mod market;
+pub mod treasury;
+pub mod vendor;
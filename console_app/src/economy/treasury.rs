@@ -0,0 +1,263 @@
+use std::collections::HashMap;
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+
+use super::currency::{EconomyError, Gold};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Movement {
+    Deposit,
+    Withdrawal,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasuryEntry {
+    // order in which the treasury applied the movements
+    pub sequence: u64,
+    // the thread that asked for it
+    pub thread: ThreadId,
+    pub movement: Movement,
+    pub amount: Gold,
+    pub balance_after: Gold,
+}
+
+// in gold, over the whole history they can outgrow what a Gold holds
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ThreadTotals {
+    pub deposited: u64,
+    pub withdrawn: u64,
+    pub movements: u64,
+}
+
+// Balance, history and per thread totals taken at one single point in time
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TreasurySnapshot {
+    pub opening: Gold,
+    pub balance: Gold,
+    pub reserve: Gold,
+    pub entries: Vec<TreasuryEntry>,
+    pub by_thread: HashMap<ThreadId, ThreadTotals>,
+}
+
+impl TreasurySnapshot {
+    // opening + deposits - withdrawals must equal the balance
+    pub fn is_consistent(&self) -> bool {
+        let deposited: u64 = self.totals().map(|totals| totals.deposited).sum();
+        let withdrawn: u64 = self.totals().map(|totals| totals.withdrawn).sum();
+        self.opening.amount() as u64 + deposited == self.balance.amount() as u64 + withdrawn
+    }
+
+    fn totals(&self) -> impl Iterator<Item = &ThreadTotals> {
+        self.by_thread.values()
+    }
+}
+
+#[derive(Debug)]
+struct State {
+    balance: Gold,
+    entries: Vec<TreasuryEntry>,
+    by_thread: HashMap<ThreadId, ThreadTotals>,
+}
+
+// Gold shared between threads, e.g. behind an Arc.
+// Every movement updates the balance and the history under one lock,
+// so no thread ever sees one without the other.
+#[derive(Debug)]
+pub struct Treasury {
+    opening: Gold,
+    // withdrawals may not take the balance below this
+    reserve: Gold,
+    state: Mutex<State>,
+}
+
+impl Treasury {
+    pub fn new(opening: Gold) -> Self {
+        Self::with_reserve(opening, Gold::ZERO)
+    }
+
+    pub fn with_reserve(opening: Gold, reserve: Gold) -> Self {
+        Self {
+            opening,
+            reserve,
+            state: Mutex::new(State {
+                balance: opening,
+                entries: Vec::new(),
+                by_thread: HashMap::new(),
+            }),
+        }
+    }
+
+    // returns the balance right after this deposit,
+    // fails without touching the balance when it would not fit
+    pub fn deposit(&self, amount: Gold) -> Result<Gold, EconomyError> {
+        let mut state = self.state.lock().unwrap();
+        state.balance.deposit(amount)?;
+        Self::record(&mut state, Movement::Deposit, amount);
+        Ok(state.balance)
+    }
+
+    // fails without touching the balance when it would dip into the reserve
+    pub fn withdraw(&self, amount: Gold) -> Result<Gold, EconomyError> {
+        let mut state = self.state.lock().unwrap();
+        let available = state
+            .balance
+            .checked_sub(self.reserve)
+            .unwrap_or(Gold::ZERO);
+        if amount > available {
+            return Err(EconomyError::InsufficientFunds {
+                needed: amount,
+                available,
+            });
+        }
+
+        state.balance.spend(amount)?;
+        Self::record(&mut state, Movement::Withdrawal, amount);
+        Ok(state.balance)
+    }
+
+    pub fn balance(&self) -> Gold {
+        self.state.lock().unwrap().balance
+    }
+
+    pub fn snapshot(&self) -> TreasurySnapshot {
+        let state = self.state.lock().unwrap();
+        TreasurySnapshot {
+            opening: self.opening,
+            balance: state.balance,
+            reserve: self.reserve,
+            entries: state.entries.clone(),
+            by_thread: state.by_thread.clone(),
+        }
+    }
+
+    fn record(state: &mut State, movement: Movement, amount: Gold) {
+        let thread = thread::current().id();
+        let totals = state.by_thread.entry(thread).or_default();
+        match movement {
+            Movement::Deposit => totals.deposited += amount.amount() as u64,
+            Movement::Withdrawal => totals.withdrawn += amount.amount() as u64,
+        }
+        totals.movements += 1;
+
+        let entry = TreasuryEntry {
+            sequence: state.entries.len() as u64,
+            thread,
+            movement,
+            amount,
+            balance_after: state.balance,
+        };
+        state.entries.push(entry);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Arc;
+
+    use super::*;
+
+    const THREADS: u32 = 8;
+    const ROUNDS: u32 = 500;
+
+    // every thread alternates deposits of 3g and withdrawals of 5g,
+    // and keeps its own count of what went through
+    fn run_workers(treasury: &Arc<Treasury>) -> HashMap<ThreadId, ThreadTotals> {
+        let handles: Vec<_> = (0..THREADS)
+            .map(|worker| {
+                let treasury = Arc::clone(treasury);
+                thread::spawn(move || {
+                    let mut totals = ThreadTotals::default();
+                    for round in 0..ROUNDS {
+                        if (worker + round) % 2 == 0 {
+                            treasury.deposit(Gold::new(3)).unwrap();
+                            totals.deposited += 3;
+                            totals.movements += 1;
+                        } else if treasury.withdraw(Gold::new(5)).is_ok() {
+                            totals.withdrawn += 5;
+                            totals.movements += 1;
+                        }
+                    }
+                    (thread::current().id(), totals)
+                })
+            })
+            .collect();
+
+        handles
+            .into_iter()
+            .map(|handle| handle.join().unwrap())
+            .collect()
+    }
+
+    #[test]
+    fn the_final_balance_adds_up_after_concurrent_movements() {
+        let treasury = Arc::new(Treasury::with_reserve(Gold::new(100), Gold::new(50)));
+        let expected = run_workers(&treasury);
+
+        let deposited: u64 = expected.values().map(|totals| totals.deposited).sum();
+        let withdrawn: u64 = expected.values().map(|totals| totals.withdrawn).sum();
+        let snapshot = treasury.snapshot();
+        assert_eq!(
+            snapshot.balance.amount() as u64,
+            100 + deposited - withdrawn
+        );
+        assert!(snapshot.is_consistent());
+    }
+
+    #[test]
+    fn every_thread_is_credited_with_its_own_movements() {
+        let treasury = Arc::new(Treasury::new(Gold::new(100)));
+        let expected = run_workers(&treasury);
+
+        let snapshot = treasury.snapshot();
+        assert_eq!(snapshot.by_thread, expected);
+        let movements: u64 = expected.values().map(|totals| totals.movements).sum();
+        assert_eq!(snapshot.entries.len() as u64, movements);
+        for (sequence, entry) in snapshot.entries.iter().enumerate() {
+            assert_eq!(entry.sequence, sequence as u64);
+        }
+    }
+
+    #[test]
+    fn withdrawals_never_dip_into_the_reserve() {
+        let reserve = Gold::new(50);
+        let treasury = Arc::new(Treasury::with_reserve(Gold::new(60), reserve));
+
+        // snapshots taken while the workers run must add up and respect the reserve too
+        let auditor = {
+            let treasury = Arc::clone(&treasury);
+            thread::spawn(move || {
+                (0..200)
+                    .map(|_| treasury.snapshot())
+                    .all(|snapshot| snapshot.is_consistent() && snapshot.balance >= reserve)
+            })
+        };
+        run_workers(&treasury);
+
+        assert!(auditor.join().unwrap());
+        let snapshot = treasury.snapshot();
+        assert!(snapshot
+            .entries
+            .iter()
+            .all(|entry| entry.balance_after >= reserve));
+        assert!(snapshot.balance >= reserve);
+    }
+
+    #[test]
+    fn a_refused_withdrawal_leaves_no_trace() {
+        let treasury = Treasury::with_reserve(Gold::new(60), Gold::new(50));
+
+        let refused = treasury.withdraw(Gold::new(20));
+
+        assert_eq!(
+            refused,
+            Err(EconomyError::InsufficientFunds {
+                needed: Gold::new(20),
+                available: Gold::new(10),
+            })
+        );
+        let snapshot = treasury.snapshot();
+        assert_eq!(snapshot.balance, Gold::new(60));
+        assert!(snapshot.entries.is_empty());
+        assert!(snapshot.by_thread.is_empty());
+    }
+}
@@ -0,0 +1,122 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use super::currency::Gold;
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Stock {
+    pub quantity: u32,
+    // restocking refills up to this amount
+    pub capacity: u32,
+    pub base_price: Gold,
+    // units traded since the last restock, recent demand pushes prices up
+    pub sold_recently: u32,
+}
+
+const DEFAULT_SUPPLIER: &str = "Merchant Guild";
+
+// A shop keeper with limited stock and a purse of their own
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Vendor {
+    pub name: String,
+    pub gold: Gold,
+    pub stock: BTreeMap<String, Stock>,
+    // the vendor sells above and buys below the market price, in basis points
+    pub spread: u32,
+    // ticks between two restocks
+    pub restock_every: u32,
+    pub ticks_until_restock: u32,
+    // who refills the shelves, named in the ledger for every restock
+    pub supplier: String,
+}
+
+impl Vendor {
+    pub fn new(name: &str, gold: Gold, restock_every: u32) -> Self {
+        Self {
+            name: name.to_string(),
+            gold,
+            stock: BTreeMap::new(),
+            spread: 2_000,
+            restock_every,
+            ticks_until_restock: restock_every,
+            supplier: DEFAULT_SUPPLIER.to_string(),
+        }
+    }
+
+    pub fn with_supplier(mut self, supplier: &str) -> Self {
+        self.supplier = supplier.to_string();
+        self
+    }
+
+    pub fn with_item(mut self, item: &str, quantity: u32, base_price: Gold) -> Self {
+        self.stock.insert(
+            item.to_string(),
+            Stock {
+                quantity,
+                capacity: quantity,
+                base_price,
+                sold_recently: 0,
+            },
+        );
+        self
+    }
+
+    pub fn trades(&self, item: &str) -> bool {
+        self.stock.contains_key(item)
+    }
+
+    // Supply and demand in basis points: an empty shelf costs up to 50% more than a full one,
+    // and every unit sold since the last restock adds 5%, capped at +50%
+    pub fn market_factor(&self, item: &str) -> u32 {
+        let stock = match self.stock.get(item) {
+            Some(stock) => stock,
+            None => return 10_000,
+        };
+
+        // in u64 so a huge shelf does not overflow on the way
+        let filled = (5_000 * u64::from(stock.quantity.min(stock.capacity)))
+            .checked_div(u64::from(stock.capacity))
+            .unwrap_or(0) as u32;
+        let scarcity = 5_000 - filled;
+        let demand = stock.sold_recently.saturating_mul(500).min(5_000);
+        10_000 + scarcity + demand
+    }
+
+    // what a customer pays the vendor for one unit
+    pub fn sell_price(&self, item: &str) -> Option<Gold> {
+        let stock = self.stock.get(item)?;
+        let market = stock.base_price.scale(self.market_factor(item));
+        Some(market.scale(10_000 + self.spread))
+    }
+
+    // what the vendor pays a customer for one unit
+    pub fn buy_price(&self, item: &str) -> Option<Gold> {
+        let stock = self.stock.get(item)?;
+        let market = stock.base_price.scale(self.market_factor(item));
+        Some(market.scale(10_000 - self.spread.min(10_000)))
+    }
+
+    // counts down the restock timer, returns the items refilled when it runs out
+    pub fn tick(&mut self) -> Vec<(String, u32)> {
+        self.ticks_until_restock = self.ticks_until_restock.saturating_sub(1);
+        if self.ticks_until_restock > 0 {
+            return Vec::new();
+        }
+
+        self.ticks_until_restock = self.restock_every;
+        self.restock()
+    }
+
+    pub fn restock(&mut self) -> Vec<(String, u32)> {
+        let mut refilled = Vec::new();
+        for (item, stock) in self.stock.iter_mut() {
+            stock.sold_recently = 0;
+            if stock.quantity < stock.capacity {
+                refilled.push((item.clone(), stock.capacity - stock.quantity));
+                stock.quantity = stock.capacity;
+            }
+        }
+        refilled
+    }
+}
@@ -0,0 +1,314 @@
+use std::collections::VecDeque;
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, ThreadId};
+
+use super::event::{GameEvent, Topic};
+
+pub type SubscriptionId = u64;
+
+type Call = Box<dyn FnMut(&GameEvent) + Send>;
+
+// An in-thread handler, callable from any thread but by one at a time
+struct Handler {
+    call: Mutex<Call>,
+    // the thread inside `call` right now, if any
+    running_on: Mutex<Option<ThreadId>>,
+    // events published by `call` itself for itself, handled once it returns
+    deferred: Mutex<VecDeque<GameEvent>>,
+}
+
+impl Handler {
+    fn new<F>(call: F) -> Self
+    where
+        F: FnMut(&GameEvent) + Send + 'static,
+    {
+        Self {
+            call: Mutex::new(Box::new(call)),
+            running_on: Mutex::new(None),
+            deferred: Mutex::new(VecDeque::new()),
+        }
+    }
+
+    // false when the event was deferred: the handler is already running on this thread,
+    // waiting for `call` would never end
+    fn deliver(&self, event: &GameEvent) -> bool {
+        let current = thread::current().id();
+        if *self.running_on.lock().unwrap() == Some(current) {
+            self.deferred.lock().unwrap().push_back(event.clone());
+            return false;
+        }
+
+        // another thread may be running it, that one is waited for
+        let mut call = self.call.lock().unwrap();
+        *self.running_on.lock().unwrap() = Some(current);
+        call(event);
+        loop {
+            let next = self.deferred.lock().unwrap().pop_front();
+            match next {
+                Some(event) => call(&event),
+                None => break,
+            }
+        }
+        *self.running_on.lock().unwrap() = None;
+        true
+    }
+}
+
+enum Delivery {
+    // called by the publishing thread, before `publish` returns
+    InThread(Arc<Handler>),
+    // handed to another thread through a bounded queue
+    Queue {
+        sender: SyncSender<GameEvent>,
+        dropped: u64,
+    },
+}
+
+struct Subscriber {
+    id: SubscriptionId,
+    topics: Vec<Topic>,
+    delivery: Delivery,
+}
+
+impl Subscriber {
+    fn wants(&self, topic: Topic) -> bool {
+        self.topics.contains(&topic)
+    }
+}
+
+// The receiving end of a queued subscription, meant to be moved to a subscriber thread.
+// It stops yielding events once the subscription is removed from the bus.
+#[derive(Debug)]
+pub struct Subscription {
+    id: SubscriptionId,
+    receiver: Receiver<GameEvent>,
+}
+
+impl Subscription {
+    pub fn id(&self) -> SubscriptionId {
+        self.id
+    }
+
+    // waits for the next event, None once the bus let go of this subscription
+    pub fn recv(&self) -> Option<GameEvent> {
+        self.receiver.recv().ok()
+    }
+
+    // an event that is already queued, if any
+    pub fn try_recv(&self) -> Option<GameEvent> {
+        self.receiver.try_recv().ok()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = GameEvent> + '_ {
+        self.receiver.iter()
+    }
+}
+
+// What happened to a single published event
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct PublishReport {
+    pub handled: usize,
+    pub queued: usize,
+    // queues that were full, those subscribers miss this event
+    pub dropped: usize,
+    // handlers that published this event themselves, they get it once they return
+    pub deferred: usize,
+}
+
+// Publish/subscribe between the parts of the game that should not know each other.
+// Share it behind an Arc, any thread can publish and subscribe.
+// In-thread handlers may publish follow-up events, one that reaches the very same handler
+// again is handled after the current one, not in the middle of it.
+#[derive(Default)]
+pub struct EventBus {
+    state: Mutex<State>,
+}
+
+#[derive(Default)]
+struct State {
+    next_id: SubscriptionId,
+    subscribers: Vec<Subscriber>,
+}
+
+impl EventBus {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn subscribe<F>(&self, topics: &[Topic], handler: F) -> SubscriptionId
+    where
+        F: FnMut(&GameEvent) + Send + 'static,
+    {
+        self.add(topics, Delivery::InThread(Arc::new(Handler::new(handler))))
+    }
+
+    // events beyond `capacity` waiting in the queue are dropped, never blocking the publisher
+    pub fn subscribe_queue(&self, topics: &[Topic], capacity: usize) -> Subscription {
+        let (sender, receiver) = mpsc::sync_channel(capacity);
+        let id = self.add(topics, Delivery::Queue { sender, dropped: 0 });
+        Subscription { id, receiver }
+    }
+
+    pub fn unsubscribe(&self, id: SubscriptionId) -> bool {
+        let mut state = self.state.lock().unwrap();
+        let before = state.subscribers.len();
+        state.subscribers.retain(|subscriber| subscriber.id != id);
+        state.subscribers.len() < before
+    }
+
+    // events a queued subscriber missed because its queue was full
+    pub fn dropped(&self, id: SubscriptionId) -> Option<u64> {
+        let state = self.state.lock().unwrap();
+        state
+            .subscribers
+            .iter()
+            .find(|subscriber| subscriber.id == id)
+            .and_then(|subscriber| match subscriber.delivery {
+                Delivery::Queue { dropped, .. } => Some(dropped),
+                Delivery::InThread(_) => None,
+            })
+    }
+
+    pub fn subscriber_count(&self) -> usize {
+        self.state.lock().unwrap().subscribers.len()
+    }
+
+    pub fn publish(&self, event: GameEvent) -> PublishReport {
+        let topic = event.topic();
+        let mut report = PublishReport::default();
+        let mut handlers = Vec::new();
+
+        {
+            let mut state = self.state.lock().unwrap();
+            // subscribers whose thread went away are forgotten
+            state.subscribers.retain_mut(|subscriber| {
+                if !subscriber.wants(topic) {
+                    return true;
+                }
+
+                match &mut subscriber.delivery {
+                    Delivery::InThread(handler) => {
+                        handlers.push(Arc::clone(handler));
+                        true
+                    }
+                    Delivery::Queue { sender, dropped } => match sender.try_send(event.clone()) {
+                        Ok(()) => {
+                            report.queued += 1;
+                            true
+                        }
+                        Err(TrySendError::Full(_)) => {
+                            *dropped += 1;
+                            report.dropped += 1;
+                            true
+                        }
+                        Err(TrySendError::Disconnected(_)) => false,
+                    },
+                }
+            });
+        }
+
+        // the bus is unlocked again, so handlers are free to publish
+        for handler in handlers {
+            if handler.deliver(&event) {
+                report.handled += 1;
+            } else {
+                report.deferred += 1;
+            }
+        }
+
+        report
+    }
+
+    // removes every subscriber, subscriber threads see the end of their queue
+    pub fn close(&self) {
+        self.state.lock().unwrap().subscribers.clear();
+    }
+
+    fn add(&self, topics: &[Topic], delivery: Delivery) -> SubscriptionId {
+        let mut state = self.state.lock().unwrap();
+        let id = state.next_id;
+        state.next_id += 1;
+        state.subscribers.push(Subscriber {
+            id,
+            topics: topics.to_vec(),
+            delivery,
+        });
+        id
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn hit(damage: u32) -> GameEvent {
+        GameEvent::CombatHit {
+            attacker: "Warrior".to_string(),
+            target: "Wolf".to_string(),
+            damage,
+        }
+    }
+
+    #[test]
+    fn a_handler_publishing_to_itself_gets_the_event_after_returning() {
+        let bus = Arc::new(EventBus::new());
+        let seen = Arc::new(Mutex::new(Vec::new()));
+        bus.subscribe(&[Topic::Combat], {
+            let bus = Arc::downgrade(&bus);
+            let seen = Arc::clone(&seen);
+            move |event| {
+                let damage = match event {
+                    GameEvent::CombatHit { damage, .. } => *damage,
+                    _ => return,
+                };
+                seen.lock().unwrap().push(format!("start {}", damage));
+                if damage > 1 {
+                    let report = bus.upgrade().unwrap().publish(hit(damage - 1));
+                    assert_eq!(report.deferred, 1);
+                    assert_eq!(report.handled, 0);
+                }
+                seen.lock().unwrap().push(format!("end {}", damage));
+            }
+        });
+
+        let report = bus.publish(hit(3));
+
+        assert_eq!(report.handled, 1);
+        assert_eq!(
+            *seen.lock().unwrap(),
+            vec!["start 3", "end 3", "start 2", "end 2", "start 1", "end 1"]
+        );
+    }
+
+    #[test]
+    fn a_follow_up_event_still_reaches_the_other_handlers_right_away() {
+        let bus = Arc::new(EventBus::new());
+        let quests = Arc::new(Mutex::new(0));
+        bus.subscribe(&[Topic::Quest], {
+            let quests = Arc::clone(&quests);
+            move |_| *quests.lock().unwrap() += 1
+        });
+        bus.subscribe(&[Topic::Loot], {
+            let bus = Arc::downgrade(&bus);
+            let quests = Arc::clone(&quests);
+            move |_| {
+                bus.upgrade().unwrap().publish(GameEvent::QuestProgressed {
+                    quest: "Pelts for the Tanner".to_string(),
+                    stage: 1,
+                    stages: 3,
+                });
+                assert_eq!(*quests.lock().unwrap(), 1);
+            }
+        });
+
+        let report = bus.publish(GameEvent::ItemLooted {
+            by: "Warrior".to_string(),
+            item: "Wolf Pelt".to_string(),
+            quantity: 1,
+        });
+
+        assert_eq!(report.handled, 1);
+        assert_eq!(report.deferred, 0);
+    }
+}
@@ -0,0 +1,49 @@
+use serde::{Deserialize, Serialize};
+
+// What a subscriber listens to, every event belongs to exactly one topic
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub enum Topic {
+    Combat,
+    Loot,
+    Quest,
+    Progression,
+}
+
+impl Topic {
+    pub const ALL: &'static [Topic] =
+        &[Topic::Combat, Topic::Loot, Topic::Quest, Topic::Progression];
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum GameEvent {
+    CombatHit {
+        attacker: String,
+        target: String,
+        damage: u32,
+    },
+    ItemLooted {
+        by: String,
+        item: String,
+        quantity: u32,
+    },
+    QuestProgressed {
+        quest: String,
+        stage: u32,
+        stages: u32,
+    },
+    LevelUp {
+        name: String,
+        level: u32,
+    },
+}
+
+impl GameEvent {
+    pub fn topic(&self) -> Topic {
+        match self {
+            GameEvent::CombatHit { .. } => Topic::Combat,
+            GameEvent::ItemLooted { .. } => Topic::Loot,
+            GameEvent::QuestProgressed { .. } => Topic::Quest,
+            GameEvent::LevelUp { .. } => Topic::Progression,
+        }
+    }
+}
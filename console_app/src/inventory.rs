@@ -0,0 +1,43 @@
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::economy::currency::Gold;
+
+// Gold and stackable items carried by the party
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Inventory {
+    pub gold: Gold,
+    pub items: BTreeMap<String, u32>,
+}
+
+impl Inventory {
+    pub fn new(gold: Gold) -> Self {
+        Self {
+            gold,
+            items: BTreeMap::new(),
+        }
+    }
+
+    pub fn add(&mut self, item: &str, count: u32) {
+        *self.items.entry(item.to_string()).or_insert(0) += count;
+    }
+
+    // returns false and leaves the inventory untouched when there are not enough items
+    pub fn remove(&mut self, item: &str, count: u32) -> bool {
+        match self.items.get_mut(item) {
+            Some(owned) if *owned >= count => {
+                *owned -= count;
+                if *owned == 0 {
+                    self.items.remove(item);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    pub fn count(&self, item: &str) -> u32 {
+        self.items.get(item).copied().unwrap_or(0)
+    }
+}
@@ -1,3 +1,7 @@
+// items! and spell! name what they generate by its full path, ::rust_101::...,
+// this makes those paths work inside the crate as well
+extern crate self as rust_101;
+
 pub mod potions {
   pub fn use_item() {
       println!("Use a potion");
@@ -15,3 +19,14 @@ pub mod pets;
 pub mod calculator;
 pub mod calculator_2;
 pub mod basic_oop;
+pub mod skills;
+pub mod combat;
+pub mod crabby;
+pub mod inventory;
+pub mod save;
+pub mod registry;
+pub mod maps;
+pub mod economy;
+pub mod workers;
+pub mod events;
+pub mod simulation;
@@ -1,29 +1,76 @@
-use std::cell::RefCell;
+// the samples spell things out step by step on purpose
+#![allow(
+    clippy::vec_init_then_push,
+    clippy::unnecessary_lazy_evaluations,
+    clippy::type_complexity,
+    clippy::useless_vec
+)]
+
+use std::cell::{Cell, RefCell};
 use std::collections::HashMap;
-use std::ops::Bound;
+use std::future::Future;
+use std::pin::{pin, Pin};
 use std::rc::Rc;
-use std::sync::mpsc::SyncSender;
-use std::sync::{mpsc, Arc, Mutex};
-use std::thread::ThreadId;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll, Wake, Waker};
+use std::time::{Duration, Instant};
 use std::vec;
 use std::{fmt, thread};
 
-use my_macro_lib::compute_time;
+use my_macro_lib::{compute_time, memoize, retry, spell, trace};
+use my_macro_runtime::memoize as memo;
+use my_macro_runtime::retry::Attempt;
+use my_macro_runtime::timing::metrics::{self, ReportFormat};
+use my_macro_runtime::timing::sink::{self as timing_sink, MemorySink};
+use my_macro_runtime::timing::spans;
+use my_macro_runtime::timing::timer::flush_all;
 use rust_101::armors::use_item::use_item;
 use rust_101::calculator::add::add;
+use rust_101::calculator_2::mul::mul;
 use rust_101::calculator_2::power::power;
+use rust_101::crabby::{Crabby, CrabbyState};
+use rust_101::economy::currency::Gold;
+use rust_101::economy::market::Market;
+use rust_101::economy::treasury::Treasury;
+use rust_101::economy::vendor::Vendor;
+use rust_101::events::bus::EventBus;
+use rust_101::events::event::{GameEvent, Topic};
+use rust_101::inventory;
+use rust_101::maps;
+use rust_101::maps::grid::{Position, Shape, TileMap};
+use rust_101::maps::pathfinding::{self, Knowledge};
+use rust_101::maps::world::World;
+use rust_101::pets::companion::{Command, Companion};
 use rust_101::pets::dogs::dog;
+use rust_101::pets::species::all_species;
 use rust_101::potions::drop_item;
+use rust_101::workers::pool::WorkerPool;
 use rust_101::{potions, weapons};
 // the name `use_item` is defined multiple times
 // `use_item` must be defined only once in the type namespace of this module
 // use rust_101::shields::use_item::use_item;
 
 use rust_101::basic_oop::characters::{
-    health_decrease, health_increase, special_attack, Healer, Mage, Warrior,
+    health_decrease, health_increase, special_attack, Character, Healer, Mage, Warrior,
 };
-use rust_101::basic_oop::quests::{Email, Pigeon, QuestManager, QuestNotifier};
+use rust_101::basic_oop::quests::{DeliveryError, Email, Pigeon, QuestManager, QuestNotifier};
+use rust_101::basic_oop::quests::{Objective, Quest, QuestLog};
+use rust_101::basic_oop::weapons as oop_weapons;
+use rust_101::combat::battlefield::Battlefield;
+use rust_101::combat::combatant::Combatant;
+use rust_101::combat::engine::{Combat, Target};
+use rust_101::register_type;
+use rust_101::registry::TypeTag;
+use rust_101::save::format::{self, SaveFormat};
+use rust_101::save::game_state::{CharacterRecord, GameState};
+use rust_101::save::legacy::{self, GameStateV1};
+use rust_101::shields::shield::{Shield, WoodShield};
 use rust_101::shields::use_item;
+use rust_101::simulation::clock::VirtualClock;
+use rust_101::simulation::game_loop::GameLoop;
+use rust_101::simulation::scheduler::Repeat;
+use rust_101::skills::ability::Ability;
+use rust_101::skills::catalog;
 
 // need to declare before use
 macro_rules! greeting_by_macro {
@@ -32,17 +79,8 @@ macro_rules! greeting_by_macro {
     };
 }
 
-macro_rules! magic_spelling {
-    (fire) => {
-        println!("Fire");
-    };
-    (water) => {
-        println!("Water");
-    };
-}
-
-// print compute time
-#[compute_time]
+// print compute time, and the tree of every timed function called from here at the end
+#[compute_time(report = "tree")]
 fn main() {
     sample_types();
     sample_string();
@@ -59,15 +97,32 @@ fn main() {
     sample_multi_threading();
     sample_share_data_between_threads();
     sample_update_share_data_in_threads();
+    sample_treasury_under_contention();
     sample_channels_for_thread_with_buffer_size();
     sample_channels_for_thread_without_buffer_size();
     sample_channels_for_thread_with_update_shared_data();
     sample_modules_and_crates();
     greeting_by_macro!("World");
-    magic_spelling!(fire);
-    magic_spelling!(water);
     sample_oop_basic();
     sample_apply_solid_principle();
+    sample_skills_and_combat();
+    sample_save_and_load();
+    sample_serializable_trait_objects();
+    sample_world_map();
+    sample_tactical_grid();
+    sample_pet_companions();
+    sample_economy();
+    sample_event_bus();
+    sample_simulation_ticks();
+    sample_timing_async_and_methods();
+    sample_timing_sinks();
+    sample_timing_metrics();
+    sample_timing_hot_paths();
+    sample_timing_spans();
+    sample_crabby_states();
+    sample_memoized_calculations();
+    sample_retrying_fallible_calls();
+    sample_spells();
 }
 
 fn sample_types() {
@@ -108,6 +163,8 @@ fn sample_string() {
     println!("Crabby's map: {}", crabby_map);
 }
 
+// treasure2 is only assigned to show the borrow checker error below
+#[allow(unused_variables, unused_assignments)]
 fn sample_lifetime() {
     // sample lifetime
     let treasure2;
@@ -166,61 +223,6 @@ fn sample_struct_and_enum() {
     crabby.state_represent();
 }
 
-struct Crabby {
-    name: String,
-    health: u8,
-    state: CrabbyState,
-}
-
-impl Crabby {
-    fn take_damage(&mut self, damage: u8) {
-        self.health = self.health.saturating_sub(damage);
-        self.state = CrabbyState::Fighting;
-    }
-
-    fn heal(&mut self, health: u8) {
-        if self.health + health >= 100 {
-            self.health = 100;
-        } else {
-            self.health += health;
-        }
-        self.state = CrabbyState::Resting;
-    }
-
-    fn collecting(&mut self, amount: u32) {
-        self.state = CrabbyState::Collecting(amount);
-    }
-
-    fn state_represent(&self) {
-        match self.state {
-            CrabbyState::Resting => println!("Crabby is resting"),
-            CrabbyState::Fighting => println!("Crabby is fighting"),
-            CrabbyState::Collecting(amount) => {
-                println!("Crabby is collecting {} treasures", amount)
-            }
-            CrabbyState::Defending => println!("Crabby is defending"),
-        }
-    }
-}
-
-enum CrabbyState {
-    Resting,
-    Fighting,
-    Collecting(u32),
-    Defending,
-}
-
-impl fmt::Display for CrabbyState {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        match self {
-            CrabbyState::Resting => write!(f, "Resting"),
-            CrabbyState::Fighting => write!(f, "Fighting"),
-            CrabbyState::Collecting(amount) => write!(f, "Collecting {}", amount),
-            CrabbyState::Defending => write!(f, "Defending"),
-        }
-    }
-}
-
 fn sample_threat_and_generic() {
     // sample trait & generic
     let gold = Inventory { item: 100 };
@@ -291,10 +293,10 @@ fn sample_loop() {
 
 fn sample_vector() {
     // Sample vector
-    let mut skills: Vec<String> = Vec::new();
+    let mut skills: Vec<Ability> = Vec::new();
 
-    skills.push("Stun".to_string());
-    skills.push("Heal".to_string());
+    skills.push(catalog::stun());
+    skills.push(catalog::heal());
 
     // .iter() returns an iterator over the vector, borrowing each element
     // .into_iter() returns an iterator that takes ownership of the vector
@@ -302,7 +304,7 @@ fn sample_vector() {
     // .drain() returns an iterator that removes and yields each element
     // .remove() removes and returns the element at the specified index
     for skill in skills.iter() {
-        println!("Skill: {}", skill);
+        println!("Skill: {}", skill.name);
     }
 
     let mut weapons: Vec<&str> = vec!["Sword", "Shield", "Staff", "Bow"];
@@ -352,24 +354,32 @@ fn sample_iterators_and_closures() {
 }
 
 fn sample_hashmaps() {
-    let mut inventory: HashMap<&str, i32> = HashMap::new();
+    let mut inventory: HashMap<&str, u32> = HashMap::new();
 
-    inventory.insert("Gold", 100);
+    inventory.insert("Potion", 3);
     inventory.insert("Sword", 1);
     inventory.insert("Shield", 1);
 
-    let gold = inventory.get("Gold");
+    let potion = inventory.get("Potion");
     let sword = inventory.get("Sword");
 
-    println!("gold: {:?}, sword: {:?}", gold, sword);
+    println!("potion: {:?}, sword: {:?}", potion, sword);
 
-    if let Some(gold) = inventory.get_mut("Gold") {
+    if let Some(potion) = inventory.get_mut("Potion") {
         // Dereference operator (*) is used to access the value that the reference points to
         // Using dereference operator to modify the value in the HashMap
-        *gold += 10;
+        *potion += 10;
+    }
+
+    // gold is not an item count, Gold cannot go below zero
+    let mut gold = Gold::new(100);
+    gold.deposit(Gold::new(10)).unwrap();
+    if let Err(error) = gold.spend(Gold::new(500)) {
+        println!("cannot spend: {}", error);
     }
+    // output: cannot spend: needs 500g but only 110g available
 
-    println!("inventory: {:?}", inventory);
+    println!("inventory: {:?}, gold: {}", inventory, gold);
 }
 
 fn sample_error_handling() {
@@ -398,7 +408,10 @@ fn sample_error_handling() {
         Ok(value) => println!("Chest 1: Success: {}", value),
         Err(error) => println!("Chest 1: Error: {}", error),
     }
-    // output: Treasure: You got 10$
+    // output:
+    // → open_treasure(key = ***)
+    // ← open_treasure: Ok("You got 10$")
+    // Chest 1: Success: You got 10$
 
     let chest_result = match open_treasure("incorrect-key") {
         Ok(message) => message,
@@ -416,13 +429,25 @@ fn sample_error_handling() {
     }
 
     let _ = open_treasure_propagating_errors_with_question_mark("correct-key");
-    // output: Chest 4: You got 10$
+    // output: the calls it makes are indented under it
+    // → open_treasure_propagating_errors_with_question_mark()
+    //   → open_treasure(key = ***)
+    //   ← open_treasure: Ok("You got 10$")
+    // Chest 4: You got 10$
+    // ← open_treasure_propagating_errors_with_question_mark: Ok(())
+    // open_treasure_propagating_errors_with_question_mark took 4.2µs
 
     let chest5_result = open_treasure_propagating_errors_with_question_mark("incorrect-key");
     if let Err(error) = chest5_result {
         println!("Chest 5: Error: {}", error);
     }
-    // output: Error: Invalid key
+    // output:
+    // → open_treasure_propagating_errors_with_question_mark()
+    //   → open_treasure(key = ***)
+    //   ← open_treasure failed: "Invalid key"
+    // ← open_treasure_propagating_errors_with_question_mark failed: "Invalid key"
+    // open_treasure_propagating_errors_with_question_mark took 1.1µs
+    // Chest 5: Error: Invalid key
 
     let upgrade_weapon_result = match upgrade_weapon("Sword", 10) {
         Some(message) => message,
@@ -439,7 +464,9 @@ fn sample_error_handling() {
     println!("Upgrade weapon 2: {}", upgrade_weapon_result);
 }
 
-fn open_treasure(key: &str) -> Result<String, String> {
+// the key is a secret, the trace shows it as ***
+#[trace]
+fn open_treasure(#[trace(redact)] key: &str) -> Result<String, String> {
     if key == "correct-key" {
         Ok("You got 10$".to_string())
     } else {
@@ -447,7 +474,12 @@ fn open_treasure(key: &str) -> Result<String, String> {
     }
 }
 
-fn open_treasure_propagating_errors_with_question_mark(key: &str) -> Result<(), String> {
+// the time is printed even when `?` returns early, and so is the trace
+#[trace]
+#[compute_time]
+fn open_treasure_propagating_errors_with_question_mark(
+    #[trace(skip)] key: &str,
+) -> Result<(), String> {
     // ? operator is used to propagate errors
     // It can only be used in functions that return Result or Option
     // It returns the value inside Ok or stops the function and returns the error inside Err
@@ -459,6 +491,7 @@ fn open_treasure_propagating_errors_with_question_mark(key: &str) -> Result<(),
     Ok(())
 }
 
+#[compute_time]
 fn upgrade_weapon(weapon: &str, material_count: i32) -> Option<String> {
     if material_count >= 10 {
         Some(format!("{} upgraded successfully", weapon))
@@ -544,6 +577,8 @@ fn sample_smart_pointer() {
 //     child: Option<TreeViewBranch<'a>>,
 // }
 
+// fields are only read through Debug
+#[allow(dead_code)]
 #[derive(Debug)]
 struct TreeNode<'a> {
     name: &'a str,
@@ -555,44 +590,34 @@ struct TreeNode<'a> {
 // use static dispatch for speed
 fn sample_traits_as_a_type() {
     let weapon = get_weapon("sword");
-    println!("weapon: {:?}", weapon);
+    println!("weapon: {}", weapon.name());
     weapon_attack(weapon);
-    // output: Attack by sword"
+    // output: Sword Attack
 
-    let bow = Bow;
+    let bow = oop_weapons::Bow;
     generic_weapon_attack(bow);
-    // output: Attack by bow
-    let sword = Sword;
+    // output: Bow Attack
+    let sword = oop_weapons::Sword;
     generic_weapon_attack(sword);
-    // output: Attach by sword
+    // output: Sword Attack
 
     let shield = get_shield();
     borrow_shield_block(&shield);
     // output: Defend by wood shield
     shield_block(shield);
     // output: Defend by wood shield
-}
-
-trait Weapon: fmt::Debug {
-    fn attack(&self);
-}
-
-#[derive(Debug)]
-struct Sword;
-
-#[derive(Debug)]
-struct Bow;
-
-impl Weapon for Sword {
-    fn attack(&self) {
-        println!("Attack by sword");
-    }
-}
 
-impl Weapon for Bow {
-    fn attack(&self) {
-        println!("Attack by bow");
-    }
+    // the weapons and shields are declared with items!, see basic_oop::weapons
+    let bow: Box<dyn oop_weapons::Weapon> = Box::new(oop_weapons::Bow);
+    println!(
+        "{}: {} {:?} damage, range {}, shield defense {}",
+        bow.name(),
+        bow.damage(),
+        bow.kind(),
+        bow.range(),
+        get_shield().defense()
+    );
+    // output: Bow: 8 Piercing damage, range 6, shield defense 5
 }
 
 // Rust cannot return a trait object directly,
@@ -615,36 +640,24 @@ impl Weapon for Bow {
 // e.g. -> Rc<dyn Weapon> || -> &'a dyn Weapon
 // However, Box is commonly used because it allows for heap allocation and ownership transfer,
 // which is often needed when working with trait objects
-fn get_weapon(weapon_type: &str) -> Box<dyn Weapon> {
+fn get_weapon(weapon_type: &str) -> Box<dyn oop_weapons::Weapon> {
     match weapon_type {
-        "sword" => Box::new(Sword),
-        "bow" => Box::new(Bow),
+        "sword" => Box::new(oop_weapons::Sword),
+        "bow" => Box::new(oop_weapons::Bow),
         _ => panic!("Unknow weapon type!"),
     }
 }
 
 // overhead heat memory
-fn weapon_attack(weapon: Box<dyn Weapon>) {
+fn weapon_attack(weapon: Box<dyn oop_weapons::Weapon>) {
     weapon.attack();
 }
 
 // safe more than dynamic dispatch
-fn generic_weapon_attack<T: Weapon>(weapon: T) {
+fn generic_weapon_attack<T: oop_weapons::Weapon>(weapon: T) {
     weapon.attack();
 }
 
-trait Shield {
-    fn block(&self);
-}
-
-struct WoodShield;
-
-impl Shield for WoodShield {
-    fn block(&self) {
-        println!("Defend by wood shield");
-    }
-}
-
 fn get_shield() -> impl Shield {
     WoodShield
 }
@@ -710,42 +723,81 @@ fn sample_update_share_data_in_threads() {
     // that enables multiple ownership of data.
     // Arc is used when you need to share data between multiple threads
     // and ensure that the data is not deallocated until all references to it are gone.
-    let cash = Arc::new(Mutex::new(100));
+    // Treasury wraps that Mutex together with a history of who moved the gold,
+    // so the balance and the history can never disagree
+    let treasury = Arc::new(Treasury::new(Gold::new(100)));
     let mut handles = vec![];
 
     for _ in 0..10 {
-        let cash = Arc::clone(&cash);
+        let treasury = Arc::clone(&treasury);
         let handle = thread::spawn(move || {
             let thread_id = thread::current().id();
-            let mut store_cash = cash.lock().unwrap();
-            *store_cash += 10;
+            let balance = treasury.deposit(Gold::new(10)).unwrap();
 
-            println!("Thread ID: {:?}, Cash: {}", thread_id, *store_cash);
+            println!("Thread ID: {:?}, Cash: {}", thread_id, balance);
         });
         handles.push(handle);
     }
 
-    println!("Total Cash: {}", *cash.lock().unwrap());
-
+    // reading the balance here would only show the deposits done so far,
+    // the total is read once every thread has been joined
     for handle in handles {
         handle.join().unwrap();
     }
 
-    let cash_result = cash.lock().unwrap();
-    println!("Total Cash: {}", *cash_result);
+    let snapshot = treasury.snapshot();
+    println!("Total Cash: {}", snapshot.balance);
+    for entry in snapshot.entries.iter() {
+        println!(
+            "#{} {:?} {:?} {} -> {}",
+            entry.sequence, entry.thread, entry.movement, entry.amount, entry.balance_after
+        );
+    }
     // output:
-    // Thread ID: ThreadId(5), Cash: 110
-    // Total Cash: 110 -> print order randomly
-    // Thread ID: ThreadId(6), Cash: 120
-    // Thread ID: ThreadId(7), Cash: 130
-    // Thread ID: ThreadId(8), Cash: 140
-    // Thread ID: ThreadId(9), Cash: 150
-    // Thread ID: ThreadId(10), Cash: 160
-    // Thread ID: ThreadId(11), Cash: 170
-    // Thread ID: ThreadId(12), Cash: 180
-    // Thread ID: ThreadId(14), Cash: 190
-    // Thread ID: ThreadId(13), Cash: 200
-    // Total Cash: 200
+    // Thread ID: ThreadId(5), Cash: 110g
+    // Thread ID: ThreadId(6), Cash: 120g -> print order randomly
+    // ...
+    // Thread ID: ThreadId(13), Cash: 200g
+    // Total Cash: 200g
+    // #0 ThreadId(5) Deposit 10g -> 110g
+    // ...
+    // #9 ThreadId(13) Deposit 10g -> 200g
+}
+
+fn sample_treasury_under_contention() {
+    const THREADS: u32 = 16;
+    const ROUNDS: u32 = 1_000;
+
+    // 50g must always stay in the treasury
+    let treasury = Arc::new(Treasury::with_reserve(Gold::new(100), Gold::new(50)));
+    let mut handles = vec![];
+
+    for worker in 0..THREADS {
+        let treasury = Arc::clone(&treasury);
+        handles.push(thread::spawn(move || {
+            let mut refused = 0;
+            for round in 0..ROUNDS {
+                if (worker + round) % 2 == 0 {
+                    treasury.deposit(Gold::new(3)).unwrap();
+                } else if treasury.withdraw(Gold::new(5)).is_err() {
+                    refused += 1;
+                }
+            }
+            refused
+        }));
+    }
+
+    let refused: u32 = handles
+        .into_iter()
+        .map(|handle| handle.join().unwrap())
+        .sum();
+
+    let snapshot = treasury.snapshot();
+    let movements = THREADS * ROUNDS;
+    println!(
+        "{} movements from {} threads, {} withdrawals refused, balance {}",
+        movements, THREADS, refused, snapshot.balance
+    );
 }
 
 fn sample_channels_for_thread_with_buffer_size() {
@@ -761,30 +813,22 @@ fn sample_channels_for_thread_with_buffer_size() {
         "cloth".to_string(),
     ];
 
-    // Note: sender is called Producer
-    let (sender, receiver): (mpsc::SyncSender<String>, mpsc::Receiver<String>) =
-        mpsc::sync_channel(items.len());
-    let sender_arc = Arc::new(sender);
-
-    for item in items.clone().into_iter() {
-        thread::spawn({
-            let thread_sender = Arc::clone(&sender_arc);
-            let item = item.clone();
-            move || {
-                thread_sender
-                    .send(format!("Worker {}: Task complete!", item))
-                    .unwrap();
-            }
-        });
-    }
+    // Note: instead of one thread per item, 3 workers share a queue of 2 jobs.
+    // The queue is a mpsc::sync_channel(2), submitting waits while it is full (backpressure)
+    let pool = WorkerPool::new(3, 2);
+    let batch = pool
+        .batch(items, |item| format!("Worker {}: Task complete!", item))
+        .unwrap();
 
-    // Note: sender is called Consumer
-    for _ in 0..items.len() {
-        let item = receiver.recv().unwrap();
-        println!("Received: {}", item);
+    // Note: results come back as soon as a job is done
+    for (_, result) in batch.in_completion_order() {
+        println!("Received: {}", result.unwrap());
     }
 
-    // output: The order of the results depends on which thread finishes its task first.
+    // lets the queued jobs finish and joins the workers
+    pool.shutdown();
+
+    // output: The order of the results depends on which worker finishes its job first.
     // Received: Worker sword: Task complete!
     // Received: Worker potion: Task complete!
     // Received: Worker shield: Task complete!
@@ -809,108 +853,86 @@ fn sample_channels_for_thread_without_buffer_size() {
         "cloth".to_string(),
     ];
 
-    // producer
-    let (sender, receiver) = mpsc::channel();
-    let sender_arc = Arc::new(sender);
-
-    for item in items.clone().into_iter() {
-        thread::spawn({
-            let thread_sender = Arc::clone(&sender_arc);
-            let item = item.clone();
-            move || {
-                thread_sender
-                    .send(format!("Worker {}: Task complete!", item))
-                    .unwrap();
+    let pool = WorkerPool::new(4, items.len());
+    let batch = pool
+        .batch(items, |item| {
+            if item == "gun" {
+                panic!("no guns in a fantasy world");
             }
-        });
+            format!("Worker {}: Task complete!", item)
+        })
+        .unwrap();
+
+    // Note: waits for the whole batch, results line up with the items
+    // a panicking job only fails itself, the other jobs and the workers carry on
+    // (the default panic hook still prints the panic to stderr)
+    for result in batch.in_submission_order() {
+        match result {
+            Ok(item) => println!("Received (in submission order): {}", item),
+            Err(error) => println!("Failed (in submission order): {}", error),
+        }
     }
 
-    // Dropping all senders signals to the receiver that no more messages will be sent.
-    drop(sender_arc);
+    // the pool is still usable after a job panicked
+    let handle = pool.submit(|| "Worker cloak: Task complete!").unwrap();
+    println!("Received (single job): {}", handle.join().unwrap());
 
-    // consumer
-    while let Ok(item) = receiver.recv() {
-        println!("Received (without buffer size): {}", item)
-    }
+    // dropping the pool shuts it down too
 
-    // output: The order of the results depends on which thread finishes its task first.
-    // Received (without buffer size): Worker sword: Task complete!
-    // Received (without buffer size): Worker shield: Task complete!
-    // Received (without buffer size): Worker potion: Task complete!
-    // Received (without buffer size): Worker dagger: Task complete!
-    // Received (without buffer size): Worker bow: Task complete!
-    // Received (without buffer size): Worker gun: Task complete!
-    // Received (without buffer size): Worker hammer: Task complete!
-    // Received (without buffer size): Worker cloth: Task complete!
-    // Received (without buffer size): Worker axe: Task complete!
+    // output:
+    // Received (in submission order): Worker sword: Task complete!
+    // Received (in submission order): Worker shield: Task complete!
+    // Received (in submission order): Worker potion: Task complete!
+    // Received (in submission order): Worker bow: Task complete!
+    // Received (in submission order): Worker dagger: Task complete!
+    // Failed (in submission order): job panicked: no guns in a fantasy world
+    // Received (in submission order): Worker hammer: Task complete!
+    // Received (in submission order): Worker axe: Task complete!
+    // Received (in submission order): Worker cloth: Task complete!
+    // Received (single job): Worker cloak: Task complete!
 }
 
 fn sample_channels_for_thread_with_update_shared_data() {
     let loots_gold = vec![10, 20, 30, 40, 50, 60, 70, 80, 90, 100];
-    let mut gold = 100;
-
-    let (sender, receiver): (
-        mpsc::SyncSender<(ThreadId, i32)>,
-        mpsc::Receiver<(ThreadId, i32)>,
-    ) = mpsc::sync_channel(3);
-    let sender_arc = Arc::new(sender);
-
-    for loot_gold in loots_gold.into_iter() {
-        thread::spawn({
-            let sender = Arc::clone(&sender_arc);
-            move || {
-                let data = (thread::current().id(), loot_gold);
-                sender.send(data).unwrap();
-            }
-        });
-    }
-
-    drop(sender_arc);
-
-    // for received in receiver {
-    //     let (thread_id, loot_gold) = received;
-    //     gold += loot_gold;
-    //     println!("Received from thread_id: {:?}, loot_gold: {}", thread_id, loot_gold);
-    // }
-
-    loop {
-        match receiver.recv() {
-            Ok((thread_id, loot_gold)) => {
-                gold += loot_gold;
-
-                println!(
-                    "Received from thread_id: {:?}, loot_gold: {}",
-                    thread_id, loot_gold
-                );
-            }
-            Err(_) => {
-                println!("All senders have been dropped");
-                break;
-            }
-        }
+    let mut gold = Gold::new(100);
+
+    let pool = WorkerPool::new(3, 3);
+    let batch = pool
+        .batch(loots_gold, |loot_gold| {
+            (
+                thread::current().name().unwrap().to_string(),
+                Gold::new(loot_gold),
+            )
+        })
+        .unwrap();
+
+    // only this thread touches `gold`, the workers just send their loot
+    for (_, result) in batch.in_completion_order() {
+        let (worker, loot_gold) = result.unwrap();
+        gold.deposit(loot_gold).unwrap();
+
+        println!("Received from {}, loot_gold: {}", worker, loot_gold);
     }
 
+    pool.shutdown();
     println!("gold: {}", gold);
 
     // output:
-    // Received from thread_id: ThreadId(33), loot_gold: 10
-    // Received from thread_id: ThreadId(34), loot_gold: 20
-    // Received from thread_id: ThreadId(35), loot_gold: 30
-    // Received from thread_id: ThreadId(36), loot_gold: 40
-    // Received from thread_id: ThreadId(40), loot_gold: 80
-    // Received from thread_id: ThreadId(38), loot_gold: 60
-    // Received from thread_id: ThreadId(39), loot_gold: 70
-    // Received from thread_id: ThreadId(37), loot_gold: 50
-    // Received from thread_id: ThreadId(42), loot_gold: 100
-    // Received from thread_id: ThreadId(41), loot_gold: 90
-    // All senders have been dropped
-    // gold: 650
+    // Received from worker-0, loot_gold: 10g
+    // Received from worker-1, loot_gold: 20g
+    // Received from worker-2, loot_gold: 30g
+    // Received from worker-0, loot_gold: 40g
+    // Received from worker-2, loot_gold: 60g
+    // Received from worker-1, loot_gold: 50g
+    // ...
+    // Received from worker-0, loot_gold: 100g
+    // gold: 650g
 }
 
 fn sample_modules_and_crates() {
     potions::use_item();
     weapons::use_item();
-    maps::use_item();
+    maps::use_item::use_item();
     drop_item();
     use_item();
     use_item::use_item();
@@ -918,12 +940,10 @@ fn sample_modules_and_crates() {
 
     println!("1 + 2 = {}", add(1, 2));
     println!("2^3 = {}", power(2, 3));
-}
 
-mod maps {
-    pub fn use_item() {
-        println!("Use a map");
-    }
+    // built with --features contracts, mul checks its result against a * b
+    println!("3 * 4 = {}", mul(3, 4));
+    // output: 3 * 4 = 12
 }
 
 fn sample_oop_basic() {
@@ -947,6 +967,15 @@ fn sample_oop_basic() {
     println!("Mage helath: {}", mage.health);
     println!("Healer helath: {}", healer.health);
 
+    // healing never goes past the max set with #[character(health, max = 100)]
+    health_increase(&mut warrior, 50);
+    println!(
+        "Warrior health after a big potion: {}/{}",
+        warrior.health,
+        warrior.max_health()
+    );
+    // output: Warrior health after a big potion: 100/100
+
     special_attack(warrior.weapon);
     special_attack(mage.weapon);
     special_attack(healer.weapon);
@@ -960,3 +989,1259 @@ fn sample_apply_solid_principle() {
     quest_manager.complete_quest(pigeon);
     quest_manager.complete_quest(email);
 }
+
+fn sample_skills_and_combat() {
+    let catalog = catalog::default_catalog();
+
+    let mut combat = Combat::new();
+    let warrior = combat.join(Combatant::from_character(
+        "Warrior",
+        &Warrior::new(),
+        &catalog,
+    ));
+    let mage = combat.join(Combatant::from_character("Mage", &Mage::new(), &catalog));
+    let healer = combat.join(Combatant::from_character(
+        "Healer",
+        &Healer::new(),
+        &catalog,
+    ));
+
+    match combat.cast(warrior, "Stun", Target::One(mage)) {
+        Ok(report) => println!("Warrior cast {}: {:?}", report.ability, report.outcomes),
+        Err(error) => println!("Warrior failed: {}", error),
+    }
+    // output: Warrior cast Stun: [Damaged { target: 1, amount: 5 }, StatusApplied { target: 1, status: Stunned }]
+
+    if let Err(error) = combat.cast(mage, "Heal", Target::Caster) {
+        println!("Mage failed: {}", error);
+    }
+    // output: Mage failed: Mage is stunned
+
+    if let Err(error) = combat.cast(warrior, "Stun", Target::One(healer)) {
+        println!("Warrior failed: {}", error);
+    }
+    // output: Warrior failed: Stun is on cooldown for 3 turns
+
+    // Heal is a Healer and Mage ability, a Warrior cannot learn it
+    let mut warrior_skills = combat.combatants[warrior].skills.clone();
+    if let Err(error) = warrior_skills.learn(catalog::heal()) {
+        println!("Warrior learning Heal: {}", error);
+    }
+    // output: Warrior learning Heal: Heal cannot be learned by this class
+
+    combat.end_turn();
+
+    let learned = combat.combatants[mage].level_up(&catalog);
+    println!("Mage reached level 2 and learned {:?}", learned);
+    // output: Mage reached level 2 and learned ["Fireball"]
+
+    let report = combat
+        .cast(mage, "Fireball", Target::Group(vec![warrior, healer]))
+        .unwrap();
+    println!("Mage cast {}: {:?}", report.ability, report.outcomes);
+
+    let report = combat.cast(healer, "Heal", Target::One(warrior)).unwrap();
+    println!("Healer cast {}: {:?}", report.ability, report.outcomes);
+
+    for combatant in combat.combatants.iter() {
+        println!(
+            "{}: health {}/{}, mana {}, stamina {}",
+            combatant.name,
+            combatant.health,
+            combatant.max_health,
+            combatant.mana,
+            combatant.stamina
+        );
+    }
+}
+
+fn sample_save_and_load() {
+    let mut quests = QuestLog::new();
+    quests.start("Find the treasure", 2);
+    quests.start("Defeat the dragon", 3);
+    quests.advance("Find the treasure");
+
+    let mut bag = inventory::Inventory::new(Gold::new(100));
+    bag.add("Potion", 3);
+    bag.add("Old map", 1);
+
+    let mut crabby = Crabby {
+        name: "Crabby".to_string(),
+        health: 100,
+        state: CrabbyState::Resting,
+    };
+    crabby.collecting(15);
+
+    let state = GameState {
+        party: vec![
+            CharacterRecord::from_character(&Warrior::new()),
+            CharacterRecord::from_character(&Mage::new()),
+        ],
+        inventory: bag,
+        quests,
+        crabby,
+    };
+
+    let json = format::encode(&state, SaveFormat::Json).unwrap();
+    let binary = format::encode(&state, SaveFormat::Binary).unwrap();
+    println!(
+        "json save: {} bytes, binary save: {} bytes",
+        json.len(),
+        binary.len()
+    );
+
+    let path = std::env::temp_dir().join("rust_101_save.bin");
+    format::save_to_file(&path, &state, SaveFormat::Binary).unwrap();
+    let loaded = format::load_from_file(&path).unwrap();
+    println!("loaded from file: {}", loaded == state);
+    // output: loaded from file: true
+
+    for record in loaded.party.iter() {
+        let character = record.restore().unwrap();
+        println!(
+            "restored {:?} with health {} and a {}",
+            character.class(),
+            character.health(),
+            character.weapon().name()
+        );
+    }
+
+    // flip one bit of the payload, the checksum no longer matches
+    let mut corrupted = binary.clone();
+    let last = corrupted.len() - 1;
+    corrupted[last] ^= 1;
+    if let Err(error) = format::decode(&corrupted) {
+        println!("load failed: {}", error);
+    }
+
+    // an old version 1 save is upgraded while loading
+    let old_state = GameStateV1 {
+        party: state.party.clone(),
+        gold: 50,
+        items: vec!["Potion".to_string(), "Potion".to_string()],
+        completed_quests: vec!["Save the princess".to_string()],
+        crabby: state.crabby.clone(),
+    };
+    let old_save = legacy::encode_v1(&old_state, SaveFormat::Json).unwrap();
+    let upgraded = format::decode(&old_save).unwrap();
+    println!(
+        "upgraded v1 save: gold {}, potions {}, completed quests {}",
+        upgraded.inventory.gold,
+        upgraded.inventory.count("Potion"),
+        upgraded.quests.completed().count()
+    );
+    // output: upgraded v1 save: gold 50, potions 2, completed quests 1
+}
+
+// A weapon defined outside the library, registered at runtime
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct Dagger {
+    sharpness: u8,
+}
+
+impl TypeTag for Dagger {
+    const TYPE_TAG: &'static str = "Dagger";
+}
+
+impl oop_weapons::Weapon for Dagger {
+    fn name(&self) -> &'static str {
+        "Dagger"
+    }
+
+    fn attack(&self) {
+        println!("Dagger Attack with sharpness {}", self.sharpness);
+    }
+
+    fn damage(&self) -> u32 {
+        self.sharpness as u32
+    }
+
+    fn kind(&self) -> oop_weapons::DamageKind {
+        oop_weapons::DamageKind::Piercing
+    }
+}
+
+fn sample_serializable_trait_objects() {
+    let party: Vec<Box<dyn Character>> = vec![Box::new(Warrior::new()), Box::new(Healer::new())];
+
+    // Box<dyn Character> is written with its type tag next to its fields
+    let json = serde_json::to_string(&party).unwrap();
+    println!("party: {}", json);
+    // output: party: [{"type":"Warrior","value":{"health":100,"intelligent":0,"strength":10,"weapon":{"type":"Sword","value":null}}},...]
+
+    let restored: Vec<Box<dyn Character>> = serde_json::from_str(&json).unwrap();
+    for character in restored.iter() {
+        println!(
+            "restored {:?} holding a {}",
+            character.class(),
+            character.weapon().name()
+        );
+    }
+
+    // cloning a trait object clones the concrete type behind it
+    let mut cloned = restored.clone();
+    cloned[0].health_decrease(30);
+    println!(
+        "original health: {}, cloned health: {}",
+        restored[0].health(),
+        cloned[0].health()
+    );
+    // output: original health: 100, cloned health: 70
+
+    let notifiers: Vec<Box<dyn QuestNotifier>> = vec![Box::new(Pigeon), Box::new(Email)];
+    let bytes = bincode::serde::encode_to_vec(&notifiers, bincode::config::standard()).unwrap();
+    let (notifiers, _): (Vec<Box<dyn QuestNotifier>>, usize) =
+        bincode::serde::decode_from_slice(&bytes, bincode::config::standard()).unwrap();
+    for notifier in notifiers.iter() {
+        notifier.notify();
+    }
+
+    let shield: Box<dyn Shield> = Box::new(WoodShield);
+    println!("shield: {}", serde_json::to_string(&shield).unwrap());
+
+    let unknown = r#"{"type":"Dagger","value":{"sharpness":7}}"#;
+    if let Err(error) = serde_json::from_str::<Box<dyn oop_weapons::Weapon>>(unknown) {
+        println!("before registering: {}", error);
+    }
+    // output: before registering: no type registered as Dagger
+
+    register_type!(dyn oop_weapons::Weapon, Dagger);
+    let dagger: Box<dyn oop_weapons::Weapon> = serde_json::from_str(unknown).unwrap();
+    dagger.attack();
+    // output: Dagger Attack with sharpness 7
+}
+
+fn sample_world_map() {
+    let mut world = World::new();
+    let coast = world.add_region("Crab Coast");
+    let forest = world.add_region("Old Forest");
+
+    let beach = world.add_location("Beach", coast, (0.0, 0.0));
+    let harbor = world.add_location("Harbor", coast, (4.0, 0.0));
+    let camp = world.add_location("Camp", forest, (3.0, 4.0));
+    let ruins = world.add_location("Ruins", forest, (8.0, 4.0));
+    let cave = world.add_location("Treasure Cave", forest, (12.0, 4.0));
+
+    world.connect(beach, harbor, 4);
+    world.connect(beach, camp, 6);
+    world.connect(harbor, ruins, 12);
+    world.connect(camp, ruins, 5);
+    world.connect(ruins, cave, 4);
+
+    let path = pathfinding::dijkstra(&world, beach, cave, Knowledge::Everything).unwrap();
+    let names: Vec<&str> = path
+        .locations
+        .iter()
+        .map(|id| world.location(*id).unwrap().name.as_str())
+        .collect();
+    println!("dijkstra: {:?}, cost {}", names, path.cost);
+    // output: dijkstra: ["Beach", "Camp", "Ruins", "Treasure Cave"], cost 15
+
+    let path = pathfinding::a_star(&world, beach, cave, Knowledge::Everything).unwrap();
+    println!("a*: {} stops, cost {}", path.locations.len(), path.cost);
+    // output: a*: 4 stops, cost 15
+
+    // with fog of war the party can only plan through what it has seen
+    world.visit(beach);
+    let known = pathfinding::a_star(&world, beach, cave, Knowledge::Discovered);
+    println!("path to the cave before exploring: {:?}", known);
+    // output: path to the cave before exploring: None
+
+    let quest = Quest::new(
+        "Find the treasure",
+        vec![
+            Objective::Reach(ruins),
+            Objective::Collect {
+                item: "Old map".to_string(),
+                count: 1,
+            },
+        ],
+    );
+    let quests = vec![quest];
+    let mut log = QuestLog::new();
+    log.start_quest(&quests[0]);
+
+    for stop in path.locations.iter() {
+        world.visit(*stop);
+        for name in log.arrive(&quests, *stop) {
+            println!(
+                "{} advanced at {}",
+                name,
+                world.location(*stop).unwrap().name
+            );
+        }
+    }
+    // output: Find the treasure advanced at Ruins
+
+    println!(
+        "discovered {} of {} locations, next objective: {:?}",
+        world.discovered().len(),
+        world.location_count(),
+        log.current_objective(&quests[0])
+    );
+    let region = world.region(world.location(cave).unwrap().region).unwrap();
+    println!(
+        "{} lies in {}",
+        world.location(cave).unwrap().name,
+        region.name
+    );
+}
+
+fn sample_tactical_grid() {
+    let map = TileMap::parse(&[
+        "..........",
+        "...#......",
+        "...#..~~..",
+        "...#..%%..",
+        "..........",
+    ]);
+
+    let catalog = catalog::default_catalog();
+    let mut combat = Combat::new();
+    let warrior = combat.join(Combatant::from_character(
+        "Warrior",
+        &Warrior::new(),
+        &catalog,
+    ));
+    let mage = combat.join(Combatant::from_character("Mage", &Mage::new(), &catalog));
+    let healer = combat.join(Combatant::from_character(
+        "Healer",
+        &Healer::new(),
+        &catalog,
+    ));
+
+    let mut field = Battlefield::new(map);
+    field.place(warrior, Position::new(1, 2), 4).unwrap();
+    field.place(mage, Position::new(6, 2), 4).unwrap();
+    field.place(healer, Position::new(7, 3), 4).unwrap();
+
+    // the wall hides the mage from the warrior
+    println!("warrior sees mage: {}", field.can_see(warrior, mage));
+    // output: warrior sees mage: false
+
+    println!(
+        "warrior can reach {} tiles this turn",
+        field.reachable(warrior).len()
+    );
+
+    match field.move_unit(warrior, Position::new(5, 2)) {
+        Ok(spent) => println!("warrior moved for {} action points", spent),
+        Err(error) => println!("warrior cannot move: {}", error),
+    }
+    // output: warrior cannot move: needs 8 action points, 4 left
+
+    let spent = field.move_unit(warrior, Position::new(2, 4)).unwrap();
+    println!("warrior moved around the wall for {} action points", spent);
+
+    let sword = Warrior::new().weapon;
+    let staff = Mage::new().weapon;
+    println!(
+        "warrior sword reaches mage: {}, mage staff reaches warrior: {}",
+        field.in_weapon_range(warrior, mage, sword.as_ref()),
+        field.in_weapon_range(mage, warrior, staff.as_ref())
+    );
+    // output: warrior sword reaches mage: false, mage staff reaches warrior: true
+
+    let line = field.units_in(
+        Position::new(6, 2),
+        Shape::Line {
+            towards: Position::new(7, 3),
+            length: 3,
+        },
+    );
+    println!("a line from the mage hits {:?}", line);
+    // output: a line from the mage hits [2]
+
+    combat.combatants[mage].level_up(&catalog);
+    let fireball = catalog::fireball();
+    let report = combat
+        .cast_on(
+            &field,
+            mage,
+            &fireball.name,
+            Target::Area(Position::new(2, 4)),
+        )
+        .unwrap();
+    println!(
+        "Mage cast {} on the grid: {:?}",
+        report.ability, report.outcomes
+    );
+    // output: Mage cast Fireball on the grid: [Damaged { target: 0, amount: 20 }]
+}
+
+fn sample_pet_companions() {
+    let names: Vec<String> = all_species()
+        .into_iter()
+        .map(|species| species.name)
+        .collect();
+    println!("species: {:?}", names);
+    // output: species: ["Dog", "Cat", "Hawk"]
+
+    let mut rex = Companion::new("Rex", &dog::species());
+    rex.follow("Warrior");
+    println!("{}, following {:?}", rex.make_noise(), rex.owner);
+    // output: Rex is barking, following Some("Warrior")
+
+    rex.teach(Command::Sit).unwrap();
+    println!("{}", rex.obey(Command::Sit).unwrap());
+    // output: Rex is sitting
+
+    if let Err(error) = rex.teach(Command::Attack) {
+        println!("teaching Attack: {}", error);
+    }
+    // output: teaching Attack: needs 60 loyalty, has 17
+
+    while rex.loyalty < Command::Attack.required_loyalty() {
+        rex.pet();
+    }
+    rex.teach(Command::Attack).unwrap();
+
+    let mut tom = Companion::new("Tom", &all_species()[1]);
+    if let Err(error) = tom.teach(Command::Fetch) {
+        println!("teaching Fetch: {}", error);
+    }
+    // output: teaching Fetch: a Cat cannot learn Fetch
+
+    let catalog = catalog::default_catalog();
+    let mut combat = Combat::new();
+    let warrior = combat.join(Combatant::from_character(
+        "Warrior",
+        &Warrior::new(),
+        &catalog,
+    ));
+    let dog_id = combat.join(rex.to_combatant(&catalog).unwrap());
+    let mage = combat.join(Combatant::from_character("Mage", &Mage::new(), &catalog));
+
+    let report = combat.cast(dog_id, "Bite", Target::One(mage)).unwrap();
+    println!("Rex cast {}: {:?}", report.ability, report.outcomes);
+    // output: Rex cast Bite: [Damaged { target: 2, amount: 10 }]
+    combat.cast(warrior, "Stun", Target::One(mage)).unwrap();
+
+    let levels = rex.after_combat(&combat.combatants[dog_id], 150);
+    println!(
+        "Rex gained {} level, now level {} with {} loyalty",
+        levels, rex.level, rex.loyalty
+    );
+    // output: Rex gained 1 level, now level 2 with 65 loyalty
+}
+
+fn sample_economy() {
+    let mut market = Market::new();
+    market.add_vendor(
+        Vendor::new("Blacksmith", Gold::new(200), 3)
+            .with_item("Sword", 2, Gold::new(50))
+            .with_item("Potion", 10, Gold::new(5)),
+    );
+
+    let opening = Gold::new(130);
+    let mut purse = inventory::Inventory::new(opening);
+
+    let vendor = market.vendor("Blacksmith").unwrap();
+    println!(
+        "Sword: vendor sells for {}, buys for {}",
+        vendor.sell_price("Sword").unwrap(),
+        vendor.buy_price("Sword").unwrap()
+    );
+    // output: Sword: vendor sells for 60g, buys for 40g
+
+    let receipt = market
+        .buy("Crabby", &mut purse, "Blacksmith", "Sword", 1)
+        .unwrap();
+    println!("bought {} Sword for {}", receipt.quantity, receipt.total);
+
+    // one sword left and one just sold, the second one is dearer
+    let vendor = market.vendor("Blacksmith").unwrap();
+    println!(
+        "Sword now sells for {}",
+        vendor.sell_price("Sword").unwrap()
+    );
+    // output: Sword now sells for 78g
+
+    if let Err(error) = market.buy("Crabby", &mut purse, "Blacksmith", "Sword", 1) {
+        println!("second sword: {}", error);
+    }
+    // output: second sword: needs 78g but only 70g available
+
+    market
+        .buy("Crabby", &mut purse, "Blacksmith", "Potion", 4)
+        .unwrap();
+    market
+        .sell("Crabby", &mut purse, "Blacksmith", "Sword", 1)
+        .unwrap();
+
+    for _ in 0..3 {
+        market.tick();
+    }
+
+    for entry in market.ledger().entries() {
+        println!(
+            "#{} tick {} {:?} {} x{} for {}",
+            entry.id, entry.tick, entry.kind, entry.item, entry.quantity, entry.total
+        );
+    }
+
+    match market.ledger().audit("Crabby", opening, purse.gold) {
+        Ok(()) => println!("Crabby's {} is fully accounted for", purse.gold),
+        Err(error) => println!("{}", error),
+    }
+
+    // gold appearing out of nowhere does not pass the audit
+    purse.gold.deposit(Gold::new(1000)).unwrap();
+    if let Err(error) = market.ledger().audit("Crabby", opening, purse.gold) {
+        println!("{}", error);
+    }
+}
+
+fn sample_event_bus() {
+    let bus = Arc::new(EventBus::new());
+
+    // logging runs on its own thread and hears about everything
+    let log = bus.subscribe_queue(Topic::ALL, 16);
+    let logger = thread::spawn(move || {
+        let mut logged = 0;
+        for event in log.iter() {
+            println!("[log] {:?}", event);
+            logged += 1;
+        }
+        logged
+    });
+
+    // the notifier only cares about quests
+    let quest_updates = bus.subscribe_queue(&[Topic::Quest], 4);
+    let notifier = thread::spawn(move || {
+        for event in quest_updates.iter() {
+            if let GameEvent::QuestProgressed {
+                quest,
+                stage,
+                stages,
+            } = event
+            {
+                if stage == stages {
+                    println!("[notifier] {} is done", quest);
+                    QuestManager.complete_quest(Pigeon);
+                }
+            }
+        }
+    });
+
+    // the quest log reacts right away on the publishing thread,
+    // then announces its own progress on the bus.
+    // Note: a Weak reference, the bus owning a handler that owns the bus would never be freed
+    let quest_log = Arc::new(Mutex::new(QuestLog::new()));
+    quest_log.lock().unwrap().start("Pelts for the Tanner", 3);
+    bus.subscribe(&[Topic::Loot], {
+        let quest_log = Arc::clone(&quest_log);
+        let bus = Arc::downgrade(&bus);
+        move |event| {
+            let quantity = match event {
+                GameEvent::ItemLooted { item, quantity, .. } if item == "Wolf Pelt" => *quantity,
+                _ => return,
+            };
+            for _ in 0..quantity {
+                let progress = quest_log
+                    .lock()
+                    .unwrap()
+                    .advance("Pelts for the Tanner")
+                    .cloned();
+                if let (Some(progress), Some(bus)) = (progress, bus.upgrade()) {
+                    bus.publish(GameEvent::QuestProgressed {
+                        quest: progress.name,
+                        stage: progress.stage,
+                        stages: progress.stages,
+                    });
+                }
+            }
+        }
+    });
+
+    bus.publish(GameEvent::CombatHit {
+        attacker: "Warrior".to_string(),
+        target: "Wolf".to_string(),
+        damage: 25,
+    });
+    let report = bus.publish(GameEvent::ItemLooted {
+        by: "Warrior".to_string(),
+        item: "Wolf Pelt".to_string(),
+        quantity: 2,
+    });
+    println!("loot: {:?}", report);
+    // output: loot: PublishReport { handled: 1, queued: 1, dropped: 0, deferred: 0 }
+
+    bus.publish(GameEvent::LevelUp {
+        name: "Warrior".to_string(),
+        level: 2,
+    });
+    bus.publish(GameEvent::ItemLooted {
+        by: "Mage".to_string(),
+        item: "Wolf Pelt".to_string(),
+        quantity: 1,
+    });
+
+    // nobody reads this queue, once its 2 slots are taken new hits are dropped
+    // instead of holding up the publisher
+    let slow = bus.subscribe_queue(&[Topic::Combat], 2);
+    for damage in 1..=5 {
+        bus.publish(GameEvent::CombatHit {
+            attacker: "Mage".to_string(),
+            target: "Wolf".to_string(),
+            damage,
+        });
+    }
+    println!("slow subscriber missed {:?} hits", bus.dropped(slow.id()));
+    // output: slow subscriber missed Some(3) hits
+
+    // lets every subscriber thread run out of events
+    bus.close();
+    let logged = logger.join().unwrap();
+    notifier.join().unwrap();
+    println!(
+        "{} events logged, quest: {:?}",
+        logged,
+        quest_log.lock().unwrap().get("Pelts for the Tanner")
+    );
+
+    // output: logging and notifying happen on other threads, the order may vary
+    // [log] CombatHit { attacker: "Warrior", target: "Wolf", damage: 25 }
+    // [log] ItemLooted { by: "Warrior", item: "Wolf Pelt", quantity: 2 }
+    // [log] QuestProgressed { quest: "Pelts for the Tanner", stage: 1, stages: 3 }
+    // ...
+    // [notifier] Pelts for the Tanner is done
+    // Quest completed, notified by pigeon
+    // 12 events logged, quest: Some(QuestProgress { name: "Pelts for the Tanner", stage: 3, stages: 3 })
+}
+
+// everything the scheduled actions work on
+struct Adventure {
+    hero: Combatant,
+    market: Market,
+}
+
+fn sample_simulation_ticks() {
+    let mut hero =
+        Combatant::from_character("Warrior", &Warrior::new(), &catalog::default_catalog());
+    hero.take_damage(60);
+    hero.skills.start_cooldown("Stun");
+
+    let mut market = Market::new();
+    market.add_vendor(Vendor::new("Alchemist", Gold::new(100), 4).with_item(
+        "Potion",
+        3,
+        Gold::new(5),
+    ));
+    let mut purse = inventory::Inventory::new(Gold::new(50));
+    market
+        .buy("Warrior", &mut purse, "Alchemist", "Potion", 3)
+        .unwrap();
+
+    let mut adventure = Adventure { hero, market };
+
+    // Note: the virtual clock only moves when we say so, every run prints the same
+    let clock = VirtualClock::new();
+    let mut game = GameLoop::new(Box::new(clock.clone()), Duration::from_millis(100));
+
+    // a potion heals 10 health per tick over 5 ticks
+    game.scheduler.every(
+        "potion",
+        1,
+        Repeat::Times(5),
+        |adventure: &mut Adventure| {
+            adventure.hero.heal(10);
+        },
+    );
+    game.scheduler.every(
+        "cooldowns",
+        1,
+        Repeat::Forever,
+        |adventure: &mut Adventure| {
+            adventure.hero.skills.tick();
+        },
+    );
+    game.scheduler
+        .every("market", 1, Repeat::Forever, |adventure: &mut Adventure| {
+            adventure.market.tick();
+        });
+    game.scheduler
+        .after("ambush", 6, |adventure: &mut Adventure| {
+            adventure.hero.take_damage(15);
+        });
+
+    let report = |game: &GameLoop<Adventure>, adventure: &Adventure| {
+        let potions = adventure.market.vendor("Alchemist").unwrap().stock["Potion"].quantity;
+        println!(
+            "tick {}: health {}/{}, stun ready in {}, potions in stock {}",
+            game.current_tick(),
+            adventure.hero.health,
+            adventure.hero.max_health,
+            adventure.hero.skills.cooldown_left("Stun"),
+            potions
+        );
+    };
+    report(&game, &adventure);
+
+    // 250ms is 2 ticks, the other 50ms count towards the next update
+    clock.advance(Duration::from_millis(250));
+    println!("ran {} ticks", game.update(&mut adventure));
+    report(&game, &adventure);
+
+    clock.advance(Duration::from_millis(50));
+    println!("ran {} ticks", game.update(&mut adventure));
+    report(&game, &adventure);
+
+    // nothing moves while paused, not even once the game resumes
+    game.pause();
+    clock.advance(Duration::from_secs(10));
+    println!("ran {} ticks while paused", game.update(&mut adventure));
+    game.resume();
+    println!("ran {} ticks after resuming", game.update(&mut adventure));
+
+    clock.advance(Duration::from_millis(300));
+    println!("ran {} ticks", game.update(&mut adventure));
+    report(&game, &adventure);
+    println!("still scheduled: {:?}", game.scheduler.pending());
+
+    // output:
+    // tick 0: health 40/100, stun ready in 3, potions in stock 0
+    // ran 2 ticks
+    // tick 2: health 60/100, stun ready in 2, potions in stock 0
+    // ran 1 ticks
+    // tick 3: health 70/100, stun ready in 1, potions in stock 0
+    // ran 0 ticks while paused
+    // ran 0 ticks after resuming
+    // ran 3 ticks
+    // tick 6: health 75/100, stun ready in 0, potions in stock 3
+    // still scheduled: [(7, "cooldowns"), (7, "market")]
+}
+
+// a tiny executor, just enough to run the async samples without a runtime crate
+fn block_on<F: Future>(future: F) -> F::Output {
+    struct ThreadWaker(thread::Thread);
+
+    impl Wake for ThreadWaker {
+        fn wake(self: Arc<Self>) {
+            self.0.unpark();
+        }
+    }
+
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut context = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    loop {
+        match future.as_mut().poll(&mut context) {
+            Poll::Ready(output) => return output,
+            Poll::Pending => thread::park(),
+        }
+    }
+}
+
+// a future that is ready once `duration` has passed
+struct Delay {
+    until: Instant,
+    waiting: bool,
+}
+
+impl Delay {
+    fn new(duration: Duration) -> Self {
+        Self {
+            until: Instant::now() + duration,
+            waiting: false,
+        }
+    }
+}
+
+impl Future for Delay {
+    type Output = ();
+
+    fn poll(mut self: Pin<&mut Self>, context: &mut Context<'_>) -> Poll<()> {
+        let now = Instant::now();
+        if now >= self.until {
+            return Poll::Ready(());
+        }
+        if !self.waiting {
+            self.waiting = true;
+            let waker = context.waker().clone();
+            let wait = self.until - now;
+            thread::spawn(move || {
+                thread::sleep(wait);
+                waker.wake();
+            });
+        }
+        Poll::Pending
+    }
+}
+
+// the time covers the whole trip, not just creating the future
+#[compute_time]
+async fn travel(destination: &str, leagues: u64) -> String {
+    Delay::new(Duration::from_millis(10 * leagues)).await;
+    format!("arrived at {} after {} leagues", destination, leagues)
+}
+
+struct Blacksmith {
+    forged: u32,
+}
+
+// every method of the impl block is timed as Blacksmith::method
+#[compute_time]
+impl Blacksmith {
+    fn forge(&mut self, item: &str) -> String {
+        self.forged += 1;
+        format!("{} forged", item)
+    }
+
+    fn forged(&self) -> u32 {
+        self.forged
+    }
+}
+
+trait Craft {
+    fn craft(&self, item: &str) -> String;
+}
+
+impl Craft for Blacksmith {
+    #[compute_time]
+    fn craft(&self, item: &str) -> String {
+        format!("{} crafted by hand", item)
+    }
+}
+
+fn sample_timing_async_and_methods() {
+    println!("{}", block_on(travel("Riverside", 3)));
+    // output:
+    // travel took 30.2ms
+    // arrived at Riverside after 3 leagues
+
+    let mut blacksmith = Blacksmith { forged: 0 };
+    println!("{}", blacksmith.forge("Sword"));
+    println!("{} forged so far", blacksmith.forged());
+    println!("{}", blacksmith.craft("Shield"));
+    // output:
+    // Blacksmith::forge took 1.1µs
+    // Sword forged
+    // Blacksmith::forged took 40ns
+    // 1 forged so far
+    // craft took 600ns
+    // Shield crafted by hand
+}
+
+// prints every log record, a real game would install env_logger or similar
+struct ConsoleLogger;
+
+impl log::Log for ConsoleLogger {
+    fn enabled(&self, _metadata: &log::Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &log::Record) {
+        println!("[{} {}] {}", record.level(), record.target(), record.args());
+    }
+
+    fn flush(&self) {}
+}
+
+static LOGGER: ConsoleLogger = ConsoleLogger;
+
+#[compute_time(sink = "log", level = "debug", unit = "ms")]
+fn brew_potions(count: u64) -> u64 {
+    thread::sleep(Duration::from_millis(count));
+    count
+}
+
+// the "collector" sink does not exist at compile time, it is registered at runtime
+#[compute_time(sink = "registered:collector", label = "forge a blade")]
+fn forge_blade(material: &str) -> String {
+    format!("{} blade", material)
+}
+
+fn sample_timing_sinks() {
+    log::set_logger(&LOGGER).unwrap();
+    log::set_max_level(log::LevelFilter::Debug);
+
+    println!("brewed {} potions", brew_potions(3));
+    // output:
+    // [DEBUG rust_101] brew_potions took 3.071ms
+    // brewed 3 potions
+
+    let collector = Arc::new(MemorySink::new());
+    timing_sink::register_sink("collector", collector.clone());
+    for material in ["Iron", "Steel", "Mithril"] {
+        forge_blade(material);
+    }
+    let timings = collector.take();
+    println!(
+        "collected {} timings of {:?}",
+        timings.len(),
+        timings.first().map(|timing| &timing.label)
+    );
+    // output: collected 3 timings of Some("forge a blade")
+
+    // without the collector the timings fall back to stdout
+    timing_sink::unregister_sink("collector");
+    forge_blade("Bronze");
+    // output: forge a blade took 1.2µs
+}
+
+// called in a hot loop, only the metrics keep track of it
+#[compute_time(sink = "none")]
+fn roll_damage(seed: u64) -> u64 {
+    (0..seed % 100).fold(seed, |damage, step| {
+        damage.wrapping_mul(31).wrapping_add(step)
+    }) % 20
+}
+
+fn sample_timing_metrics() {
+    let total: u64 = (0..1_000).map(roll_damage).sum();
+    println!("rolled {} damage in total", total);
+
+    let roll = metrics::snapshot()
+        .into_iter()
+        .find(|metric| metric.path.ends_with("::roll_damage"))
+        .unwrap();
+    println!(
+        "{}: {} calls, min {:?}, mean {:?}, p99 {:?}, max {:?}",
+        roll.path, roll.count, roll.min, roll.mean, roll.p99, roll.max
+    );
+    // output: rust_101::roll_damage: 1000 calls, min 167ns, mean 958ns, p99 1.791µs, max 14.576µs
+
+    // the same numbers, ready for a dashboard, with one line per bucket of which only
+    // the last is shown here
+    for line in metrics::report(ReportFormat::Prometheus)
+        .lines()
+        .filter(|line| line.contains("roll_damage"))
+        .filter(|line| !line.contains("_bucket") || line.contains("+Inf"))
+    {
+        println!("{}", line);
+    }
+    // output:
+    // compute_time_seconds_bucket{function="rust_101::roll_damage",le="+Inf"} 1000
+    // compute_time_seconds_sum{function="rust_101::roll_damage"} 0.00031
+    // compute_time_seconds_count{function="rust_101::roll_damage"} 1000
+
+    let json = metrics::report(ReportFormat::Json);
+    println!(
+        "json report of {} functions",
+        json.matches("\"function\"").count()
+    );
+}
+
+// most steps are instant, only the ones crossing a river take a while
+#[compute_time(threshold = "1ms", unit = "ms")]
+fn path_step(step: u64) -> u64 {
+    if step.is_multiple_of(40) {
+        thread::sleep(Duration::from_millis(2));
+    }
+    step
+}
+
+#[compute_time(sample = 250)]
+fn spawn_monster(id: u64) -> u64 {
+    id * 7
+}
+
+// a frame every few milliseconds, reported at most every 20ms
+#[compute_time(flush = "20ms", unit = "ms")]
+fn render_frame(frame: u64) -> u64 {
+    thread::sleep(Duration::from_millis(2));
+    frame
+}
+
+fn sample_timing_hot_paths() {
+    // only the slow steps are printed
+    let steps: u64 = (1..=100).map(path_step).sum();
+    println!("walked {} steps", steps);
+    // output:
+    // path_step took 2.081ms
+    // path_step took 2.075ms
+    // walked 5050 steps
+
+    // calls 1, 251, 501 and 751 are printed
+    let monsters: u64 = (0..1_000).map(spawn_monster).sum();
+    println!("spawned monsters worth {}", monsters);
+    // output:
+    // spawn_monster took 260ns
+    // ...
+    // spawned monsters worth 3496500
+
+    for frame in 0..30 {
+        render_frame(frame);
+    }
+    // what is still pending is reported now instead of at the next flush
+    flush_all();
+    // output:
+    // render_frame took 22.370ms over 10 calls, mean 2.237ms, slowest 3.433ms
+    // render_frame took 19.976ms over 9 calls, mean 2.220ms, slowest 3.364ms
+    // render_frame took 20.964ms over 10 calls, mean 2.096ms, slowest 2.180ms
+    // render_frame took 2.071ms
+}
+
+#[compute_time(sink = "none")]
+fn run_quest() {
+    travel_leg();
+    fight();
+    travel_leg();
+}
+
+#[compute_time(sink = "none")]
+fn travel_leg() {
+    thread::sleep(Duration::from_millis(3));
+}
+
+#[compute_time(sink = "none")]
+fn fight() {
+    for _ in 0..3 {
+        swing();
+    }
+    // looting takes a moment too, that time belongs to fight itself
+    thread::sleep(Duration::from_millis(1));
+}
+
+#[compute_time(sink = "none")]
+fn swing() {
+    thread::sleep(Duration::from_millis(1));
+}
+
+fn sample_timing_spans() {
+    run_quest();
+
+    // each timed function remembers which timed functions it was called from
+    let tree = spans::tree();
+    let quest = &tree["main"].children["run_quest"];
+    println!(
+        "run_quest: {:.1?} inclusive, {:.1?} exclusive",
+        quest.inclusive, quest.exclusive
+    );
+    for (name, node) in quest.children.iter() {
+        println!(
+            "  {} x{}: {:.1?} inclusive, {:.1?} exclusive",
+            name, node.calls, node.inclusive, node.exclusive
+        );
+    }
+    // output:
+    // run_quest: 10.6ms inclusive, 85.5µs exclusive
+    //   fight x1: 4.3ms inclusive, 1.1ms exclusive
+    //   travel_leg x2: 6.1ms inclusive, 6.1ms exclusive
+
+    // flamegraph tools read this format, e.g. inferno-flamegraph < quest.folded > quest.svg
+    for line in spans::folded()
+        .lines()
+        .filter(|line| line.contains("run_quest"))
+    {
+        println!("{}", line);
+    }
+    // output:
+    // main;run_quest 85
+    // main;run_quest;fight 1074
+    // main;run_quest;fight;swing 3215
+    // main;run_quest;travel_leg 6140
+}
+
+fn sample_crabby_states() {
+    // Display, FromStr and the variant list come from #[derive(StateEnum)]
+    println!("Crabby states: {}", CrabbyState::VARIANTS.join(", "));
+    for state in CrabbyState::iter() {
+        println!("{:>12} is {}", state, state.variant_name());
+    }
+    // output:
+    // Crabby states: Resting, Fighting, Collecting, Defending
+    //      Resting is Resting
+    //     Fighting is Fighting
+    // Collecting 0 is Collecting
+    //    Defending is Defending
+
+    // a state read back from a save file or a console command
+    for text in ["Collecting 15", "Defending", "Sleeping"] {
+        match text.parse::<CrabbyState>() {
+            Ok(state) => println!("parsed {:?}", state),
+            Err(error) => println!("{}", error),
+        }
+    }
+    // output:
+    // parsed Collecting(15)
+    // parsed Defending
+    // `Sleeping` is not a CrabbyState, expected one of Resting, Fighting, Collecting, Defending
+}
+
+// the loot table of a dungeon level, the same levels are rolled again and again
+// from every worker, so they share one cache
+#[memoize(capacity = 16, ttl = "10s", scope = "global")]
+fn loot_table(level: u32) -> Vec<u32> {
+    (1..=5)
+        .map(|tier| power(level as i32, tier) as u32)
+        .collect()
+}
+
+fn sample_memoized_calculations() {
+    for _ in 0..3 {
+        println!("3^4 = {}", power(3, 4));
+    }
+    // output: 3^4 = 81 (three times, only the first one multiplies)
+
+    let pool = WorkerPool::new(3, 6);
+    let tables = pool.batch(vec![2, 3, 2, 3, 2, 3], loot_table).unwrap();
+    for (level, table) in tables.in_submission_order().into_iter().take(2).enumerate() {
+        println!("loot table of level {}: {:?}", level + 2, table.unwrap());
+    }
+    // output:
+    // loot table of level 2: [2, 4, 8, 16, 32]
+    // loot table of level 3: [3, 9, 27, 81, 243]
+
+    for stats in memo::stats() {
+        println!(
+            "{}: {} hits, {} misses",
+            stats.path, stats.hits, stats.misses
+        );
+    }
+    // output (power keeps a cache per thread and the workers may race
+    // to fill the loot tables, so the numbers vary from run to run):
+    // rust_101::calculator_2::power::power: 2 hits, 12 misses
+    // rust_101::loot_table: 4 hits, 2 misses
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum ChestError {
+    // the lock sticks, turning the key again may open it
+    Jammed,
+    InvalidKey,
+}
+
+impl ChestError {
+    fn is_jammed(&self) -> bool {
+        *self == ChestError::Jammed
+    }
+}
+
+fn print_attempt<E: fmt::Debug>(attempt: &Attempt<E>) {
+    println!(
+        "{} attempt {}/{}: {:?}, retry in {:?}",
+        attempt.function, attempt.number, attempt.times, attempt.error, attempt.retry_in
+    );
+}
+
+// like open_treasure, but the lock of an old chest sticks the first `sticks` turns
+#[retry(
+    times = 3,
+    backoff = "exponential",
+    delay = "1ms",
+    retry_if = "ChestError::is_jammed",
+    on_attempt = "print_attempt"
+)]
+fn open_rusty_treasure(key: &str, sticks: &Cell<u32>) -> Result<String, ChestError> {
+    if key != "correct-key" {
+        return Err(ChestError::InvalidKey);
+    }
+    if sticks.get() > 0 {
+        sticks.set(sticks.get() - 1);
+        return Err(ChestError::Jammed);
+    }
+    Ok("You got 10$".to_string())
+}
+
+// A pigeon that has to fly through a few storms, each one sends it back home
+#[derive(Clone, serde::Serialize, serde::Deserialize)]
+struct StormyPigeon {
+    storms: Cell<u32>,
+}
+
+impl TypeTag for StormyPigeon {
+    const TYPE_TAG: &'static str = "StormyPigeon";
+}
+
+impl QuestNotifier for StormyPigeon {
+    fn notify(&self) {
+        println!("Quest completed, notified by a soaked pigeon");
+    }
+
+    fn deliver(&self) -> Result<(), DeliveryError> {
+        if self.storms.get() > 0 {
+            self.storms.set(self.storms.get() - 1);
+            return Err(DeliveryError::Lost("caught in a storm".to_string()));
+        }
+        self.notify();
+        Ok(())
+    }
+}
+
+fn sample_retrying_fallible_calls() {
+    println!("{:?}", open_rusty_treasure("correct-key", &Cell::new(2)));
+    // output:
+    // open_rusty_treasure attempt 1/3: Jammed, retry in Some(1ms)
+    // open_rusty_treasure attempt 2/3: Jammed, retry in Some(2ms)
+    // Ok("You got 10$")
+
+    // a wrong key is not worth a second try
+    println!("{:?}", open_rusty_treasure("incorrect-key", &Cell::new(0)));
+    // output:
+    // open_rusty_treasure attempt 1/3: InvalidKey, retry in None
+    // Err(InvalidKey)
+
+    let quest_manager = QuestManager;
+    let pigeon = StormyPigeon {
+        storms: Cell::new(2),
+    };
+    let delivered = quest_manager.complete_quest_reliably(&pigeon);
+    println!("delivered: {:?}", delivered);
+    // output:
+    // delivery 1/4 failed, the messenger got lost: caught in a storm, sending again in 5ms
+    // delivery 2/4 failed, the messenger got lost: caught in a storm, sending again in 10ms
+    // Quest completed, notified by a soaked pigeon
+    // delivered: Ok(())
+
+    let pigeon = StormyPigeon {
+        storms: Cell::new(10),
+    };
+    if let Err(error) = quest_manager.complete_quest_reliably(&pigeon) {
+        println!("not delivered: {}", error);
+    }
+    // output:
+    // delivery 1/4 failed, the messenger got lost: caught in a storm, sending again in 5ms
+    // delivery 2/4 failed, the messenger got lost: caught in a storm, sending again in 10ms
+    // delivery 3/4 failed, the messenger got lost: caught in a storm, sending again in 20ms
+    // delivery 4/4 failed, the messenger got lost: caught in a storm, giving up
+    // not delivered: the messenger got lost: caught in a storm
+}
+
+fn sample_spells() {
+    let fire = spell!(fire);
+    println!("{}: {:?}, {} mana", fire.name, fire.effects, fire.cost.mana);
+    // output: Fire: [Damage(12)], 10 mana
+
+    // each element of a combo adds its own effect, power scales them and wind spreads them
+    // around the target
+    let storm = spell!(fire + wind, power = 3);
+    println!(
+        "{}: {:?} on {:?}, {} mana, cooldown {}",
+        storm.name, storm.effects, storm.targeting, storm.cost.mana, storm.cooldown
+    );
+    // output: Fire + Wind III: [Damage(36), Damage(18)] on Area { radius: 1 }, 48 mana, cooldown 3
+
+    let spring = spell!(water + light, target = self, name = "Healing Spring");
+    println!(
+        "{}: {:?} on {:?}",
+        spring.name, spring.effects, spring.targeting
+    );
+    // output: Healing Spring: [Heal(10), ApplyStatus { status: Regenerating { heal: 4 }, turns: 3 }] on SelfOnly
+
+    // spell!(fire + ice) does not compile:
+    // unknown element `ice`, expected one of fire, water, wind, earth, poison, light
+    // and neither does spell!(fire + water):
+    // `water` heals and `fire` hurts, a spell can not do both to its targets
+
+    // spells are abilities like any other, learned and cast the same way
+    let catalog = catalog::default_catalog();
+    let mut combat = Combat::new();
+    let mage = combat.join(Combatant::from_character("Mage", &Mage::new(), &catalog));
+    let warrior = combat.join(Combatant::from_character(
+        "Warrior",
+        &Warrior::new(),
+        &catalog,
+    ));
+    combat.combatants[mage]
+        .skills
+        .learn(spell!(earth + poison, power = 2))
+        .unwrap();
+
+    let report = combat
+        .cast(mage, "Earth + Poison II", Target::One(warrior))
+        .unwrap();
+    println!("Mage cast {}: {:?}", report.ability, report.outcomes);
+    // output: Mage cast Earth + Poison II: [Damaged { target: 1, amount: 8 }, StatusApplied { target: 1, status: Stunned }, StatusApplied { target: 1, status: Poisoned { damage: 6 } }]
+}
@@ -0,0 +1,366 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    pub x: i32,
+    pub y: i32,
+}
+
+impl Position {
+    pub fn new(x: i32, y: i32) -> Self {
+        Self { x, y }
+    }
+
+    // number of king moves, diagonals count as one step
+    pub fn distance(&self, other: Position) -> u32 {
+        (self.x - other.x)
+            .unsigned_abs()
+            .max((self.y - other.y).unsigned_abs())
+    }
+
+    fn neighbours(&self) -> [Position; 4] {
+        [
+            Position::new(self.x + 1, self.y),
+            Position::new(self.x - 1, self.y),
+            Position::new(self.x, self.y + 1),
+            Position::new(self.x, self.y - 1),
+        ]
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Tile {
+    Floor,
+    // blocks movement and sight
+    Wall,
+    // slow to wade through, does not block sight
+    Water,
+    // rubble, mud, bushes
+    Difficult,
+}
+
+impl Tile {
+    // action points needed to step onto the tile, None when it cannot be entered
+    pub fn move_cost(&self) -> Option<u32> {
+        match self {
+            Tile::Floor => Some(1),
+            Tile::Difficult => Some(2),
+            Tile::Water => Some(3),
+            Tile::Wall => None,
+        }
+    }
+
+    pub fn blocks_sight(&self) -> bool {
+        matches!(self, Tile::Wall)
+    }
+}
+
+// Area of effect shapes, all centred on `origin`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Shape {
+    // every tile within `radius` steps
+    Square { radius: u32 },
+    // every tile within `radius` in straight line distance
+    Circle { radius: u32 },
+    // `length` tiles after the origin in the direction of `towards`
+    Line { towards: Position, length: u32 },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TileMap {
+    width: i32,
+    height: i32,
+    tiles: Vec<Tile>,
+}
+
+impl TileMap {
+    pub fn new(width: i32, height: i32) -> Self {
+        Self {
+            width,
+            height,
+            tiles: vec![Tile::Floor; (width * height) as usize],
+        }
+    }
+
+    // one row per line: '#' wall, '~' water, '%' difficult, anything else floor
+    pub fn parse(rows: &[&str]) -> Self {
+        let height = rows.len() as i32;
+        // a tile per character, not per byte
+        let width = rows
+            .iter()
+            .map(|row| row.chars().count())
+            .max()
+            .unwrap_or(0) as i32;
+        let mut map = Self::new(width, height);
+
+        for (y, row) in rows.iter().enumerate() {
+            for (x, symbol) in row.chars().enumerate() {
+                let tile = match symbol {
+                    '#' => Tile::Wall,
+                    '~' => Tile::Water,
+                    '%' => Tile::Difficult,
+                    _ => Tile::Floor,
+                };
+                map.set(Position::new(x as i32, y as i32), tile);
+            }
+        }
+        map
+    }
+
+    pub fn width(&self) -> i32 {
+        self.width
+    }
+
+    pub fn height(&self) -> i32 {
+        self.height
+    }
+
+    pub fn contains(&self, position: Position) -> bool {
+        position.x >= 0 && position.y >= 0 && position.x < self.width && position.y < self.height
+    }
+
+    pub fn get(&self, position: Position) -> Option<Tile> {
+        if self.contains(position) {
+            Some(self.tiles[self.index(position)])
+        } else {
+            None
+        }
+    }
+
+    pub fn set(&mut self, position: Position, tile: Tile) {
+        if self.contains(position) {
+            let index = self.index(position);
+            self.tiles[index] = tile;
+        }
+    }
+
+    // every tile reachable with `action_points`, with the cheapest cost to get there
+    pub fn movement_range(&self, from: Position, action_points: u32) -> HashMap<Position, u32> {
+        let mut reached = HashMap::new();
+        let mut open = BinaryHeap::new();
+
+        reached.insert(from, 0);
+        open.push(Reverse((0, from)));
+
+        while let Some(Reverse((cost, current))) = open.pop() {
+            if reached.get(&current).is_some_and(|best| cost > *best) {
+                continue;
+            }
+
+            for next in current.neighbours() {
+                let step = match self.get(next).and_then(|tile| tile.move_cost()) {
+                    Some(step) => step,
+                    None => continue,
+                };
+                let next_cost = cost + step;
+                if next_cost > action_points {
+                    continue;
+                }
+                if reached.get(&next).is_none_or(|best| next_cost < *best) {
+                    reached.insert(next, next_cost);
+                    open.push(Reverse((next_cost, next)));
+                }
+            }
+        }
+
+        reached
+    }
+
+    // Tiles crossed by a straight line between two positions (Bresenham)
+    pub fn line(&self, from: Position, to: Position) -> Vec<Position> {
+        let (dx, dy) = ((to.x - from.x).abs(), -(to.y - from.y).abs());
+        let (step_x, step_y) = ((to.x - from.x).signum(), (to.y - from.y).signum());
+        let mut error = dx + dy;
+        let mut current = from;
+        let mut tiles = vec![current];
+
+        while current != to {
+            let doubled = 2 * error;
+            if doubled >= dy {
+                error += dy;
+                current.x += step_x;
+            }
+            if doubled <= dx {
+                error += dx;
+                current.y += step_y;
+            }
+            tiles.push(current);
+        }
+        tiles
+    }
+
+    // the end points themselves never block, walls in between do
+    pub fn line_of_sight(&self, from: Position, to: Position) -> bool {
+        let line = self.line(from, to);
+        line.iter()
+            .skip(1)
+            .take(line.len().saturating_sub(2))
+            .all(|position| self.get(*position).is_some_and(|tile| !tile.blocks_sight()))
+    }
+
+    // tiles covered by a shape that can be seen from its origin
+    pub fn area(&self, origin: Position, shape: Shape) -> Vec<Position> {
+        let candidates: Vec<Position> = match shape {
+            Shape::Square { radius } => self.around(origin, radius).collect(),
+            Shape::Circle { radius } => self
+                .around(origin, radius)
+                .filter(|position| {
+                    let (dx, dy) = (position.x - origin.x, position.y - origin.y);
+                    dx * dx + dy * dy <= (radius * radius) as i32
+                })
+                .collect(),
+            Shape::Line { towards, length } => {
+                if towards == origin {
+                    Vec::new()
+                } else {
+                    // stretch the line past `towards` so short targets still give full length
+                    let (dx, dy) = (towards.x - origin.x, towards.y - origin.y);
+                    let scale = length as i32 / dx.abs().max(dy.abs()) + 1;
+                    let end = Position::new(origin.x + dx * scale, origin.y + dy * scale);
+                    self.line(origin, end)
+                        .into_iter()
+                        .skip(1)
+                        .take(length as usize)
+                        .collect()
+                }
+            }
+        };
+
+        candidates
+            .into_iter()
+            .filter(|position| self.contains(*position) && self.line_of_sight(origin, *position))
+            .collect()
+    }
+
+    fn around(&self, origin: Position, radius: u32) -> impl Iterator<Item = Position> {
+        let radius = radius as i32;
+        (origin.y - radius..=origin.y + radius).flat_map(move |y| {
+            (origin.x - radius..=origin.x + radius).map(move |x| Position::new(x, y))
+        })
+    }
+
+    fn index(&self, position: Position) -> usize {
+        (position.y * self.width + position.x) as usize
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn walls_block_sight_but_not_the_end_points() {
+        let map = TileMap::parse(&[".....", ".#...", "....."]);
+
+        assert!(!map.line_of_sight(Position::new(0, 1), Position::new(2, 1)));
+        // looking at the wall, or out from it, is fine
+        assert!(map.line_of_sight(Position::new(0, 1), Position::new(1, 1)));
+        assert!(map.line_of_sight(Position::new(1, 1), Position::new(4, 1)));
+        // water does not block sight
+        let map = TileMap::parse(&[".~~."]);
+        assert!(map.line_of_sight(Position::new(0, 0), Position::new(3, 0)));
+    }
+
+    #[test]
+    fn movement_pays_for_water_and_difficult_terrain() {
+        let map = TileMap::parse(&[".~%.#."]);
+        let range = map.movement_range(Position::new(0, 0), 5);
+
+        let mut reached: Vec<(Position, u32)> = range.into_iter().collect();
+        reached.sort();
+        assert_eq!(
+            reached,
+            vec![
+                (Position::new(0, 0), 0),
+                (Position::new(1, 0), 3),
+                (Position::new(2, 0), 5),
+            ]
+        );
+
+        // one more point reaches the floor behind the rubble, the wall stops the rest
+        let range = map.movement_range(Position::new(0, 0), 100);
+        assert_eq!(range.get(&Position::new(3, 0)), Some(&6));
+        assert_eq!(range.get(&Position::new(5, 0)), None);
+    }
+
+    #[test]
+    fn movement_takes_the_cheapest_way_around() {
+        let map = TileMap::parse(&[".~.", "..."]);
+        let range = map.movement_range(Position::new(0, 0), 10);
+        // around the water is 3 steps, through it 4 points
+        assert_eq!(range.get(&Position::new(2, 0)), Some(&4));
+        assert_eq!(range.get(&Position::new(2, 1)), Some(&3));
+    }
+
+    #[test]
+    fn a_line_shape_is_as_long_as_asked_whatever_the_target_distance() {
+        let map = TileMap::new(10, 10);
+        let origin = Position::new(0, 0);
+
+        let line = map.area(
+            origin,
+            Shape::Line {
+                towards: Position::new(1, 0),
+                length: 3,
+            },
+        );
+        assert_eq!(
+            line,
+            vec![
+                Position::new(1, 0),
+                Position::new(2, 0),
+                Position::new(3, 0)
+            ]
+        );
+
+        let diagonal = map.area(
+            origin,
+            Shape::Line {
+                towards: Position::new(6, 6),
+                length: 2,
+            },
+        );
+        assert_eq!(diagonal, vec![Position::new(1, 1), Position::new(2, 2)]);
+
+        let nowhere = Shape::Line {
+            towards: origin,
+            length: 3,
+        };
+        assert!(map.area(origin, nowhere).is_empty());
+    }
+
+    #[test]
+    fn an_area_stops_at_walls_and_the_map_edge() {
+        let map = TileMap::parse(&["..#.."]);
+        let line = map.area(
+            Position::new(0, 0),
+            Shape::Line {
+                towards: Position::new(1, 0),
+                length: 10,
+            },
+        );
+        // the wall is hit, what is behind it is not
+        assert_eq!(line, vec![Position::new(1, 0), Position::new(2, 0)]);
+
+        let square = TileMap::new(3, 3).area(Position::new(0, 0), Shape::Square { radius: 1 });
+        assert_eq!(square.len(), 4);
+    }
+
+    #[test]
+    fn a_circle_leaves_out_the_corners() {
+        let map = TileMap::new(5, 5);
+        let circle = map.area(Position::new(2, 2), Shape::Circle { radius: 1 });
+        assert_eq!(circle.len(), 5);
+        assert!(!circle.contains(&Position::new(1, 1)));
+    }
+
+    #[test]
+    fn a_map_is_as_wide_as_its_longest_row_in_characters() {
+        let map = TileMap::parse(&["é#", "..."]);
+        assert_eq!(map.width(), 3);
+        let map = TileMap::parse(&["~é#"]);
+        assert_eq!(map.width(), 3);
+        assert_eq!(map.get(Position::new(2, 0)), Some(Tile::Wall));
+    }
+}
@@ -0,0 +1,4 @@
+pub mod grid;
+pub mod pathfinding;
+pub mod use_item;
+pub mod world;
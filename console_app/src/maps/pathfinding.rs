@@ -0,0 +1,233 @@
+use std::cmp::Reverse;
+use std::collections::BinaryHeap;
+
+use super::world::{LocationId, World};
+
+// Which locations a path may go through
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Knowledge {
+    Everything,
+    // only locations the party has discovered, see World::visit
+    Discovered,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Path {
+    // includes both the start and the destination
+    pub locations: Vec<LocationId>,
+    pub cost: u32,
+}
+
+// Cheapest path, explores in every direction
+pub fn dijkstra(
+    world: &World,
+    from: LocationId,
+    to: LocationId,
+    knowledge: Knowledge,
+) -> Option<Path> {
+    search(world, from, to, knowledge, |_| 0)
+}
+
+// Cheapest path, explores towards the destination first using map positions
+pub fn a_star(
+    world: &World,
+    from: LocationId,
+    to: LocationId,
+    knowledge: Knowledge,
+) -> Option<Path> {
+    // rounding down keeps the estimate below the real cost
+    search(world, from, to, knowledge, |id| {
+        world
+            .distance(id, to)
+            .map_or(0, |distance| distance.floor() as u32)
+    })
+}
+
+fn search<H>(
+    world: &World,
+    from: LocationId,
+    to: LocationId,
+    knowledge: Knowledge,
+    heuristic: H,
+) -> Option<Path>
+where
+    H: Fn(LocationId) -> u32,
+{
+    let count = world.location_count();
+    if from.0 >= count || to.0 >= count {
+        return None;
+    }
+
+    let allowed = |id: LocationId| match knowledge {
+        Knowledge::Everything => true,
+        Knowledge::Discovered => world.is_discovered(id),
+    };
+    if !allowed(to) {
+        return None;
+    }
+
+    let mut best_cost: Vec<Option<u32>> = vec![None; count];
+    let mut came_from: Vec<Option<LocationId>> = vec![None; count];
+    // min heap ordered by estimated total cost
+    let mut open = BinaryHeap::new();
+
+    best_cost[from.0] = Some(0);
+    open.push(Reverse((heuristic(from), 0, from)));
+
+    while let Some(Reverse((_, cost, current))) = open.pop() {
+        if current == to {
+            return Some(Path {
+                locations: rebuild(&came_from, to),
+                cost,
+            });
+        }
+        // an older, more expensive entry for a location already improved
+        if best_cost[current.0].is_some_and(|best| cost > best) {
+            continue;
+        }
+
+        for route in world.routes_from(current) {
+            if !allowed(route.to) {
+                continue;
+            }
+
+            // a route too expensive to add up can never be part of the cheapest path
+            let Some(next_cost) = cost.checked_add(route.cost) else {
+                continue;
+            };
+            if best_cost[route.to.0].is_none_or(|best| next_cost < best) {
+                best_cost[route.to.0] = Some(next_cost);
+                came_from[route.to.0] = Some(current);
+                open.push(Reverse((
+                    next_cost.saturating_add(heuristic(route.to)),
+                    next_cost,
+                    route.to,
+                )));
+            }
+        }
+    }
+
+    None
+}
+
+fn rebuild(came_from: &[Option<LocationId>], to: LocationId) -> Vec<LocationId> {
+    let mut locations = vec![to];
+    let mut current = to;
+    while let Some(previous) = came_from[current.0] {
+        locations.push(previous);
+        current = previous;
+    }
+    locations.reverse();
+    locations
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // camp -1- ford -1- tower along the road, camp -5- tower straight across
+    // and camp -6- hill -6- tower the long way round
+    fn world() -> (World, [LocationId; 4]) {
+        let mut world = World::new();
+        let region = world.add_region("Valley");
+        let camp = world.add_location("Camp", region, (0.0, 0.0));
+        let ford = world.add_location("Ford", region, (1.0, 0.0));
+        let tower = world.add_location("Tower", region, (2.0, 0.0));
+        let hill = world.add_location("Hill", region, (1.0, 5.0));
+        world.connect(camp, ford, 1);
+        world.connect(ford, tower, 1);
+        world.connect(camp, tower, 5);
+        world.connect(camp, hill, 6);
+        world.connect(hill, tower, 6);
+        (world, [camp, ford, tower, hill])
+    }
+
+    #[test]
+    fn the_cheapest_path_is_found() {
+        let (world, [camp, ford, tower, hill]) = world();
+
+        for search in [dijkstra, a_star] {
+            let path = search(&world, camp, tower, Knowledge::Everything).unwrap();
+            assert_eq!(path.locations, vec![camp, ford, tower]);
+            assert_eq!(path.cost, 2);
+
+            let path = search(&world, hill, ford, Knowledge::Everything).unwrap();
+            assert_eq!(path.locations, vec![hill, camp, ford]);
+            assert_eq!(path.cost, 7);
+        }
+    }
+
+    #[test]
+    fn only_discovered_locations_are_used_when_asked() {
+        let (mut world, [camp, _, tower, hill]) = world();
+        world.discover(camp);
+        world.discover(tower);
+
+        let path = dijkstra(&world, camp, tower, Knowledge::Discovered).unwrap();
+        assert_eq!(path.locations, vec![camp, tower]);
+        assert_eq!(path.cost, 5);
+        // an undiscovered destination can not be planned for
+        assert_eq!(dijkstra(&world, camp, hill, Knowledge::Discovered), None);
+        assert_eq!(a_star(&world, camp, hill, Knowledge::Discovered), None);
+    }
+
+    #[test]
+    fn an_unreachable_location_has_no_path() {
+        let (mut world, [camp, ..]) = world();
+        let region = world.add_region("Island");
+        let island = world.add_location("Island", region, (9.0, 9.0));
+        // there is a way back from the island, but none out to it
+        world.connect_one_way(island, camp, 20);
+
+        assert_eq!(dijkstra(&world, camp, island, Knowledge::Everything), None);
+        assert_eq!(a_star(&world, camp, island, Knowledge::Everything), None);
+        assert!(a_star(&world, island, camp, Knowledge::Everything).is_some());
+        assert_eq!(
+            dijkstra(&world, camp, LocationId(99), Knowledge::Everything),
+            None
+        );
+    }
+
+    #[test]
+    fn a_route_too_expensive_to_add_up_is_skipped() {
+        let (mut world, [camp, ford, tower, _]) = world();
+        world.connect_one_way(camp, ford, u32::MAX);
+        world.connect_one_way(ford, tower, u32::MAX);
+
+        let path = dijkstra(&world, camp, tower, Knowledge::Everything).unwrap();
+        assert_eq!(path.cost, 2);
+    }
+
+    #[test]
+    fn a_star_and_dijkstra_agree_on_the_cost() {
+        // a 4 x 4 grid of roads costing 1 to 3, diagonals cost 2
+        let mut world = World::new();
+        let region = world.add_region("Plains");
+        let ids: Vec<LocationId> = (0..16)
+            .map(|i| {
+                let (x, y) = (i % 4, i / 4);
+                world.add_location(&format!("{}", i), region, (x as f64, y as f64))
+            })
+            .collect();
+        for i in 0..16 {
+            let (x, y) = (i % 4, i / 4);
+            if x < 3 {
+                world.connect(ids[i], ids[i + 1], 1 + (i as u32 * 7) % 3);
+            }
+            if y < 3 {
+                world.connect(ids[i], ids[i + 4], 1 + (i as u32 * 5) % 3);
+            }
+            if x < 3 && y < 3 {
+                world.connect(ids[i], ids[i + 5], 2);
+            }
+        }
+
+        for &from in ids.iter() {
+            for &to in ids.iter() {
+                let slow = dijkstra(&world, from, to, Knowledge::Everything).unwrap();
+                let fast = a_star(&world, from, to, Knowledge::Everything).unwrap();
+                assert_eq!(slow.cost, fast.cost, "{:?} to {:?}", from, to);
+            }
+        }
+    }
+}
@@ -0,0 +1,3 @@
+pub fn use_item() {
+    println!("Use a map");
+}
@@ -0,0 +1,136 @@
+use std::collections::HashSet;
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct RegionId(pub usize);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub struct LocationId(pub usize);
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Region {
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Location {
+    pub name: String,
+    pub region: RegionId,
+    // map coordinates, used by A* to estimate the remaining distance
+    pub position: (f64, f64),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Route {
+    pub to: LocationId,
+    pub cost: u32,
+}
+
+// Regions and locations connected by routes, plus what the party has discovered so far
+#[derive(Debug, Clone, Default)]
+pub struct World {
+    regions: Vec<Region>,
+    locations: Vec<Location>,
+    routes: Vec<Vec<Route>>,
+    discovered: HashSet<LocationId>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add_region(&mut self, name: &str) -> RegionId {
+        self.regions.push(Region {
+            name: name.to_string(),
+        });
+        RegionId(self.regions.len() - 1)
+    }
+
+    pub fn add_location(
+        &mut self,
+        name: &str,
+        region: RegionId,
+        position: (f64, f64),
+    ) -> LocationId {
+        self.locations.push(Location {
+            name: name.to_string(),
+            region,
+            position,
+        });
+        self.routes.push(Vec::new());
+        LocationId(self.locations.len() - 1)
+    }
+
+    // A* expects the cost of a route to be at least the straight line distance
+    // between its ends, otherwise it may not find the cheapest path
+    pub fn connect(&mut self, a: LocationId, b: LocationId, cost: u32) {
+        self.connect_one_way(a, b, cost);
+        self.connect_one_way(b, a, cost);
+    }
+
+    pub fn connect_one_way(&mut self, from: LocationId, to: LocationId, cost: u32) {
+        self.routes[from.0].push(Route { to, cost });
+    }
+
+    pub fn region(&self, id: RegionId) -> Option<&Region> {
+        self.regions.get(id.0)
+    }
+
+    pub fn location(&self, id: LocationId) -> Option<&Location> {
+        self.locations.get(id.0)
+    }
+
+    pub fn location_by_name(&self, name: &str) -> Option<LocationId> {
+        self.locations
+            .iter()
+            .position(|location| location.name == name)
+            .map(LocationId)
+    }
+
+    pub fn locations_in(&self, region: RegionId) -> Vec<LocationId> {
+        (0..self.locations.len())
+            .map(LocationId)
+            .filter(|id| self.locations[id.0].region == region)
+            .collect()
+    }
+
+    pub fn location_count(&self) -> usize {
+        self.locations.len()
+    }
+
+    pub fn routes_from(&self, id: LocationId) -> &[Route] {
+        self.routes.get(id.0).map(Vec::as_slice).unwrap_or(&[])
+    }
+
+    // straight line distance on the map, None when either location does not exist
+    pub fn distance(&self, a: LocationId, b: LocationId) -> Option<f64> {
+        let (ax, ay) = self.location(a)?.position;
+        let (bx, by) = self.location(b)?.position;
+        Some(((ax - bx).powi(2) + (ay - by).powi(2)).sqrt())
+    }
+
+    // Fog of war: a location is hidden until discovered,
+    // visiting a location also reveals everything one route away
+    pub fn is_discovered(&self, id: LocationId) -> bool {
+        self.discovered.contains(&id)
+    }
+
+    pub fn discover(&mut self, id: LocationId) {
+        self.discovered.insert(id);
+    }
+
+    pub fn visit(&mut self, id: LocationId) {
+        self.discover(id);
+        let neighbours: Vec<LocationId> =
+            self.routes_from(id).iter().map(|route| route.to).collect();
+        self.discovered.extend(neighbours);
+    }
+
+    pub fn discovered(&self) -> Vec<LocationId> {
+        let mut discovered: Vec<LocationId> = self.discovered.iter().copied().collect();
+        discovered.sort();
+        discovered
+    }
+}
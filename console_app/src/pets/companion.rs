@@ -0,0 +1,245 @@
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use super::species::Species;
+use crate::combat::combatant::Combatant;
+use crate::skills::ability::Learner;
+use crate::skills::catalog::{self, Unlock};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Command {
+    Sit,
+    Fetch,
+    Attack,
+    Guard,
+}
+
+impl Command {
+    // a pet must trust its owner this much before it learns the command
+    pub fn required_loyalty(&self) -> u8 {
+        match self {
+            Command::Sit => 0,
+            Command::Fetch => 20,
+            Command::Guard => 40,
+            Command::Attack => 60,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PetError {
+    CannotLearn { species: String, command: Command },
+    NotLoyalEnough { needed: u8, loyalty: u8 },
+    NotTrained(Command),
+    Refuses(String),
+}
+
+impl fmt::Display for PetError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PetError::CannotLearn { species, command } => {
+                write!(f, "a {} cannot learn {:?}", species, command)
+            }
+            PetError::NotLoyalEnough { needed, loyalty } => {
+                write!(f, "needs {} loyalty, has {}", needed, loyalty)
+            }
+            PetError::NotTrained(command) => write!(f, "{:?} has not been taught", command),
+            PetError::Refuses(name) => write!(f, "{} ignores the command", name),
+        }
+    }
+}
+
+// below this loyalty a pet ignores every command
+const STUBBORN: u8 = 10;
+
+// A pet following a character around, with its own stats and training
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Companion {
+    pub name: String,
+    pub species: Species,
+    pub owner: Option<String>,
+    pub level: u32,
+    pub experience: u32,
+    pub health: u32,
+    pub max_health: u32,
+    pub attack: u32,
+    // 0 to 100
+    pub loyalty: u8,
+    pub commands: Vec<Command>,
+}
+
+impl Companion {
+    pub fn new(name: &str, species: &Species) -> Self {
+        Self {
+            name: name.to_string(),
+            species: species.clone(),
+            owner: None,
+            level: 1,
+            experience: 0,
+            health: species.health,
+            max_health: species.health,
+            attack: species.attack,
+            loyalty: 15,
+            commands: Vec::new(),
+        }
+    }
+
+    pub fn follow(&mut self, owner: &str) {
+        self.owner = Some(owner.to_string());
+    }
+
+    pub fn stay(&mut self) {
+        self.owner = None;
+    }
+
+    pub fn make_noise(&self) -> String {
+        format!("{} is {}", self.name, self.species.sound)
+    }
+
+    // petting builds trust
+    pub fn pet(&mut self) {
+        self.change_loyalty(5);
+    }
+
+    pub fn knows(&self, command: Command) -> bool {
+        self.commands.contains(&command)
+    }
+
+    pub fn teach(&mut self, command: Command) -> Result<(), PetError> {
+        if !self.species.commands.contains(&command) {
+            return Err(PetError::CannotLearn {
+                species: self.species.name.clone(),
+                command,
+            });
+        }
+
+        let needed = command.required_loyalty();
+        if self.loyalty < needed {
+            return Err(PetError::NotLoyalEnough {
+                needed,
+                loyalty: self.loyalty,
+            });
+        }
+
+        if !self.knows(command) {
+            self.commands.push(command);
+        }
+        Ok(())
+    }
+
+    pub fn obey(&mut self, command: Command) -> Result<String, PetError> {
+        if !self.knows(command) {
+            return Err(PetError::NotTrained(command));
+        }
+        if self.loyalty < STUBBORN {
+            return Err(PetError::Refuses(self.name.clone()));
+        }
+
+        self.change_loyalty(2);
+        self.gain_experience(5);
+        Ok(match command {
+            Command::Sit => format!("{} is sitting", self.name),
+            Command::Fetch => format!("{} fetched the stick", self.name),
+            Command::Attack => format!("{} is ready to attack", self.name),
+            Command::Guard => format!("{} is guarding", self.name),
+        })
+    }
+
+    // every level needs level * 100 experience, returns the levels gained
+    pub fn gain_experience(&mut self, amount: u32) -> u32 {
+        self.experience += amount;
+        let mut gained = 0;
+        while self.experience >= self.level * 100 {
+            self.experience -= self.level * 100;
+            self.level += 1;
+            self.max_health += 5;
+            self.attack += 1;
+            self.health = self.max_health;
+            gained += 1;
+        }
+        gained
+    }
+
+    // only a pet trained to attack joins a fight
+    pub fn to_combatant(&self, catalog: &[Unlock]) -> Result<Combatant, PetError> {
+        if !self.knows(Command::Attack) {
+            return Err(PetError::NotTrained(Command::Attack));
+        }
+
+        let mut combatant = Combatant::new(
+            &self.name,
+            Learner::Pet(self.species.clone()),
+            self.health,
+            0,
+            20 + self.attack * 2,
+        );
+        combatant.max_health = self.max_health;
+        combatant.level = self.level;
+        for name in self.species.abilities.iter() {
+            if let Some(ability) = catalog::find(catalog, name) {
+                let _ = combatant.skills.learn(ability);
+            }
+        }
+        Ok(combatant)
+    }
+
+    // carries wounds over from combat and rewards surviving it
+    pub fn after_combat(&mut self, combatant: &Combatant, experience: u32) -> u32 {
+        self.health = combatant.health;
+        if combatant.is_alive() {
+            self.change_loyalty(3);
+            self.gain_experience(experience)
+        } else {
+            0
+        }
+    }
+
+    fn change_loyalty(&mut self, amount: u8) {
+        self.loyalty = self.loyalty.saturating_add(amount).min(100);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_oop::characters::CharacterClass;
+    use crate::pets::species::find_species;
+    use crate::skills::skill_book::SkillBook;
+
+    fn trained(species: &str) -> Companion {
+        let mut pet = Companion::new(species, &find_species(species).unwrap());
+        pet.loyalty = Command::Attack.required_loyalty();
+        pet.teach(Command::Attack).unwrap();
+        pet
+    }
+
+    #[test]
+    fn a_pet_learns_only_the_abilities_of_its_species() {
+        let catalog = catalog::default_catalog();
+        let mut combatant = trained("Dog").to_combatant(&catalog).unwrap();
+
+        // levelling up goes through the whole catalog again
+        let learned = combatant.level_up(&catalog);
+        assert!(learned.is_empty());
+        let names: Vec<&str> = combatant
+            .skills
+            .abilities()
+            .iter()
+            .map(|ability| ability.name.as_str())
+            .collect();
+        assert_eq!(names, vec!["Bite"]);
+    }
+
+    #[test]
+    fn pet_abilities_stay_with_the_species_listing_them() {
+        let mut cat = trained("Cat")
+            .to_combatant(&catalog::default_catalog())
+            .unwrap();
+        assert!(cat.skills.knows("Scratch"));
+        assert!(cat.skills.learn(catalog::bite()).is_err());
+
+        let mut warrior = SkillBook::new(Learner::Class(CharacterClass::Warrior));
+        assert!(warrior.learn(catalog::bite()).is_err());
+    }
+}
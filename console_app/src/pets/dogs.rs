@@ -1,4 +1,11 @@
 pub mod dog {
+  use crate::pets::species::{find_species, Species};
+
+  // the dog is one species among those in pets::species
+  pub fn species() -> Species {
+    find_species("Dog").expect("dogs are part of the built in species")
+  }
+
   pub fn bark() {
     println!("Dog is barking");
   }
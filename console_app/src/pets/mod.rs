@@ -1 +1,3 @@
+pub mod companion;
 pub mod dogs;
+pub mod species;
@@ -0,0 +1,59 @@
+use serde::{Deserialize, Serialize};
+
+use super::companion::Command;
+
+// Everything that makes one kind of pet different from another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Species {
+    pub name: String,
+    // "Dog is barking"
+    pub sound: String,
+    pub health: u32,
+    pub attack: u32,
+    // commands this species is able to learn at all
+    pub commands: Vec<Command>,
+    // abilities from the skill catalog used once trained to attack
+    pub abilities: Vec<String>,
+}
+
+// New species only need a new entry here, no code changes
+const SPECIES_DATA: &str = r#"[
+    {
+        "name": "Dog",
+        "sound": "barking",
+        "health": 60,
+        "attack": 8,
+        "commands": ["Sit", "Fetch", "Attack", "Guard"],
+        "abilities": ["Bite"]
+    },
+    {
+        "name": "Cat",
+        "sound": "meowing",
+        "health": 40,
+        "attack": 6,
+        "commands": ["Sit", "Attack"],
+        "abilities": ["Scratch"]
+    },
+    {
+        "name": "Hawk",
+        "sound": "screeching",
+        "health": 30,
+        "attack": 10,
+        "commands": ["Fetch", "Attack"],
+        "abilities": ["Dive"]
+    }
+]"#;
+
+pub fn all_species() -> Vec<Species> {
+    parse_species(SPECIES_DATA).expect("built in species data is valid")
+}
+
+pub fn parse_species(json: &str) -> serde_json::Result<Vec<Species>> {
+    serde_json::from_str(json)
+}
+
+pub fn find_species(name: &str) -> Option<Species> {
+    all_species()
+        .into_iter()
+        .find(|species| species.name == name)
+}
@@ -0,0 +1,237 @@
+// Type-tagged registry for trait objects
+//
+// serde cannot (de)serialize Box<dyn Trait> on its own because the concrete type
+// is gone after boxing. Every concrete type gets a tag, the box is written as
+// { "type": tag, "value": fields } and the tag is looked up in the registry of
+// the trait to find a constructor when reading it back.
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::RwLock;
+
+use serde::de::{self, Deserializer};
+use serde::ser::Serializer;
+use serde::{Deserialize, Serialize};
+
+pub use serde_json::Value;
+
+// used by the macros below so callers do not need serde in scope
+#[doc(hidden)]
+pub use serde;
+#[doc(hidden)]
+pub use serde_json;
+
+// Stable name of a concrete type, it is written into save files
+pub trait TypeTag {
+    const TYPE_TAG: &'static str;
+}
+
+// Object safe half of TypeTag + Serialize, used as a supertrait of registered traits
+pub trait Tagged {
+    fn type_tag(&self) -> &'static str;
+    fn to_value(&self) -> serde_json::Result<Value>;
+}
+
+impl<T: TypeTag + Serialize> Tagged for T {
+    fn type_tag(&self) -> &'static str {
+        T::TYPE_TAG
+    }
+
+    fn to_value(&self) -> serde_json::Result<Value> {
+        serde_json::to_value(self)
+    }
+}
+
+#[derive(Debug)]
+pub enum RegistryError {
+    UnknownTag(String),
+    Json(serde_json::Error),
+}
+
+impl fmt::Display for RegistryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            RegistryError::UnknownTag(tag) => write!(f, "no type registered as {}", tag),
+            RegistryError::Json(error) => write!(f, "{}", error),
+        }
+    }
+}
+
+impl std::error::Error for RegistryError {}
+
+pub type Constructor<T> = fn(Value) -> serde_json::Result<Box<T>>;
+
+pub struct Registry<T: ?Sized> {
+    constructors: HashMap<&'static str, Constructor<T>>,
+}
+
+impl<T: ?Sized> Registry<T> {
+    pub fn new() -> Self {
+        Self {
+            constructors: HashMap::new(),
+        }
+    }
+
+    // registering the same tag twice replaces the previous constructor
+    pub fn register(&mut self, tag: &'static str, constructor: Constructor<T>) {
+        self.constructors.insert(tag, constructor);
+    }
+
+    pub fn contains(&self, tag: &str) -> bool {
+        self.constructors.contains_key(tag)
+    }
+
+    pub fn tags(&self) -> Vec<&'static str> {
+        let mut tags: Vec<&'static str> = self.constructors.keys().copied().collect();
+        tags.sort();
+        tags
+    }
+
+    pub fn construct(&self, tag: &str, value: Value) -> Result<Box<T>, RegistryError> {
+        let constructor = self
+            .constructors
+            .get(tag)
+            .ok_or_else(|| RegistryError::UnknownTag(tag.to_string()))?;
+        constructor(value).map_err(RegistryError::Json)
+    }
+}
+
+impl<T: ?Sized> Default for Registry<T> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// A trait object type with one global registry, implemented by `trait_object!`
+pub trait Registered: 'static {
+    fn registry() -> &'static RwLock<Registry<Self>>;
+}
+
+pub fn register<T: ?Sized + Registered>(tag: &'static str, constructor: Constructor<T>) {
+    T::registry().write().unwrap().register(tag, constructor);
+}
+
+pub fn construct<T: ?Sized + Registered>(tag: &str, value: Value) -> Result<Box<T>, RegistryError> {
+    T::registry().read().unwrap().construct(tag, value)
+}
+
+pub fn tags<T: ?Sized + Registered>() -> Vec<&'static str> {
+    T::registry().read().unwrap().tags()
+}
+
+// On the wire the value is kept as is for self describing formats like JSON,
+// and as a JSON string for formats like bincode that cannot read a Value back
+#[derive(Serialize, Deserialize)]
+struct TaggedRepr<V> {
+    #[serde(rename = "type")]
+    tag: String,
+    value: V,
+}
+
+pub fn serialize_tagged<S: Serializer>(
+    object: &dyn Tagged,
+    serializer: S,
+) -> Result<S::Ok, S::Error> {
+    use serde::ser::Error;
+
+    let value = object.to_value().map_err(S::Error::custom)?;
+    let tag = object.type_tag().to_string();
+    if serializer.is_human_readable() {
+        TaggedRepr { tag, value }.serialize(serializer)
+    } else {
+        let value = value.to_string();
+        TaggedRepr { tag, value }.serialize(serializer)
+    }
+}
+
+pub fn deserialize_tagged<'de, T, D>(deserializer: D) -> Result<Box<T>, D::Error>
+where
+    T: ?Sized + Registered,
+    D: Deserializer<'de>,
+{
+    let (tag, value) = if deserializer.is_human_readable() {
+        let repr = TaggedRepr::<Value>::deserialize(deserializer)?;
+        (repr.tag, repr.value)
+    } else {
+        let repr = TaggedRepr::<String>::deserialize(deserializer)?;
+        let value = serde_json::from_str(&repr.value).map_err(de::Error::custom)?;
+        (repr.tag, value)
+    };
+    construct::<T>(&tag, value).map_err(de::Error::custom)
+}
+
+// Makes `dyn $trait` clonable, serializable and registered.
+// `$clone_trait` becomes a supertrait of `$trait` that every `Clone` implementor gets for free,
+// `$defaults` fills the registry the first time it is used.
+#[macro_export]
+macro_rules! trait_object {
+    ($trait:ident, $clone_trait:ident, $defaults:path) => {
+        pub trait $clone_trait {
+            fn clone_box(&self) -> Box<dyn $trait>;
+        }
+
+        impl<T: $trait + Clone + 'static> $clone_trait for T {
+            fn clone_box(&self) -> Box<dyn $trait> {
+                Box::new(self.clone())
+            }
+        }
+
+        impl Clone for Box<dyn $trait> {
+            fn clone(&self) -> Self {
+                self.clone_box()
+            }
+        }
+
+        impl $crate::registry::Registered for dyn $trait {
+            fn registry() -> &'static std::sync::RwLock<$crate::registry::Registry<Self>> {
+                static REGISTRY: std::sync::OnceLock<
+                    std::sync::RwLock<$crate::registry::Registry<dyn $trait>>,
+                > = std::sync::OnceLock::new();
+                REGISTRY.get_or_init(|| {
+                    let mut registry = $crate::registry::Registry::new();
+                    $defaults(&mut registry);
+                    std::sync::RwLock::new(registry)
+                })
+            }
+        }
+
+        impl $crate::registry::serde::Serialize for Box<dyn $trait> {
+            fn serialize<S: $crate::registry::serde::Serializer>(
+                &self,
+                serializer: S,
+            ) -> Result<S::Ok, S::Error> {
+                $crate::registry::serialize_tagged(self.as_ref(), serializer)
+            }
+        }
+
+        impl<'de> $crate::registry::serde::Deserialize<'de> for Box<dyn $trait> {
+            fn deserialize<D: $crate::registry::serde::Deserializer<'de>>(
+                deserializer: D,
+            ) -> Result<Self, D::Error> {
+                $crate::registry::deserialize_tagged::<dyn $trait, D>(deserializer)
+            }
+        }
+    };
+}
+
+// Registers a concrete type under its TypeTag, either in a registry being filled
+// `register_type!(registry, Sword)` or in the global one `register_type!(dyn Weapon, Sword)`
+#[macro_export]
+macro_rules! register_type {
+    (dyn $trait:path, $type:ty) => {
+        $crate::registry::register::<dyn $trait>(
+            <$type as $crate::registry::TypeTag>::TYPE_TAG,
+            |value| {
+                Ok(Box::new($crate::registry::serde_json::from_value::<$type>(
+                    value,
+                )?))
+            },
+        )
+    };
+    ($registry:expr, $type:ty) => {
+        $registry.register(<$type as $crate::registry::TypeTag>::TYPE_TAG, |value| {
+            Ok(Box::new($crate::registry::serde_json::from_value::<$type>(
+                value,
+            )?))
+        })
+    };
+}
@@ -0,0 +1,57 @@
+use std::{fmt, io};
+
+#[derive(Debug)]
+pub enum SaveError {
+    Io(io::Error),
+    Json(serde_json::Error),
+    Binary(String),
+    // the stored checksum does not match the saved data
+    Corrupted { expected: u32, actual: u32 },
+    UnsupportedVersion(u32),
+    UnknownWeapon(String),
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SaveError::Io(error) => write!(f, "io error: {}", error),
+            SaveError::Json(error) => write!(f, "json error: {}", error),
+            SaveError::Binary(error) => write!(f, "binary error: {}", error),
+            SaveError::Corrupted { expected, actual } => write!(
+                f,
+                "corrupted save: checksum {:08x} does not match {:08x}",
+                actual, expected
+            ),
+            SaveError::UnsupportedVersion(version) => {
+                write!(f, "unsupported save version {}", version)
+            }
+            SaveError::UnknownWeapon(name) => write!(f, "unknown weapon {}", name),
+        }
+    }
+}
+
+impl std::error::Error for SaveError {}
+
+impl From<io::Error> for SaveError {
+    fn from(error: io::Error) -> Self {
+        SaveError::Io(error)
+    }
+}
+
+impl From<serde_json::Error> for SaveError {
+    fn from(error: serde_json::Error) -> Self {
+        SaveError::Json(error)
+    }
+}
+
+impl From<bincode::error::EncodeError> for SaveError {
+    fn from(error: bincode::error::EncodeError) -> Self {
+        SaveError::Binary(error.to_string())
+    }
+}
+
+impl From<bincode::error::DecodeError> for SaveError {
+    fn from(error: bincode::error::DecodeError) -> Self {
+        SaveError::Binary(error.to_string())
+    }
+}
@@ -0,0 +1,270 @@
+use std::fs;
+use std::path::Path;
+
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use super::error::SaveError;
+use super::game_state::GameState;
+use super::legacy::GameStateV1;
+
+pub const CURRENT_VERSION: u32 = 2;
+
+// binary saves start with MAGIC, then version and checksum as little endian u32
+const MAGIC: &[u8; 4] = b"R101";
+const HEADER_LEN: usize = 12;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SaveFormat {
+    // human readable, easy to inspect and edit
+    Json,
+    // compact, header followed by bincode
+    Binary,
+}
+
+#[derive(Serialize, Deserialize)]
+struct JsonSave {
+    version: u32,
+    checksum: u32,
+    state: serde_json::Value,
+}
+
+// Payload of a save whose version is known but not yet decoded
+enum Payload<'a> {
+    Json(serde_json::Value),
+    Binary(&'a [u8]),
+}
+
+impl Payload<'_> {
+    fn decode<T: DeserializeOwned>(self) -> Result<T, SaveError> {
+        match self {
+            Payload::Json(value) => Ok(serde_json::from_value(value)?),
+            Payload::Binary(bytes) => {
+                let (state, read) =
+                    bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+                // the checksum only says the bytes were written this way, not that they all belong
+                if read < bytes.len() {
+                    return Err(SaveError::Binary(format!(
+                        "{} bytes left over after the state",
+                        bytes.len() - read
+                    )));
+                }
+                Ok(state)
+            }
+        }
+    }
+}
+
+pub fn encode(state: &GameState, format: SaveFormat) -> Result<Vec<u8>, SaveError> {
+    encode_version(CURRENT_VERSION, state, format)
+}
+
+// the format is detected from the first bytes
+pub fn decode(bytes: &[u8]) -> Result<GameState, SaveError> {
+    if bytes.starts_with(MAGIC) {
+        decode_binary(bytes)
+    } else {
+        decode_json(bytes)
+    }
+}
+
+pub fn save_to_file<P: AsRef<Path>>(
+    path: P,
+    state: &GameState,
+    format: SaveFormat,
+) -> Result<(), SaveError> {
+    fs::write(path, encode(state, format)?)?;
+    Ok(())
+}
+
+pub fn load_from_file<P: AsRef<Path>>(path: P) -> Result<GameState, SaveError> {
+    decode(&fs::read(path)?)
+}
+
+pub(crate) fn encode_version<T: Serialize>(
+    version: u32,
+    state: &T,
+    format: SaveFormat,
+) -> Result<Vec<u8>, SaveError> {
+    match format {
+        SaveFormat::Json => {
+            // the checksum covers the state as a serde_json::Value,
+            // so it is computed the same way when loading
+            let state = serde_json::to_value(state)?;
+            let checksum = crc32fast::hash(&serde_json::to_vec(&state)?);
+            let save = JsonSave {
+                version,
+                checksum,
+                state,
+            };
+            Ok(serde_json::to_vec_pretty(&save)?)
+        }
+        SaveFormat::Binary => {
+            let payload = bincode::serde::encode_to_vec(state, bincode::config::standard())?;
+            let mut bytes = Vec::with_capacity(HEADER_LEN + payload.len());
+            bytes.extend_from_slice(MAGIC);
+            bytes.extend_from_slice(&version.to_le_bytes());
+            bytes.extend_from_slice(&crc32fast::hash(&payload).to_le_bytes());
+            bytes.extend_from_slice(&payload);
+            Ok(bytes)
+        }
+    }
+}
+
+fn decode_json(bytes: &[u8]) -> Result<GameState, SaveError> {
+    let save: JsonSave = serde_json::from_slice(bytes)?;
+    verify(save.checksum, &serde_json::to_vec(&save.state)?)?;
+    migrate(save.version, Payload::Json(save.state))
+}
+
+fn decode_binary(bytes: &[u8]) -> Result<GameState, SaveError> {
+    if bytes.len() < HEADER_LEN {
+        return Err(SaveError::Binary(
+            "save is shorter than its header".to_string(),
+        ));
+    }
+
+    let version = u32::from_le_bytes(bytes[4..8].try_into().unwrap());
+    let checksum = u32::from_le_bytes(bytes[8..12].try_into().unwrap());
+    let payload = &bytes[HEADER_LEN..];
+    verify(checksum, payload)?;
+    migrate(version, Payload::Binary(payload))
+}
+
+fn verify(expected: u32, data: &[u8]) -> Result<(), SaveError> {
+    let actual = crc32fast::hash(data);
+    if actual == expected {
+        Ok(())
+    } else {
+        Err(SaveError::Corrupted { expected, actual })
+    }
+}
+
+// Every old version is decoded with its own struct and upgraded step by step,
+// a new version only needs one more arm and a From impl for the previous one
+fn migrate(version: u32, payload: Payload) -> Result<GameState, SaveError> {
+    match version {
+        1 => Ok(GameState::from(payload.decode::<GameStateV1>()?)),
+        CURRENT_VERSION => payload.decode::<GameState>(),
+        _ => Err(SaveError::UnsupportedVersion(version)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::basic_oop::characters::{Mage, Warrior};
+    use crate::basic_oop::quests::QuestLog;
+    use crate::crabby::{Crabby, CrabbyState};
+    use crate::economy::currency::Gold;
+    use crate::inventory::Inventory;
+    use crate::save::game_state::CharacterRecord;
+    use crate::save::legacy::{self, GameStateV1};
+
+    fn crabby() -> Crabby {
+        Crabby {
+            name: "Crabby".to_string(),
+            health: 80,
+            state: CrabbyState::Collecting(15),
+        }
+    }
+
+    fn state() -> GameState {
+        let mut inventory = Inventory::new(Gold::new(100));
+        inventory.add("Potion", 3);
+        let mut quests = QuestLog::new();
+        quests.start("Find the treasure", 2);
+        quests.advance("Find the treasure");
+
+        GameState {
+            party: vec![
+                CharacterRecord::from_character(&Warrior::new()),
+                CharacterRecord::from_character(&Mage::new()),
+            ],
+            inventory,
+            quests,
+            crabby: crabby(),
+        }
+    }
+
+    #[test]
+    fn a_game_state_survives_both_formats() {
+        let state = state();
+        for format in [SaveFormat::Json, SaveFormat::Binary] {
+            let bytes = encode(&state, format).unwrap();
+            assert_eq!(decode(&bytes).unwrap(), state);
+        }
+    }
+
+    #[test]
+    fn a_flipped_payload_byte_is_reported_as_corrupted() {
+        let mut bytes = encode(&state(), SaveFormat::Binary).unwrap();
+        let last = bytes.len() - 1;
+        bytes[last] ^= 0xff;
+        assert!(matches!(decode(&bytes), Err(SaveError::Corrupted { .. })));
+
+        let json = encode(&state(), SaveFormat::Json).unwrap();
+        let json = String::from_utf8(json).unwrap().replace("Potion", "Potiom");
+        assert!(matches!(
+            decode(json.as_bytes()),
+            Err(SaveError::Corrupted { .. })
+        ));
+    }
+
+    #[test]
+    fn a_save_shorter_than_its_header_is_refused() {
+        let bytes = encode(&state(), SaveFormat::Binary).unwrap();
+        assert!(matches!(
+            decode(&bytes[..HEADER_LEN - 1]),
+            Err(SaveError::Binary(_))
+        ));
+    }
+
+    #[test]
+    fn bytes_after_the_state_are_refused_even_with_a_matching_checksum() {
+        let mut bytes = encode(&state(), SaveFormat::Binary).unwrap();
+        bytes.extend_from_slice(b"junk");
+        let checksum = crc32fast::hash(&bytes[HEADER_LEN..]);
+        bytes[8..12].copy_from_slice(&checksum.to_le_bytes());
+
+        assert!(matches!(decode(&bytes), Err(SaveError::Binary(_))));
+    }
+
+    #[test]
+    fn a_version_1_save_is_migrated_to_the_current_version() {
+        let old = GameStateV1 {
+            party: state().party,
+            gold: 50,
+            items: vec!["Potion".to_string(), "Potion".to_string()],
+            completed_quests: vec!["Save the princess".to_string()],
+            crabby: crabby(),
+        };
+
+        for format in [SaveFormat::Json, SaveFormat::Binary] {
+            let loaded = decode(&legacy::encode_v1(&old, format).unwrap()).unwrap();
+            assert_eq!(loaded.party, old.party);
+            assert_eq!(loaded.inventory.gold, Gold::new(50));
+            assert_eq!(loaded.inventory.count("Potion"), 2);
+            let completed: Vec<&str> = loaded
+                .quests
+                .completed()
+                .map(|quest| quest.name.as_str())
+                .collect();
+            assert_eq!(completed, vec!["Save the princess"]);
+            assert_eq!(loaded.crabby, old.crabby);
+
+            // saved again it is written as the current version
+            let bytes = encode(&loaded, SaveFormat::Binary).unwrap();
+            assert_eq!(bytes[4..8], CURRENT_VERSION.to_le_bytes());
+        }
+    }
+
+    #[test]
+    fn an_unknown_version_is_refused() {
+        let bytes = encode_version(CURRENT_VERSION + 1, &state(), SaveFormat::Json).unwrap();
+        assert!(matches!(
+            decode(&bytes),
+            Err(SaveError::UnsupportedVersion(version)) if version == CURRENT_VERSION + 1
+        ));
+    }
+}
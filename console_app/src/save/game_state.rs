@@ -0,0 +1,57 @@
+use serde::{Deserialize, Serialize};
+
+use super::error::SaveError;
+use crate::basic_oop::characters::{Character, CharacterClass, Healer, Mage, Warrior};
+use crate::basic_oop::quests::QuestLog;
+use crate::basic_oop::weapons::weapon_by_name;
+use crate::crabby::Crabby;
+use crate::inventory::Inventory;
+
+// Plain data copy of a character, the weapon is stored by name
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CharacterRecord {
+    pub class: CharacterClass,
+    pub health: u8,
+    pub strength: u8,
+    pub intelligent: u8,
+    pub weapon: String,
+}
+
+impl CharacterRecord {
+    pub fn from_character<T: Character + ?Sized>(character: &T) -> Self {
+        Self {
+            class: character.class(),
+            health: character.health(),
+            strength: character.strength(),
+            intelligent: character.intelligent(),
+            weapon: character.weapon().name().to_string(),
+        }
+    }
+
+    pub fn restore(&self) -> Result<Box<dyn Character>, SaveError> {
+        let weapon = weapon_by_name(&self.weapon)
+            .ok_or_else(|| SaveError::UnknownWeapon(self.weapon.clone()))?;
+        let (health, strength, intelligent) = (self.health, self.strength, self.intelligent);
+
+        Ok(match self.class {
+            CharacterClass::Warrior => {
+                Box::new(Warrior::with_stats(health, strength, intelligent, weapon))
+            }
+            CharacterClass::Mage => {
+                Box::new(Mage::with_stats(health, strength, intelligent, weapon))
+            }
+            CharacterClass::Healer => {
+                Box::new(Healer::with_stats(health, strength, intelligent, weapon))
+            }
+        })
+    }
+}
+
+// Everything written to a save file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameState {
+    pub party: Vec<CharacterRecord>,
+    pub inventory: Inventory,
+    pub quests: QuestLog,
+    pub crabby: Crabby,
+}
@@ -0,0 +1,53 @@
+use serde::{Deserialize, Serialize};
+
+use super::error::SaveError;
+use super::format::{encode_version, SaveFormat};
+use super::game_state::{CharacterRecord, GameState};
+use crate::basic_oop::quests::{QuestLog, QuestProgress};
+use crate::crabby::Crabby;
+use crate::economy::currency::Gold;
+use crate::inventory::Inventory;
+
+// Version 1 saves kept gold next to the party, listed every item once per copy
+// and only remembered which quests were finished
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct GameStateV1 {
+    pub party: Vec<CharacterRecord>,
+    pub gold: u32,
+    pub items: Vec<String>,
+    pub completed_quests: Vec<String>,
+    pub crabby: Crabby,
+}
+
+impl From<GameStateV1> for GameState {
+    fn from(old: GameStateV1) -> Self {
+        let mut inventory = Inventory::new(Gold::new(old.gold));
+        for item in old.items.iter() {
+            inventory.add(item, 1);
+        }
+
+        let quests = QuestLog {
+            quests: old
+                .completed_quests
+                .into_iter()
+                .map(|name| QuestProgress {
+                    name,
+                    stage: 1,
+                    stages: 1,
+                })
+                .collect(),
+        };
+
+        GameState {
+            party: old.party,
+            inventory,
+            quests,
+            crabby: old.crabby,
+        }
+    }
+}
+
+// writes a version 1 save, only needed to check that old saves still load
+pub fn encode_v1(state: &GameStateV1, format: SaveFormat) -> Result<Vec<u8>, SaveError> {
+    encode_version(1, state, format)
+}
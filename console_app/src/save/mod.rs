@@ -0,0 +1,4 @@
+pub mod error;
+pub mod format;
+pub mod game_state;
+pub mod legacy;
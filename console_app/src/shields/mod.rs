@@ -1,2 +1,3 @@
 pub mod use_item;
 pub mod block_attacking;
+pub mod shield;
@@ -0,0 +1,14 @@
+use my_macro_lib::items;
+
+use crate::registry::Tagged;
+
+pub trait Shield: ShieldClone + Tagged {
+  fn block(&self);
+  fn defense(&self) -> u32;
+}
+
+crate::trait_object!(Shield, ShieldClone, register_shields);
+
+items! {
+  Shield WoodShield { defense: 5, block: "Defend by wood shield" }
+}
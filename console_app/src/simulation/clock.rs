@@ -0,0 +1,57 @@
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+// Where the game loop reads the time from
+pub trait Clock: Send {
+    // time elapsed since the clock started
+    fn now(&self) -> Duration;
+}
+
+// Real time, for the game itself
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock {
+    started: Instant,
+}
+
+impl SystemClock {
+    pub fn new() -> Self {
+        Self {
+            started: Instant::now(),
+        }
+    }
+}
+
+impl Default for SystemClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for SystemClock {
+    fn now(&self) -> Duration {
+        self.started.elapsed()
+    }
+}
+
+// Time that only moves when told to, so time dependent behaviour can be replayed exactly.
+// Clones share the same time: hand one to the game loop and keep one to advance it.
+#[derive(Debug, Clone, Default)]
+pub struct VirtualClock {
+    now: Arc<Mutex<Duration>>,
+}
+
+impl VirtualClock {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn advance(&self, by: Duration) {
+        *self.now.lock().unwrap() += by;
+    }
+}
+
+impl Clock for VirtualClock {
+    fn now(&self) -> Duration {
+        *self.now.lock().unwrap()
+    }
+}
@@ -0,0 +1,173 @@
+use std::time::Duration;
+
+use super::clock::Clock;
+use super::scheduler::{Scheduler, Tick};
+
+// Runs the simulation in fixed steps, however unevenly the real time passes.
+// Time left over from one update is carried over to the next, so a slow frame
+// runs several ticks and a fast one may run none.
+pub struct GameLoop<C> {
+    clock: Box<dyn Clock>,
+    timestep: Duration,
+    // a very slow frame is not caught up all at once, the rest of its time is dropped
+    max_ticks_per_update: u32,
+    last_update: Duration,
+    accumulated: Duration,
+    paused: bool,
+    pub scheduler: Scheduler<C>,
+}
+
+impl<C> GameLoop<C> {
+    pub fn new(clock: Box<dyn Clock>, timestep: Duration) -> Self {
+        assert!(!timestep.is_zero(), "the timestep must not be zero");
+
+        let last_update = clock.now();
+        Self {
+            clock,
+            timestep,
+            max_ticks_per_update: 10,
+            last_update,
+            accumulated: Duration::ZERO,
+            paused: false,
+            scheduler: Scheduler::new(),
+        }
+    }
+
+    pub fn with_max_ticks_per_update(mut self, max_ticks_per_update: u32) -> Self {
+        self.max_ticks_per_update = max_ticks_per_update.max(1);
+        self
+    }
+
+    pub fn timestep(&self) -> Duration {
+        self.timestep
+    }
+
+    pub fn current_tick(&self) -> Tick {
+        self.scheduler.now()
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    // the simulation stands still, the time spent paused is never caught up
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        if self.paused {
+            self.paused = false;
+            self.last_update = self.clock.now();
+        }
+    }
+
+    // runs as many ticks as the time since the last update allows, returns how many ran
+    pub fn update(&mut self, context: &mut C) -> u32 {
+        let now = self.clock.now();
+        let elapsed = now.saturating_sub(self.last_update);
+        self.last_update = now;
+        if self.paused {
+            return 0;
+        }
+
+        self.accumulated += elapsed;
+        let mut ticks = 0;
+        while self.accumulated >= self.timestep {
+            if ticks == self.max_ticks_per_update {
+                self.accumulated = Duration::ZERO;
+                break;
+            }
+            self.accumulated -= self.timestep;
+            self.scheduler.tick(context);
+            ticks += 1;
+        }
+        ticks
+    }
+
+    // a single tick right away, e.g. to step through a paused game
+    pub fn step(&mut self, context: &mut C) -> Vec<String> {
+        self.scheduler.tick(context)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::clock::VirtualClock;
+    use super::super::scheduler::Repeat;
+    use super::*;
+
+    // a loop ticking every 100ms that counts its ticks in the context
+    fn counting_loop(clock: &VirtualClock) -> GameLoop<u32> {
+        let mut game = GameLoop::new(Box::new(clock.clone()), Duration::from_millis(100));
+        game.scheduler
+            .every("count", 1, Repeat::Forever, |count: &mut u32| *count += 1);
+        game
+    }
+
+    #[test]
+    fn leftover_time_counts_towards_the_next_update() {
+        let clock = VirtualClock::new();
+        let mut game = counting_loop(&clock);
+        let mut count = 0;
+
+        clock.advance(Duration::from_millis(250));
+        assert_eq!(game.update(&mut count), 2);
+        clock.advance(Duration::from_millis(50));
+        assert_eq!(game.update(&mut count), 1);
+        assert_eq!(game.update(&mut count), 0);
+
+        assert_eq!(count, 3);
+        assert_eq!(game.current_tick(), 3);
+    }
+
+    #[test]
+    fn a_slow_frame_is_caught_up_only_so_far() {
+        let clock = VirtualClock::new();
+        let mut game = counting_loop(&clock).with_max_ticks_per_update(4);
+        let mut count = 0;
+
+        clock.advance(Duration::from_secs(1));
+        assert_eq!(game.update(&mut count), 4);
+        // the rest of that second is dropped, not carried over
+        assert_eq!(game.update(&mut count), 0);
+        assert_eq!(count, 4);
+    }
+
+    #[test]
+    fn the_time_spent_paused_is_never_caught_up() {
+        let clock = VirtualClock::new();
+        let mut game = counting_loop(&clock);
+        let mut count = 0;
+
+        game.pause();
+        clock.advance(Duration::from_secs(10));
+        assert_eq!(game.update(&mut count), 0);
+        game.resume();
+        assert_eq!(game.update(&mut count), 0);
+
+        // stepping still works while paused
+        game.pause();
+        assert_eq!(game.step(&mut count), vec!["count"]);
+        assert_eq!(count, 1);
+    }
+
+    #[test]
+    fn tasks_run_on_the_tick_the_clock_reaches() {
+        let clock = VirtualClock::new();
+        let mut game: GameLoop<Vec<Tick>> =
+            GameLoop::new(Box::new(clock.clone()), Duration::from_millis(100));
+        game.scheduler.after("ambush", 3, |ticks: &mut Vec<Tick>| {
+            ticks.push(3);
+        });
+        let mut ticks = Vec::new();
+
+        clock.advance(Duration::from_millis(200));
+        game.update(&mut ticks);
+        assert!(ticks.is_empty());
+        clock.advance(Duration::from_millis(100));
+        game.update(&mut ticks);
+        assert_eq!(ticks, vec![3]);
+        assert!(game.scheduler.pending().is_empty());
+    }
+}
@@ -0,0 +1,3 @@
+pub mod clock;
+pub mod game_loop;
+pub mod scheduler;
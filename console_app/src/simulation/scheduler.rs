@@ -0,0 +1,223 @@
+use std::cmp::Reverse;
+use std::collections::{BinaryHeap, HashMap};
+
+pub type Tick = u64;
+pub type TaskId = u64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Repeat {
+    Once,
+    // runs this many times in total
+    Times(u32),
+    Forever,
+}
+
+type Action<C> = Box<dyn FnMut(&mut C) + Send>;
+
+struct Task<C> {
+    name: String,
+    // ticks between two runs
+    every: Tick,
+    runs_left: Option<u32>,
+    action: Action<C>,
+}
+
+// Actions that happen some ticks from now, once or again and again.
+// `C` is whatever the actions work on, e.g. the combatants and the market.
+pub struct Scheduler<C> {
+    now: Tick,
+    next_id: TaskId,
+    // soonest first, tasks due on the same tick run in the order they were scheduled
+    queue: BinaryHeap<Reverse<(Tick, TaskId)>>,
+    tasks: HashMap<TaskId, Task<C>>,
+}
+
+impl<C> Scheduler<C> {
+    pub fn new() -> Self {
+        Self {
+            now: 0,
+            next_id: 0,
+            queue: BinaryHeap::new(),
+            tasks: HashMap::new(),
+        }
+    }
+
+    pub fn now(&self) -> Tick {
+        self.now
+    }
+
+    // runs `action` once, `delay` ticks from now
+    pub fn after<F>(&mut self, name: &str, delay: Tick, action: F) -> TaskId
+    where
+        F: FnMut(&mut C) + Send + 'static,
+    {
+        self.schedule(name, delay, delay, Repeat::Once, action)
+    }
+
+    // runs `action` every `every` ticks, the first time `every` ticks from now
+    pub fn every<F>(&mut self, name: &str, every: Tick, repeat: Repeat, action: F) -> TaskId
+    where
+        F: FnMut(&mut C) + Send + 'static,
+    {
+        self.schedule(name, every, every, repeat, action)
+    }
+
+    pub fn schedule<F>(
+        &mut self,
+        name: &str,
+        delay: Tick,
+        every: Tick,
+        repeat: Repeat,
+        action: F,
+    ) -> TaskId
+    where
+        F: FnMut(&mut C) + Send + 'static,
+    {
+        let runs_left = match repeat {
+            Repeat::Once => Some(1),
+            Repeat::Times(times) => Some(times),
+            Repeat::Forever => None,
+        };
+
+        let id = self.next_id;
+        self.next_id += 1;
+        // an action can not run on the tick it was scheduled on, that one is already over
+        self.queue.push(Reverse((self.now + delay.max(1), id)));
+        self.tasks.insert(
+            id,
+            Task {
+                name: name.to_string(),
+                every: every.max(1),
+                runs_left,
+                action: Box::new(action),
+            },
+        );
+        id
+    }
+
+    pub fn cancel(&mut self, id: TaskId) -> bool {
+        // the queue entry stays behind and is skipped once it comes up
+        self.tasks.remove(&id).is_some()
+    }
+
+    pub fn is_scheduled(&self, id: TaskId) -> bool {
+        self.tasks.contains_key(&id)
+    }
+
+    // names of the tasks still waiting to run, soonest first
+    pub fn pending(&self) -> Vec<(Tick, &str)> {
+        let mut pending: Vec<(Tick, TaskId)> = self
+            .queue
+            .iter()
+            .map(|Reverse(entry)| *entry)
+            .filter(|(_, id)| self.tasks.contains_key(id))
+            .collect();
+        pending.sort();
+        pending
+            .into_iter()
+            .map(|(due, id)| (due, self.tasks[&id].name.as_str()))
+            .collect()
+    }
+
+    // moves one tick forward and runs everything due, returns the names of what ran
+    pub fn tick(&mut self, context: &mut C) -> Vec<String> {
+        self.now += 1;
+        let mut ran = Vec::new();
+
+        while let Some(Reverse((due, id))) = self.queue.peek().copied() {
+            if due > self.now {
+                break;
+            }
+            self.queue.pop();
+
+            let task = match self.tasks.get_mut(&id) {
+                Some(task) => task,
+                None => continue,
+            };
+            if task.runs_left == Some(0) {
+                self.tasks.remove(&id);
+                continue;
+            }
+            (task.action)(context);
+            ran.push(task.name.clone());
+
+            if let Some(runs_left) = task.runs_left.as_mut() {
+                *runs_left -= 1;
+                if *runs_left == 0 {
+                    self.tasks.remove(&id);
+                    continue;
+                }
+            }
+            self.queue.push(Reverse((due + task.every, id)));
+        }
+
+        ran
+    }
+}
+
+impl<C> Default for Scheduler<C> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn log(name: &'static str) -> impl FnMut(&mut Vec<&'static str>) + Send {
+        move |ran: &mut Vec<&'static str>| ran.push(name)
+    }
+
+    #[test]
+    fn tasks_due_on_the_same_tick_run_in_the_order_they_were_scheduled() {
+        let mut scheduler = Scheduler::new();
+        scheduler.after("second", 2, log("second"));
+        scheduler.after("first", 1, log("first"));
+        scheduler.after("third", 2, log("third"));
+        let mut ran = Vec::new();
+
+        scheduler.tick(&mut ran);
+        scheduler.tick(&mut ran);
+
+        assert_eq!(ran, vec!["first", "second", "third"]);
+    }
+
+    #[test]
+    fn a_repeated_task_stops_after_its_runs() {
+        let mut scheduler = Scheduler::new();
+        let id = scheduler.every("potion", 2, Repeat::Times(3), log("potion"));
+        let mut ran = Vec::new();
+
+        for _ in 0..10 {
+            scheduler.tick(&mut ran);
+        }
+
+        assert_eq!(ran, vec!["potion"; 3]);
+        assert!(!scheduler.is_scheduled(id));
+    }
+
+    #[test]
+    fn a_cancelled_task_never_runs() {
+        let mut scheduler = Scheduler::new();
+        let id = scheduler.every("market", 1, Repeat::Forever, log("market"));
+        scheduler.after("ambush", 1, log("ambush"));
+        let mut ran = Vec::new();
+
+        assert!(scheduler.cancel(id));
+        assert!(!scheduler.cancel(id));
+        scheduler.tick(&mut ran);
+
+        assert_eq!(ran, vec!["ambush"]);
+        assert!(scheduler.pending().is_empty());
+    }
+
+    #[test]
+    fn a_task_can_not_run_on_the_tick_it_was_scheduled_on() {
+        let mut scheduler = Scheduler::new();
+        scheduler.after("now", 0, log("now"));
+
+        assert_eq!(scheduler.pending(), vec![(1, "now")]);
+        assert_eq!(scheduler.tick(&mut Vec::new()), vec!["now"]);
+    }
+}
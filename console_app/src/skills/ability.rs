@@ -0,0 +1,102 @@
+use crate::basic_oop::characters::CharacterClass;
+use crate::combat::status::Status;
+use crate::pets::species::Species;
+
+// Who an ability can be cast on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Targeting {
+    // only the caster
+    SelfOnly,
+    // exactly one combatant
+    Single,
+    // every combatant inside the radius
+    Area { radius: u32 },
+}
+
+// What happens to each target when the ability lands
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    Damage(u32),
+    Heal(u32),
+    ApplyStatus { status: Status, turns: u32 },
+}
+
+// Who learns abilities: a character of a class, or a pet of a species
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Learner {
+    Class(CharacterClass),
+    Pet(Species),
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cost {
+    pub mana: u32,
+    pub stamina: u32,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Ability {
+    pub name: String,
+    pub cost: Cost,
+    // number of turns before the ability can be cast again
+    pub cooldown: u32,
+    pub targeting: Targeting,
+    // empty means every class can learn it
+    pub classes: Vec<CharacterClass>,
+    // only pets learn it, the ones whose species lists it
+    pub pet: bool,
+    pub effects: Vec<Effect>,
+}
+
+impl Ability {
+    pub fn new(name: &str, targeting: Targeting) -> Self {
+        Self {
+            name: name.to_string(),
+            cost: Cost::default(),
+            cooldown: 0,
+            targeting,
+            classes: Vec::new(),
+            pet: false,
+            effects: Vec::new(),
+        }
+    }
+
+    pub fn mana(mut self, mana: u32) -> Self {
+        self.cost.mana = mana;
+        self
+    }
+
+    pub fn stamina(mut self, stamina: u32) -> Self {
+        self.cost.stamina = stamina;
+        self
+    }
+
+    pub fn cooldown(mut self, turns: u32) -> Self {
+        self.cooldown = turns;
+        self
+    }
+
+    pub fn class(mut self, class: CharacterClass) -> Self {
+        self.classes.push(class);
+        self
+    }
+
+    pub fn pet(mut self) -> Self {
+        self.pet = true;
+        self
+    }
+
+    pub fn effect(mut self, effect: Effect) -> Self {
+        self.effects.push(effect);
+        self
+    }
+
+    pub fn usable_by(&self, learner: &Learner) -> bool {
+        match learner {
+            Learner::Class(class) => {
+                !self.pet && (self.classes.is_empty() || self.classes.contains(class))
+            }
+            Learner::Pet(species) => self.pet && species.abilities.contains(&self.name),
+        }
+    }
+}
@@ -0,0 +1,130 @@
+use super::ability::{Ability, Effect, Learner, Targeting};
+use crate::basic_oop::characters::CharacterClass;
+use crate::combat::status::Status;
+
+// An ability becomes learnable when the character reaches `level`
+#[derive(Debug, Clone)]
+pub struct Unlock {
+    pub level: u32,
+    pub ability: Ability,
+}
+
+impl Unlock {
+    pub fn new(level: u32, ability: Ability) -> Self {
+        Self { level, ability }
+    }
+}
+
+pub fn stun() -> Ability {
+    Ability::new("Stun", Targeting::Single)
+        .stamina(15)
+        .cooldown(3)
+        .class(CharacterClass::Warrior)
+        .effect(Effect::Damage(5))
+        .effect(Effect::ApplyStatus {
+            status: Status::Stunned,
+            turns: 1,
+        })
+}
+
+pub fn heal() -> Ability {
+    Ability::new("Heal", Targeting::Single)
+        .mana(10)
+        .cooldown(1)
+        .class(CharacterClass::Healer)
+        .class(CharacterClass::Mage)
+        .effect(Effect::Heal(25))
+}
+
+pub fn fireball() -> Ability {
+    Ability::new("Fireball", Targeting::Area { radius: 1 })
+        .mana(20)
+        .cooldown(2)
+        .class(CharacterClass::Mage)
+        .effect(Effect::Damage(20))
+}
+
+pub fn poison_blade() -> Ability {
+    Ability::new("Poison Blade", Targeting::Single)
+        .stamina(10)
+        .class(CharacterClass::Warrior)
+        .effect(Effect::Damage(8))
+        .effect(Effect::ApplyStatus {
+            status: Status::Poisoned { damage: 4 },
+            turns: 3,
+        })
+}
+
+pub fn renew() -> Ability {
+    Ability::new("Renew", Targeting::SelfOnly)
+        .mana(15)
+        .cooldown(4)
+        .class(CharacterClass::Healer)
+        .effect(Effect::ApplyStatus {
+            status: Status::Regenerating { heal: 6 },
+            turns: 3,
+        })
+}
+
+pub fn second_wind() -> Ability {
+    Ability::new("Second Wind", Targeting::SelfOnly)
+        .stamina(20)
+        .cooldown(5)
+        .effect(Effect::Heal(15))
+}
+
+pub fn bite() -> Ability {
+    Ability::new("Bite", Targeting::Single)
+        .stamina(10)
+        .pet()
+        .effect(Effect::Damage(10))
+}
+
+pub fn scratch() -> Ability {
+    Ability::new("Scratch", Targeting::Single)
+        .stamina(5)
+        .pet()
+        .effect(Effect::Damage(4))
+        .effect(Effect::ApplyStatus {
+            status: Status::Poisoned { damage: 2 },
+            turns: 2,
+        })
+}
+
+pub fn dive() -> Ability {
+    Ability::new("Dive", Targeting::Single)
+        .stamina(15)
+        .cooldown(2)
+        .pet()
+        .effect(Effect::Damage(14))
+}
+
+pub fn default_catalog() -> Vec<Unlock> {
+    vec![
+        Unlock::new(1, stun()),
+        Unlock::new(1, heal()),
+        Unlock::new(1, second_wind()),
+        Unlock::new(1, bite()),
+        Unlock::new(1, scratch()),
+        Unlock::new(1, dive()),
+        Unlock::new(2, fireball()),
+        Unlock::new(2, poison_blade()),
+        Unlock::new(3, renew()),
+    ]
+}
+
+// abilities a learner unlocks exactly at `level`
+pub fn unlocked_at(catalog: &[Unlock], learner: &Learner, level: u32) -> Vec<Ability> {
+    catalog
+        .iter()
+        .filter(|unlock| unlock.level == level && unlock.ability.usable_by(learner))
+        .map(|unlock| unlock.ability.clone())
+        .collect()
+}
+
+pub fn find(catalog: &[Unlock], name: &str) -> Option<Ability> {
+    catalog
+        .iter()
+        .find(|unlock| unlock.ability.name == name)
+        .map(|unlock| unlock.ability.clone())
+}
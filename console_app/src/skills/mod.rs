@@ -0,0 +1,3 @@
+pub mod ability;
+pub mod catalog;
+pub mod skill_book;
@@ -0,0 +1,113 @@
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+use super::ability::{Ability, Learner};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkillError {
+    AlreadyKnown(String),
+    WrongClass(String),
+    Unknown(String),
+    OnCooldown { name: String, turns_left: u32 },
+}
+
+impl fmt::Display for SkillError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            SkillError::AlreadyKnown(name) => write!(f, "{} is already known", name),
+            SkillError::WrongClass(name) => write!(f, "{} cannot be learned by this class", name),
+            SkillError::Unknown(name) => write!(f, "{} has not been learned", name),
+            SkillError::OnCooldown { name, turns_left } => {
+                write!(f, "{} is on cooldown for {} turns", name, turns_left)
+            }
+        }
+    }
+}
+
+// Abilities learned by one character together with their cooldowns
+#[derive(Debug, Clone)]
+pub struct SkillBook {
+    learner: Learner,
+    abilities: Vec<Ability>,
+    cooldowns: HashMap<String, u32>,
+    // cast this turn, their cooldown starts counting at the end of the next one
+    just_cast: HashSet<String>,
+}
+
+impl SkillBook {
+    pub fn new(learner: Learner) -> Self {
+        Self {
+            learner,
+            abilities: Vec::new(),
+            cooldowns: HashMap::new(),
+            just_cast: HashSet::new(),
+        }
+    }
+
+    pub fn learner(&self) -> &Learner {
+        &self.learner
+    }
+
+    pub fn learn(&mut self, ability: Ability) -> Result<(), SkillError> {
+        if !ability.usable_by(&self.learner) {
+            return Err(SkillError::WrongClass(ability.name));
+        }
+        if self.knows(&ability.name) {
+            return Err(SkillError::AlreadyKnown(ability.name));
+        }
+
+        self.abilities.push(ability);
+        Ok(())
+    }
+
+    pub fn knows(&self, name: &str) -> bool {
+        self.abilities.iter().any(|ability| ability.name == name)
+    }
+
+    pub fn get(&self, name: &str) -> Option<&Ability> {
+        self.abilities.iter().find(|ability| ability.name == name)
+    }
+
+    pub fn abilities(&self) -> &[Ability] {
+        &self.abilities
+    }
+
+    pub fn cooldown_left(&self, name: &str) -> u32 {
+        self.cooldowns.get(name).copied().unwrap_or(0)
+    }
+
+    // ready to cast: learned and not cooling down
+    pub fn ready(&self, name: &str) -> Result<&Ability, SkillError> {
+        let ability = self
+            .get(name)
+            .ok_or_else(|| SkillError::Unknown(name.to_string()))?;
+
+        match self.cooldown_left(name) {
+            0 => Ok(ability),
+            turns_left => Err(SkillError::OnCooldown {
+                name: name.to_string(),
+                turns_left,
+            }),
+        }
+    }
+
+    pub fn start_cooldown(&mut self, name: &str) {
+        if let Some(ability) = self.get(name) {
+            if ability.cooldown > 0 {
+                self.cooldowns.insert(name.to_string(), ability.cooldown);
+                self.just_cast.insert(name.to_string());
+            }
+        }
+    }
+
+    // called once at the end of every turn
+    pub fn tick(&mut self) {
+        for (name, turns_left) in self.cooldowns.iter_mut() {
+            if !self.just_cast.contains(name) {
+                *turns_left = turns_left.saturating_sub(1);
+            }
+        }
+        self.cooldowns.retain(|_, turns_left| *turns_left > 0);
+        self.just_cast.clear();
+    }
+}
@@ -0,0 +1,39 @@
+use std::fmt;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PoolError {
+    // the pool no longer takes jobs
+    ShutDown,
+    // `try_submit` found every slot of the queue taken
+    QueueFull,
+}
+
+impl fmt::Display for PoolError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PoolError::ShutDown => write!(f, "the worker pool is shut down"),
+            PoolError::QueueFull => write!(f, "the job queue is full"),
+        }
+    }
+}
+
+impl std::error::Error for PoolError {}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum JobError {
+    // the job panicked, the worker that ran it keeps going
+    Panicked(String),
+    // the job was dropped before it could run
+    Lost,
+}
+
+impl fmt::Display for JobError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            JobError::Panicked(message) => write!(f, "job panicked: {}", message),
+            JobError::Lost => write!(f, "job was dropped before it finished"),
+        }
+    }
+}
+
+impl std::error::Error for JobError {}
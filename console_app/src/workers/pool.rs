@@ -0,0 +1,322 @@
+use std::any::Any;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, SyncSender, TrySendError};
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+use super::error::{JobError, PoolError};
+
+type Job = Box<dyn FnOnce() + Send + 'static>;
+
+// The answer of a single job, wait for it with `join`
+#[derive(Debug)]
+pub struct JobHandle<T> {
+    receiver: Receiver<Result<T, JobError>>,
+}
+
+impl<T> JobHandle<T> {
+    pub fn join(self) -> Result<T, JobError> {
+        self.receiver.recv().unwrap_or(Err(JobError::Lost))
+    }
+}
+
+// The answers of a group of jobs, tagged with the position they were submitted at
+#[derive(Debug)]
+pub struct Batch<T> {
+    receiver: Receiver<(usize, Result<T, JobError>)>,
+    len: usize,
+}
+
+impl<T> Batch<T> {
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    // as soon as each job is done, whatever order that is
+    pub fn in_completion_order(self) -> impl Iterator<Item = (usize, Result<T, JobError>)> {
+        self.receiver.into_iter().take(self.len)
+    }
+
+    // waits for the whole batch, answers line up with the submitted items
+    pub fn in_submission_order(self) -> Vec<Result<T, JobError>> {
+        let mut results: Vec<Result<T, JobError>> =
+            (0..self.len).map(|_| Err(JobError::Lost)).collect();
+        for (index, result) in self.in_completion_order() {
+            results[index] = result;
+        }
+        results
+    }
+}
+
+// A fixed number of threads sharing one bounded job queue.
+// Submitting blocks while the queue is full, so a fast producer can never
+// pile up more than `capacity` jobs ahead of the workers.
+#[derive(Debug)]
+pub struct WorkerPool {
+    sender: Option<SyncSender<Job>>,
+    workers: Vec<JoinHandle<()>>,
+}
+
+impl WorkerPool {
+    pub fn new(size: usize, capacity: usize) -> Self {
+        assert!(size > 0, "a worker pool needs at least one thread");
+
+        let (sender, receiver) = mpsc::sync_channel::<Job>(capacity);
+        let receiver = Arc::new(Mutex::new(receiver));
+        let workers = (0..size)
+            .map(|index| {
+                let receiver = Arc::clone(&receiver);
+                thread::Builder::new()
+                    .name(format!("worker-{}", index))
+                    .spawn(move || Self::work(receiver))
+                    .unwrap()
+            })
+            .collect();
+
+        Self {
+            sender: Some(sender),
+            workers,
+        }
+    }
+
+    pub fn size(&self) -> usize {
+        self.workers.len()
+    }
+
+    // waits for a free slot in the queue
+    pub fn submit<T, F>(&self, job: F) -> Result<JobHandle<T>, PoolError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (job, receiver) = Self::wrap(job);
+        self.sender()?.send(job).map_err(|_| PoolError::ShutDown)?;
+        Ok(JobHandle { receiver })
+    }
+
+    // gives up right away when the queue is full
+    pub fn try_submit<T, F>(&self, job: F) -> Result<JobHandle<T>, PoolError>
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (job, receiver) = Self::wrap(job);
+        match self.sender()?.try_send(job) {
+            Ok(()) => Ok(JobHandle { receiver }),
+            Err(TrySendError::Full(_)) => Err(PoolError::QueueFull),
+            Err(TrySendError::Disconnected(_)) => Err(PoolError::ShutDown),
+        }
+    }
+
+    // runs `job` once per item
+    pub fn batch<I, T, F>(
+        &self,
+        items: impl IntoIterator<Item = I>,
+        job: F,
+    ) -> Result<Batch<T>, PoolError>
+    where
+        I: Send + 'static,
+        T: Send + 'static,
+        F: Fn(I) -> T + Send + Sync + 'static,
+    {
+        let sender = self.sender()?;
+        let job = Arc::new(job);
+        let (results, receiver) = mpsc::channel();
+
+        let mut len = 0;
+        for (index, item) in items.into_iter().enumerate() {
+            let job = Arc::clone(&job);
+            let results = results.clone();
+            sender
+                .send(Box::new(move || {
+                    let result = Self::catch(move || job(item));
+                    let _ = results.send((index, result));
+                }))
+                .map_err(|_| PoolError::ShutDown)?;
+            len += 1;
+        }
+
+        Ok(Batch { receiver, len })
+    }
+
+    // lets the queued jobs finish, then stops every worker
+    pub fn shutdown(mut self) {
+        self.stop();
+    }
+
+    fn sender(&self) -> Result<&SyncSender<Job>, PoolError> {
+        self.sender.as_ref().ok_or(PoolError::ShutDown)
+    }
+
+    fn stop(&mut self) {
+        // closing the queue makes every idle worker leave its loop
+        self.sender.take();
+        for worker in self.workers.drain(..) {
+            let _ = worker.join();
+        }
+    }
+
+    fn work(receiver: Arc<Mutex<Receiver<Job>>>) {
+        loop {
+            // the lock is only held while waiting for the next job, not while running it
+            let job = receiver.lock().unwrap().recv();
+            match job {
+                Ok(job) => job(),
+                Err(_) => break,
+            }
+        }
+    }
+
+    fn wrap<T, F>(job: F) -> (Job, Receiver<Result<T, JobError>>)
+    where
+        T: Send + 'static,
+        F: FnOnce() -> T + Send + 'static,
+    {
+        let (sender, receiver) = mpsc::sync_channel(1);
+        let job: Job = Box::new(move || {
+            let _ = sender.send(Self::catch(job));
+        });
+        (job, receiver)
+    }
+
+    // a panicking job only fails itself, never the worker running it
+    fn catch<T>(job: impl FnOnce() -> T) -> Result<T, JobError> {
+        panic::catch_unwind(AssertUnwindSafe(job))
+            .map_err(|payload| JobError::Panicked(panic_message(payload.as_ref())))
+    }
+}
+
+impl Drop for WorkerPool {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    if let Some(message) = payload.downcast_ref::<&str>() {
+        message.to_string()
+    } else if let Some(message) = payload.downcast_ref::<String>() {
+        message.clone()
+    } else {
+        "unknown panic".to_string()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    // an item of a batch holding its job back until it gets the go
+    type Gated = (usize, Receiver<()>);
+
+    fn gated(count: usize) -> (Vec<SyncSender<()>>, Vec<Gated>) {
+        (0..count)
+            .map(|value| {
+                let (go, wait) = mpsc::sync_channel(1);
+                (go, (value, wait))
+            })
+            .unzip()
+    }
+
+    #[test]
+    fn a_panicking_job_only_fails_itself() {
+        let pool = WorkerPool::new(1, 4);
+
+        let failed = pool
+            .submit(|| -> u32 { panic!("the dragon woke up") })
+            .unwrap();
+        let next = pool.submit(|| 7).unwrap();
+
+        assert_eq!(
+            failed.join(),
+            Err(JobError::Panicked("the dragon woke up".to_string()))
+        );
+        // the only worker survived and ran the next job
+        assert_eq!(next.join(), Ok(7));
+    }
+
+    #[test]
+    fn a_batch_answers_in_completion_or_submission_order() {
+        let pool = WorkerPool::new(3, 3);
+
+        let (gates, items) = gated(3);
+        let batch = pool.batch(items, |(value, wait)| {
+            wait.recv().unwrap();
+            value * 10
+        });
+        let mut completed = batch.unwrap().in_completion_order();
+        let mut order = Vec::new();
+        for index in [2, 0, 1] {
+            gates[index].send(()).unwrap();
+            order.push(completed.next().unwrap());
+        }
+        assert_eq!(order, vec![(2, Ok(20)), (0, Ok(0)), (1, Ok(10))]);
+
+        let (gates, items) = gated(3);
+        let batch = pool
+            .batch(items, |(value, wait)| {
+                wait.recv().unwrap();
+                value * 10
+            })
+            .unwrap();
+        for index in [2, 0, 1] {
+            gates[index].send(()).unwrap();
+        }
+        assert_eq!(batch.in_submission_order(), vec![Ok(0), Ok(10), Ok(20)]);
+    }
+
+    #[test]
+    fn try_submit_gives_up_once_the_queue_is_full() {
+        let pool = WorkerPool::new(1, 1);
+        let (started, running) = mpsc::channel();
+        let (go, wait) = mpsc::channel::<()>();
+
+        let busy = pool
+            .submit(move || {
+                started.send(()).unwrap();
+                wait.recv().unwrap();
+            })
+            .unwrap();
+        // the only worker is busy, so the queue's single slot is all that is left
+        running.recv().unwrap();
+        let queued = pool.try_submit(|| 1).unwrap();
+        assert_eq!(pool.try_submit(|| 2).unwrap_err(), PoolError::QueueFull);
+
+        go.send(()).unwrap();
+        assert_eq!(busy.join(), Ok(()));
+        assert_eq!(queued.join(), Ok(1));
+    }
+
+    #[test]
+    fn queued_jobs_finish_before_the_pool_stops() {
+        let done = Arc::new(AtomicUsize::new(0));
+        let submit_all = |pool: &WorkerPool| {
+            for _ in 0..5 {
+                let done = Arc::clone(&done);
+                pool.submit(move || {
+                    thread::sleep(Duration::from_millis(5));
+                    done.fetch_add(1, Ordering::SeqCst);
+                })
+                .unwrap();
+            }
+        };
+
+        let pool = WorkerPool::new(1, 8);
+        submit_all(&pool);
+        pool.shutdown();
+        assert_eq!(done.load(Ordering::SeqCst), 5);
+
+        // dropping the pool waits the same way
+        {
+            let pool = WorkerPool::new(2, 8);
+            submit_all(&pool);
+        }
+        assert_eq!(done.load(Ordering::SeqCst), 10);
+    }
+}
@@ -0,0 +1,328 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, quote_spanned};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, Field, Fields, Ident, Token};
+
+// The stats every Character has, each one needs a field marked with it
+const ROLES: &[&str] = &["health", "strength", "intelligent", "weapon"];
+
+// One entry of #[character(...)]: a name, optionally with a value, e.g. `health` or `max = 100`
+struct Arg {
+    name: Ident,
+    value: Option<Expr>,
+}
+
+impl Parse for Arg {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name: Ident = input.parse()?;
+        let value = if input.peek(Token![=]) {
+            input.parse::<Token![=]>()?;
+            Some(input.parse()?)
+        } else {
+            None
+        };
+        Ok(Arg { name, value })
+    }
+}
+
+struct Stat<'a> {
+    field: &'a Field,
+    ident: &'a Ident,
+    default: Option<Expr>,
+    max: Option<Expr>,
+}
+
+pub fn expand(input: DeriveInput) -> TokenStream {
+    match derive(&input) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let fields = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(fields) => &fields.named,
+            _ => {
+                return Err(syn::Error::new_spanned(
+                    &data.fields,
+                    "#[derive(Character)] needs a struct with named fields",
+                ))
+            }
+        },
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "#[derive(Character)] only works on structs",
+            ))
+        }
+    };
+
+    let class = class(input)?;
+    let mut stats: Vec<Option<Stat>> = ROLES.iter().map(|_| None).collect();
+    let mut others = Vec::new();
+
+    for field in fields.iter() {
+        let ident = field.ident.as_ref().unwrap();
+        let mut role = None;
+        let mut default = None;
+        let mut max = None;
+
+        for attr in field
+            .attrs
+            .iter()
+            .filter(|attr| attr.path.is_ident("character"))
+        {
+            let args = attr.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)?;
+            for arg in args {
+                let key = arg.name.to_string();
+                match (key.as_str(), arg.value) {
+                    ("default", Some(value)) => default = Some(value),
+                    ("max", Some(value)) => max = Some(value),
+                    ("default" | "max", None) => {
+                        return Err(syn::Error::new_spanned(
+                            &arg.name,
+                            format!("`{}` needs a value, e.g. `{} = 100`", key, key),
+                        ))
+                    }
+                    (stat, None) if ROLES.contains(&stat) => {
+                        if role.replace(arg.name.clone()).is_some() {
+                            return Err(syn::Error::new_spanned(
+                                &arg.name,
+                                "a field can only hold one stat",
+                            ));
+                        }
+                    }
+                    _ => {
+                        let expected = ROLES.join(", ");
+                        return Err(syn::Error::new_spanned(
+                            &arg.name,
+                            format!(
+                                "unknown character argument, expected one of {}, `default` or `max`",
+                                expected
+                            ),
+                        ));
+                    }
+                }
+            }
+        }
+
+        let role = match role {
+            Some(role) => role,
+            None => {
+                if default.is_some() || max.is_some() {
+                    return Err(syn::Error::new_spanned(
+                        ident,
+                        format!(
+                            "mark the field with the stat it holds, one of {}",
+                            ROLES.join(", ")
+                        ),
+                    ));
+                }
+                others.push(ident);
+                continue;
+            }
+        };
+
+        if max.is_some() && role != "health" {
+            return Err(syn::Error::new_spanned(&role, "only `health` has a `max`"));
+        }
+
+        let slot = ROLES.iter().position(|stat| role == stat).unwrap();
+        if stats[slot].is_some() {
+            return Err(syn::Error::new_spanned(
+                &role,
+                format!("`{}` is already held by another field", role),
+            ));
+        }
+        stats[slot] = Some(Stat {
+            field,
+            ident,
+            default,
+            max,
+        });
+    }
+
+    let missing: Vec<String> = ROLES
+        .iter()
+        .zip(stats.iter())
+        .filter(|(_, stat)| stat.is_none())
+        .map(|(role, _)| format!("#[character({})]", role))
+        .collect();
+    if !missing.is_empty() {
+        return Err(syn::Error::new_spanned(
+            name,
+            format!(
+                "#[derive(Character)] is missing a field marked {}",
+                missing.join(", ")
+            ),
+        ));
+    }
+
+    let stats: Vec<Stat> = stats.into_iter().map(Option::unwrap).collect();
+    let (health, strength, intelligent, weapon) = (&stats[0], &stats[1], &stats[2], &stats[3]);
+    Ok(generate(
+        name,
+        &class,
+        health,
+        strength,
+        intelligent,
+        weapon,
+        &others,
+    ))
+}
+
+// #[character(class = Mage)] on the struct, the struct's own name when left out
+fn class(input: &DeriveInput) -> syn::Result<Ident> {
+    let mut class = None;
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("character"))
+    {
+        let args = attr.parse_args_with(Punctuated::<Arg, Token![,]>::parse_terminated)?;
+        for arg in args {
+            match (arg.name.to_string().as_str(), arg.value) {
+                ("class", Some(Expr::Path(path))) if path.path.get_ident().is_some() => {
+                    class = path.path.get_ident().cloned();
+                }
+                _ => {
+                    return Err(syn::Error::new_spanned(
+                        &arg.name,
+                        "expected `class = Variant`, a variant of CharacterClass",
+                    ))
+                }
+            }
+        }
+    }
+    Ok(class.unwrap_or_else(|| input.ident.clone()))
+}
+
+fn generate(
+    name: &Ident,
+    class: &Ident,
+    health: &Stat,
+    strength: &Stat,
+    intelligent: &Stat,
+    weapon: &Stat,
+    others: &[&Ident],
+) -> TokenStream {
+    let health_field = health.ident;
+    let strength_field = strength.ident;
+    let intelligent_field = intelligent.ident;
+    let weapon_field = weapon.ident;
+
+    let max = match &health.max {
+        Some(max) => quote!(#max),
+        None => quote!(u8::MAX),
+    };
+    // a fresh character starts at full health unless told otherwise
+    let health_default = match &health.default {
+        Some(default) => quote!(#default),
+        None => quote!(#name::MAX_HEALTH),
+    };
+    let default_of = |stat: &Stat| match &stat.default {
+        Some(default) => quote!(#default),
+        None => quote!(::std::default::Default::default()),
+    };
+    let strength_default = default_of(strength);
+    let intelligent_default = default_of(intelligent);
+    let weapon_default = default_of(weapon);
+
+    // the stats are read as u8, a field of another type is reported where it is declared
+    let as_u8 = |stat: &Stat| {
+        let field = stat.ident;
+        quote_spanned!(stat.field.ty.span()=> self.#field)
+    };
+    let health_value = as_u8(health);
+    let strength_value = as_u8(strength);
+    let intelligent_value = as_u8(intelligent);
+    let weapon_type = &weapon.field.ty;
+    let weapon_value = quote_spanned!(weapon_type.span()=> self.#weapon_field.as_ref());
+
+    let others_default = others
+        .iter()
+        .map(|field| quote!(#field: ::std::default::Default::default()));
+    let others_default_again = others_default.clone();
+
+    let max_span = health
+        .max
+        .as_ref()
+        .map(|max| max.span())
+        .unwrap_or_else(Span::call_site);
+    let max_health = quote_spanned!(max_span=> pub const MAX_HEALTH: u8 = #max;);
+
+    quote! {
+        impl #name {
+            #max_health
+
+            pub fn new() -> Self {
+                Self {
+                    #health_field: #health_default,
+                    #strength_field: #strength_default,
+                    #intelligent_field: #intelligent_default,
+                    #weapon_field: #weapon_default,
+                    #(#others_default,)*
+                }
+            }
+
+            pub fn with_stats(
+                health: u8,
+                strength: u8,
+                intelligent: u8,
+                weapon: #weapon_type,
+            ) -> Self {
+                Self {
+                    #health_field: health.min(Self::MAX_HEALTH),
+                    #strength_field: strength,
+                    #intelligent_field: intelligent,
+                    #weapon_field: weapon,
+                    #(#others_default_again,)*
+                }
+            }
+        }
+
+        impl ::std::default::Default for #name {
+            fn default() -> Self {
+                Self::new()
+            }
+        }
+
+        impl Character for #name {
+            fn class(&self) -> CharacterClass {
+                CharacterClass::#class
+            }
+
+            fn health(&self) -> u8 {
+                #health_value
+            }
+
+            fn max_health(&self) -> u8 {
+                Self::MAX_HEALTH
+            }
+
+            fn strength(&self) -> u8 {
+                #strength_value
+            }
+
+            fn intelligent(&self) -> u8 {
+                #intelligent_value
+            }
+
+            fn weapon(&self) -> &dyn Weapon {
+                #weapon_value
+            }
+
+            fn health_increase(&mut self, value: u8) {
+                self.#health_field = self.#health_field.saturating_add(value).min(Self::MAX_HEALTH);
+            }
+
+            fn health_decrease(&mut self, value: u8) {
+                self.#health_field = self.#health_field.saturating_sub(value);
+            }
+        }
+    }
+}
@@ -0,0 +1,19 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::{Block, ReturnType, Signature, Type};
+
+// The body of a function as an expression giving what it returns. The body runs in a closure,
+// or an async block, so its `return`s and `?` come back to the code around it instead of
+// leaving the function. `moved` hands the arguments over to the body, otherwise it only
+// borrows them and they can still be read once it is done.
+pub fn wrap_body(sig: &Signature, block: &Block, moved: bool) -> TokenStream {
+    let capture = if moved { quote!(move) } else { quote!() };
+    match (&sig.asyncness, &sig.output) {
+        (Some(_), _) => quote!(async #capture #block.await),
+        // an impl Trait can not be written as the return type of a closure
+        (None, ReturnType::Type(_, ty)) if !matches!(**ty, Type::ImplTrait(_)) => {
+            quote!((#capture || -> #ty #block)())
+        }
+        (None, _) => quote!((#capture || #block)()),
+    }
+}
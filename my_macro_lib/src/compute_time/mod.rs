@@ -0,0 +1,131 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{
+    parse_quote, AttributeArgs, Block, ImplItem, ItemFn, ItemImpl, Signature, TraitItemMethod, Type,
+};
+
+use self::options::Options;
+
+pub mod body;
+pub mod options;
+
+pub fn expand(args: AttributeArgs, item: TokenStream) -> TokenStream {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(error) => return error.to_compile_error(),
+    };
+
+    // an attribute on a whole impl block times every method in it
+    if let Ok(item_impl) = syn::parse2::<ItemImpl>(item.clone()) {
+        return expand_impl(&options, item_impl);
+    }
+    // free functions, and methods when the attribute sits on a single one of them
+    if let Ok(item_fn) = syn::parse2::<ItemFn>(item.clone()) {
+        return expand_fn(&options, item_fn);
+    }
+    // a provided method inside a trait definition
+    if let Ok(method) = syn::parse2::<TraitItemMethod>(item.clone()) {
+        return expand_trait_method(&options, method);
+    }
+
+    syn::Error::new_spanned(
+        item,
+        "#[compute_time] only works on functions, methods and impl blocks",
+    )
+    .to_compile_error()
+}
+
+fn expand_fn(options: &Options, mut item_fn: ItemFn) -> TokenStream {
+    let label = options.label_or(item_fn.sig.ident.to_string());
+    if let Err(error) = check_signature(&item_fn.sig) {
+        return error.to_compile_error();
+    }
+
+    item_fn.block = Box::new(timed_block(options, &label, &item_fn.block));
+    item_fn.into_token_stream()
+}
+
+fn expand_impl(options: &Options, mut item_impl: ItemImpl) -> TokenStream {
+    // methods are reported as Type::method, a `label` takes the place of the type
+    let type_name = options.label_or(type_name(&item_impl.self_ty));
+    let mut errors = TokenStream::new();
+
+    for item in item_impl.items.iter_mut() {
+        if let ImplItem::Method(method) = item {
+            if let Err(error) = check_signature(&method.sig) {
+                errors.extend(error.to_compile_error());
+                continue;
+            }
+            let label = format!("{}::{}", type_name, method.sig.ident);
+            method.block = timed_block(options, &label, &method.block);
+        }
+    }
+
+    quote! {
+        #item_impl
+        #errors
+    }
+}
+
+fn expand_trait_method(options: &Options, mut method: TraitItemMethod) -> TokenStream {
+    if let Err(error) = check_signature(&method.sig) {
+        return error.to_compile_error();
+    }
+
+    match method.default.as_mut() {
+        Some(block) => {
+            let label = options.label_or(method.sig.ident.to_string());
+            *block = timed_block(options, &label, block);
+            method.into_token_stream()
+        }
+        None => syn::Error::new_spanned(
+            &method.sig,
+            "#[compute_time] needs a body to time, put it on the methods of the impl blocks instead",
+        )
+        .to_compile_error(),
+    }
+}
+
+fn check_signature(signature: &Signature) -> syn::Result<()> {
+    if let Some(constness) = &signature.constness {
+        return Err(syn::Error::new_spanned(
+            constness,
+            "#[compute_time] can not time a const fn, the clock is only available at runtime",
+        ));
+    }
+    Ok(())
+}
+
+fn type_name(self_ty: &Type) -> String {
+    match self_ty {
+        // the last segment without its generics, e.g. `Registry` for `crate::registry::Registry<T>`
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident.to_string())
+            .unwrap_or_default(),
+        other => other.to_token_stream().to_string(),
+    }
+}
+
+// The timer is a guard: it records when it is dropped, which happens however the function ends,
+// with its last expression, a `return`, a `?` or a panic.
+// The original body stays the last expression, so its value is still what the function returns.
+// In an async fn the body only runs once the future is polled, so the guard lives
+// until the future completes and the whole awaited time is measured.
+fn timed_block(options: &Options, label: &str, body: &Block) -> Block {
+    let timer_options = options.timer_options(label);
+    parse_quote! {
+        {
+            static COMPUTE_TIME_CALL_SITE: ::my_macro_runtime::timing::timer::CallSite =
+                ::my_macro_runtime::timing::timer::CallSite::new();
+            let _compute_time = ::my_macro_runtime::timing::timer::Timer::start(
+                #timer_options,
+                &COMPUTE_TIME_CALL_SITE,
+            );
+
+            #body
+        }
+    }
+}
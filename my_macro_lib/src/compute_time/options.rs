@@ -0,0 +1,289 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{AttributeArgs, Lit, LitStr, Meta, NestedMeta};
+
+// The arguments of #[compute_time(sink = "log", level = "debug", label = "...", unit = "ms")],
+// and report = "table" to print the metrics of every timed function once this one returns,
+// or "tree" / "folded" for the nested calls between them.
+// In hot code threshold = "5ms" only reports slower calls, sample = 100 reports one call
+// out of 100 and flush = "1s" adds calls up and reports them once a second. The calls left
+// out are not in the metrics and call trees either, that is what keeps them cheap.
+// sink is one of the sinks my_macro_runtime comes with, or "registered:name" for one added
+// with register_sink. All of them are optional.
+pub struct Options {
+    pub sink: Option<LitStr>,
+    pub level: Option<Ident>,
+    pub label: Option<LitStr>,
+    pub unit: Option<Ident>,
+    pub report: Option<Ident>,
+    // in nanoseconds
+    pub threshold: Option<u64>,
+    pub sample: Option<u64>,
+    // in nanoseconds
+    pub flush: Option<u64>,
+}
+
+const NAMES: &[&str] = &[
+    "sink",
+    "level",
+    "label",
+    "unit",
+    "report",
+    "threshold",
+    "sample",
+    "flush",
+];
+
+pub const LEVELS: &[(&str, &str)] = &[
+    ("error", "Error"),
+    ("warn", "Warn"),
+    ("info", "Info"),
+    ("debug", "Debug"),
+    ("trace", "Trace"),
+];
+
+// the sinks my_macro_runtime registers itself, by the name they are registered under
+const SINKS: &[(&str, &str)] = &[
+    ("stdout", "stdout"),
+    ("stderr", "stderr"),
+    ("log", "log"),
+    ("none", "none"),
+];
+
+// what a sink registered at runtime is written with, followed by its name
+const REGISTERED: &str = "registered:";
+
+const UNITS: &[(&str, &str)] = &[
+    ("auto", "Auto"),
+    ("ns", "Nanoseconds"),
+    ("us", "Microseconds"),
+    ("ms", "Milliseconds"),
+    ("s", "Seconds"),
+];
+
+const REPORTS: &[(&str, &str)] = &[
+    ("table", "Table"),
+    ("json", "Json"),
+    ("prometheus", "Prometheus"),
+    ("tree", "CallTree"),
+    ("folded", "Folded"),
+];
+
+impl Options {
+    pub fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut options = Options {
+            sink: None,
+            level: None,
+            label: None,
+            unit: None,
+            report: None,
+            threshold: None,
+            sample: None,
+            flush: None,
+        };
+
+        for (key, lit) in name_values(
+            args,
+            NAMES,
+            "expected `name = \"value\"`, e.g. `sink = \"stderr\"`",
+        )? {
+            let lit = &lit;
+            match key.as_str() {
+                "sink" => options.sink = Some(sink(&string(lit)?)?),
+                "label" => options.label = Some(string(lit)?),
+                "level" => options.level = Some(choice(&string(lit)?, LEVELS)?),
+                "unit" => options.unit = Some(choice(&string(lit)?, UNITS)?),
+                "report" => options.report = Some(choice(&string(lit)?, REPORTS)?),
+                "threshold" => options.threshold = Some(duration(&string(lit)?)?),
+                "sample" => options.sample = Some(sample(lit)?),
+                "flush" => options.flush = Some(positive(duration(&string(lit)?)?, lit)?),
+                _ => unreachable!("name_values only lets NAMES through"),
+            }
+        }
+
+        Ok(options)
+    }
+
+    // the `label` argument, or `default` when there is none
+    pub fn label_or(&self, default: String) -> String {
+        match &self.label {
+            Some(label) => label.value(),
+            None => default,
+        }
+    }
+
+    // the runtime TimerOptions for a function reported as `label`
+    pub fn timer_options(&self, label: &str) -> TokenStream {
+        let sink = match &self.sink {
+            Some(sink) => sink.value(),
+            None => "stdout".to_string(),
+        };
+        let level = self
+            .level
+            .clone()
+            .unwrap_or_else(|| Ident::new("Info", Span::call_site()));
+        let unit = self
+            .unit
+            .clone()
+            .unwrap_or_else(|| Ident::new("Auto", Span::call_site()));
+        let threshold = self.threshold.unwrap_or(0);
+        let sample_every = self.sample.unwrap_or(1);
+        let flush_every = match self.flush {
+            Some(nanos) => quote!(Some(std::time::Duration::from_nanos(#nanos))),
+            None => quote!(None),
+        };
+        let report = match &self.report {
+            Some(format) => {
+                quote!(Some(::my_macro_runtime::timing::metrics::ReportFormat::#format))
+            }
+            None => quote!(None),
+        };
+
+        quote! {
+            ::my_macro_runtime::timing::timer::TimerOptions {
+                label: #label,
+                module: module_path!(),
+                sink: #sink,
+                level: ::my_macro_runtime::timing::sink::Level::#level,
+                unit: ::my_macro_runtime::timing::sink::Unit::#unit,
+                report: #report,
+                threshold: std::time::Duration::from_nanos(#threshold),
+                sample_every: #sample_every,
+                flush_every: #flush_every,
+            }
+        }
+    }
+}
+
+// The `name = value` arguments of an attribute, in the order they are written.
+// A name that is not one of `names`, or is given twice, is an error pointing at it,
+// anything else than `name = value` gets the `expected` message.
+pub fn name_values(
+    args: AttributeArgs,
+    names: &[&str],
+    expected: &str,
+) -> syn::Result<Vec<(String, Lit)>> {
+    let mut values: Vec<(String, Lit)> = Vec::new();
+    for arg in args {
+        let name_value = match arg {
+            NestedMeta::Meta(Meta::NameValue(name_value)) => name_value,
+            other => return Err(syn::Error::new_spanned(other, expected)),
+        };
+        let key = name_value
+            .path
+            .get_ident()
+            .map(|ident| ident.to_string())
+            .unwrap_or_default();
+
+        if !names.contains(&key.as_str()) {
+            let known: Vec<String> = names.iter().map(|name| format!("`{}`", name)).collect();
+            return Err(syn::Error::new_spanned(
+                &name_value.path,
+                format!("unknown argument, expected one of {}", known.join(", ")),
+            ));
+        }
+        if values.iter().any(|(seen, _)| *seen == key) {
+            return Err(syn::Error::new_spanned(
+                &name_value.path,
+                format!("`{}` is given more than once", key),
+            ));
+        }
+        values.push((key, name_value.lit));
+    }
+    Ok(values)
+}
+
+// maps the written value to its runtime variant, or points at it when it is not one of `choices`
+pub fn choice(value: &LitStr, choices: &[(&str, &str)]) -> syn::Result<Ident> {
+    let written = value.value();
+    match choices.iter().find(|(name, _)| *name == written) {
+        Some((_, variant)) => Ok(Ident::new(variant, value.span())),
+        None => {
+            let names: Vec<&str> = choices.iter().map(|(name, _)| *name).collect();
+            Err(syn::Error::new_spanned(
+                value,
+                format!("expected one of {}", names.join(", ")),
+            ))
+        }
+    }
+}
+
+// the name the runtime looks the sink up by, a sink registered at runtime can not be checked
+// here, but a typo in a built-in one can
+fn sink(value: &LitStr) -> syn::Result<LitStr> {
+    let written = value.value();
+    match written.strip_prefix(REGISTERED) {
+        Some(name) if !name.is_empty() => Ok(LitStr::new(name, value.span())),
+        Some(_) => Err(syn::Error::new_spanned(
+            value,
+            format!("expected the name of the sink after `{}`", REGISTERED),
+        )),
+        None => match choice(value, SINKS) {
+            Ok(_) => Ok(value.clone()),
+            Err(_) => {
+                let names: Vec<&str> = SINKS.iter().map(|(name, _)| *name).collect();
+                Err(syn::Error::new_spanned(
+                    value,
+                    format!(
+                        "expected one of {}, or \"{}name\" for a sink added with register_sink",
+                        names.join(", "),
+                        REGISTERED
+                    ),
+                ))
+            }
+        },
+    }
+}
+
+pub fn string(lit: &Lit) -> syn::Result<LitStr> {
+    match lit {
+        Lit::Str(value) => Ok(value.clone()),
+        other => Err(syn::Error::new_spanned(other, "expected a string")),
+    }
+}
+
+fn sample(lit: &Lit) -> syn::Result<u64> {
+    let every = match lit {
+        Lit::Int(every) => every.base10_parse::<u64>()?,
+        other => {
+            return Err(syn::Error::new_spanned(
+                other,
+                "expected a number, e.g. `sample = 100` for one call out of 100",
+            ))
+        }
+    };
+    positive(every, lit)
+}
+
+pub fn positive(value: u64, lit: &Lit) -> syn::Result<u64> {
+    if value == 0 {
+        return Err(syn::Error::new_spanned(lit, "must be more than zero"));
+    }
+    Ok(value)
+}
+
+// "250us", "5ms", "1.5s" and so on, in nanoseconds
+pub fn duration(value: &LitStr) -> syn::Result<u64> {
+    let written = value.value();
+    let units: &[(&str, f64)] = &[
+        ("ns", 1.0),
+        ("us", 1_000.0),
+        ("µs", 1_000.0),
+        ("ms", 1_000_000.0),
+        ("s", 1_000_000_000.0),
+    ];
+
+    for (suffix, nanos_per_unit) in units {
+        if let Some(amount) = written.strip_suffix(suffix) {
+            if let Ok(amount) = amount.trim().parse::<f64>() {
+                if amount >= 0.0 {
+                    return Ok((amount * nanos_per_unit).round() as u64);
+                }
+            }
+        }
+    }
+    Err(syn::Error::new_spanned(
+        value,
+        "expected a duration such as \"250us\", \"5ms\" or \"1s\"",
+    ))
+}
@@ -0,0 +1,146 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{Block, Expr, ItemFn, Pat, Stmt};
+
+use crate::compute_time::body::wrap_body;
+
+// The checks #[requires], #[ensures] and #[invariant] add to a function
+#[derive(Clone, Copy)]
+pub enum Contract {
+    Requires,
+    Ensures,
+    Invariant,
+}
+
+impl Contract {
+    fn attribute(self) -> &'static str {
+        match self {
+            Contract::Requires => "#[requires]",
+            Contract::Ensures => "#[ensures]",
+            Contract::Invariant => "#[invariant]",
+        }
+    }
+}
+
+// What an attribute checks: a condition, or for #[ensures] a closure over the return value
+enum Condition {
+    Holds(Expr),
+    Returns(Pat, Expr),
+}
+
+fn condition(contract: Contract, attr: TokenStream) -> syn::Result<Condition> {
+    let example = match contract {
+        Contract::Requires => "#[requires(amount > 0)]",
+        Contract::Ensures => "#[ensures(|ret| *ret == a * b)]",
+        Contract::Invariant => "#[invariant(self.health <= self.max_health)]",
+    };
+    if attr.is_empty() {
+        return Err(syn::Error::new(
+            Span::call_site(),
+            format!("expected a condition, e.g. {}", example),
+        ));
+    }
+    let expr = syn::parse2::<Expr>(attr)?;
+
+    match (contract, expr) {
+        (Contract::Ensures, Expr::Closure(closure)) if closure.inputs.len() == 1 => {
+            Ok(Condition::Returns(closure.inputs[0].clone(), *closure.body))
+        }
+        (Contract::Ensures, other) => Err(syn::Error::new_spanned(
+            other,
+            format!(
+                "expected a closure taking the return value, e.g. {}",
+                example
+            ),
+        )),
+        (_, expr) => Ok(Condition::Holds(expr)),
+    }
+}
+
+pub fn expand(contract: Contract, attr: TokenStream, item: TokenStream) -> TokenStream {
+    let condition = match condition(contract, attr) {
+        Ok(condition) => condition,
+        Err(error) => return error.to_compile_error(),
+    };
+    let item_fn = match syn::parse2::<ItemFn>(item.clone()) {
+        Ok(item_fn) => item_fn,
+        Err(_) => {
+            return syn::Error::new_spanned(
+                item,
+                format!("{} only works on functions", contract.attribute()),
+            )
+            .to_compile_error()
+        }
+    };
+
+    if cfg!(feature = "contracts") {
+        check(contract, &condition, item_fn).into_token_stream()
+    } else {
+        unchecked(&condition, item_fn).into_token_stream()
+    }
+}
+
+// Without the `contracts` feature a condition on the arguments is only put in a closure
+// that is never called, so it still has to compile and what it reads is still used.
+// An #[ensures] closure is left out with the rest.
+fn unchecked(condition: &Condition, mut item_fn: ItemFn) -> ItemFn {
+    if let Condition::Holds(expr) = condition {
+        let unused: Stmt = syn::parse_quote!(let _ = || #expr;);
+        item_fn.block.stmts.insert(0, unused);
+    }
+    item_fn
+}
+
+fn check(contract: Contract, condition: &Condition, mut item_fn: ItemFn) -> ItemFn {
+    let label = item_fn.sig.ident.to_string();
+    let assertion = |what: &str, expr: &Expr| -> Stmt {
+        syn::parse_quote! {
+            ::std::assert!(
+                #expr,
+                "{} of {} failed: {}",
+                #what,
+                #label,
+                ::std::stringify!(#expr),
+            );
+        }
+    };
+
+    match (contract, condition) {
+        (Contract::Requires, Condition::Holds(expr)) => {
+            let check = assertion("precondition", expr);
+            item_fn.block.stmts.insert(0, check);
+        }
+        (Contract::Invariant, Condition::Holds(expr)) => {
+            let on_entry = assertion("invariant on entry", expr);
+            let on_exit = assertion("invariant on exit", expr);
+            item_fn.block = after_body(&item_fn, quote!(#on_exit));
+            item_fn.block.stmts.insert(0, on_entry);
+        }
+        (Contract::Ensures, Condition::Returns(ret, expr)) => {
+            let check = assertion("postcondition", expr);
+            item_fn.block = after_body(
+                &item_fn,
+                quote! {
+                    let #ret = &contract_value;
+                    #check
+                },
+            );
+        }
+        _ => unreachable!("the condition is read to match its contract"),
+    }
+    item_fn
+}
+
+// Runs the body, then `check`, and returns what the body returned.
+// The body only borrows the arguments, the check can still read them.
+fn after_body(item_fn: &ItemFn, check: TokenStream) -> Box<Block> {
+    let body = wrap_body(&item_fn.sig, &item_fn.block, false);
+
+    Box::new(syn::parse_quote! {{
+        let contract_value = #body;
+        {
+            #check
+        }
+        contract_value
+    }})
+}
@@ -0,0 +1,398 @@
+use proc_macro2::{Span, TokenStream};
+use quote::{format_ident, quote};
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{braced, Attribute, Ident, Lit, LitInt, LitStr, Token};
+
+// What a value of an item field has to be
+#[derive(Clone, Copy)]
+enum Value {
+    // a whole number greater than zero
+    Positive,
+    // a string literal
+    Text,
+    // a variant of the named enum, e.g. `Slashing` for DamageKind
+    Variant(&'static str),
+}
+
+struct FieldSpec {
+    name: &'static str,
+    value: Value,
+    required: bool,
+}
+
+// The traits items! can implement and the fields each of them takes
+struct Kind {
+    name: &'static str,
+    fields: &'static [FieldSpec],
+}
+
+const KINDS: &[Kind] = &[
+    Kind {
+        name: "Weapon",
+        fields: &[
+            FieldSpec {
+                name: "damage",
+                value: Value::Positive,
+                required: true,
+            },
+            FieldSpec {
+                name: "kind",
+                value: Value::Variant("DamageKind"),
+                required: true,
+            },
+            FieldSpec {
+                name: "range",
+                value: Value::Positive,
+                required: false,
+            },
+            FieldSpec {
+                name: "attack",
+                value: Value::Text,
+                required: false,
+            },
+        ],
+    },
+    Kind {
+        name: "Shield",
+        fields: &[
+            FieldSpec {
+                name: "defense",
+                value: Value::Positive,
+                required: true,
+            },
+            FieldSpec {
+                name: "block",
+                value: Value::Text,
+                required: false,
+            },
+        ],
+    },
+];
+
+// `damage: 10`, `kind: Slashing` or `attack: "Sword Attack"`
+struct Field {
+    name: Ident,
+    value: FieldValue,
+}
+
+enum FieldValue {
+    Lit(Lit),
+    Ident(Ident),
+}
+
+impl Parse for Field {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let name = input.parse()?;
+        input.parse::<Token![:]>()?;
+        let value = if input.peek(Ident) {
+            FieldValue::Ident(input.parse()?)
+        } else {
+            FieldValue::Lit(input.parse()?)
+        };
+        Ok(Field { name, value })
+    }
+}
+
+// `Weapon Sword { damage: 10, kind: Slashing }`, with the attributes put on the struct
+struct Item {
+    attrs: Vec<Attribute>,
+    kind: Ident,
+    name: Ident,
+    fields: Punctuated<Field, Token![,]>,
+}
+
+impl Parse for Item {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let attrs = input.call(Attribute::parse_outer)?;
+        let kind = input.parse()?;
+        let name = input.parse()?;
+        let content;
+        braced!(content in input);
+        let fields = content.parse_terminated(Field::parse)?;
+        Ok(Item {
+            attrs,
+            kind,
+            name,
+            fields,
+        })
+    }
+}
+
+pub struct Items(Vec<Item>);
+
+impl Parse for Items {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let mut items = Vec::new();
+        while !input.is_empty() {
+            items.push(input.parse()?);
+        }
+        Ok(Items(items))
+    }
+}
+
+// An item after its fields were checked, by field name
+struct Checked<'a> {
+    item: &'a Item,
+    kind: &'static Kind,
+    values: Vec<(&'static str, &'a FieldValue)>,
+}
+
+impl Checked<'_> {
+    fn get(&self, name: &str) -> Option<&FieldValue> {
+        self.values
+            .iter()
+            .find(|(field, _)| *field == name)
+            .map(|(_, value)| *value)
+    }
+}
+
+pub fn expand(items: Items) -> TokenStream {
+    match generate(&items) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn generate(items: &Items) -> syn::Result<TokenStream> {
+    let mut checked = Vec::new();
+    for item in items.0.iter() {
+        if checked
+            .iter()
+            .any(|other: &Checked| other.item.name == item.name)
+        {
+            return Err(syn::Error::new_spanned(
+                &item.name,
+                format!("`{}` is defined twice", item.name),
+            ));
+        }
+        checked.push(check(item)?);
+    }
+
+    let definitions = checked.iter().map(definition);
+
+    // one registration function per trait, named like the ones trait_object! expects
+    let registrations = KINDS.iter().filter_map(|kind| {
+        let names: Vec<&Ident> = checked
+            .iter()
+            .filter(|item| item.kind.name == kind.name)
+            .map(|item| &item.item.name)
+            .collect();
+        if names.is_empty() {
+            return None;
+        }
+        let register = format_ident!("register_{}s", kind.name.to_lowercase());
+        let trait_path = kind_path(kind.name);
+        Some(quote! {
+            pub fn #register(registry: &mut ::rust_101::registry::Registry<dyn #trait_path>) {
+                #(::rust_101::register_type!(registry, #names);)*
+            }
+        })
+    });
+
+    Ok(quote! {
+        #(#definitions)*
+        #(#registrations)*
+    })
+}
+
+fn check(item: &Item) -> syn::Result<Checked<'_>> {
+    let kind = KINDS
+        .iter()
+        .find(|kind| item.kind == kind.name)
+        .ok_or_else(|| {
+            let names: Vec<&str> = KINDS.iter().map(|kind| kind.name).collect();
+            syn::Error::new_spanned(
+                &item.kind,
+                format!("unknown kind of item, expected one of {}", names.join(", ")),
+            )
+        })?;
+
+    let mut values = Vec::new();
+    for field in item.fields.iter() {
+        let spec = kind
+            .fields
+            .iter()
+            .find(|spec| field.name == spec.name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = kind.fields.iter().map(|spec| spec.name).collect();
+                syn::Error::new_spanned(
+                    &field.name,
+                    format!(
+                        "a {} has no field `{}`, expected one of {}",
+                        kind.name,
+                        field.name,
+                        names.join(", ")
+                    ),
+                )
+            })?;
+        if values.iter().any(|(name, _)| *name == spec.name) {
+            return Err(syn::Error::new_spanned(
+                &field.name,
+                format!("`{}` is set twice", spec.name),
+            ));
+        }
+        check_value(spec, &field.value)?;
+        values.push((spec.name, &field.value));
+    }
+
+    let missing: Vec<&str> = kind
+        .fields
+        .iter()
+        .filter(|spec| spec.required && !values.iter().any(|(name, _)| *name == spec.name))
+        .map(|spec| spec.name)
+        .collect();
+    if !missing.is_empty() {
+        return Err(syn::Error::new_spanned(
+            &item.name,
+            format!(
+                "{} `{}` is missing {}",
+                kind.name,
+                item.name,
+                missing.join(", ")
+            ),
+        ));
+    }
+
+    Ok(Checked { item, kind, values })
+}
+
+fn check_value(spec: &FieldSpec, value: &FieldValue) -> syn::Result<()> {
+    match (spec.value, value) {
+        (Value::Positive, FieldValue::Lit(Lit::Int(int))) => match int.base10_parse::<u32>() {
+            Ok(number) if number > 0 => Ok(()),
+            _ => Err(syn::Error::new_spanned(
+                int,
+                format!("`{}` has to be a whole number above zero", spec.name),
+            )),
+        },
+        (Value::Text, FieldValue::Lit(Lit::Str(_))) => Ok(()),
+        // the variant itself is checked by the compiler against the enum
+        (Value::Variant(_), FieldValue::Ident(_)) => Ok(()),
+        (expected, value) => {
+            let expected = match expected {
+                Value::Positive => "a whole number, e.g. `10`".to_string(),
+                Value::Text => "a string, e.g. \"Sword Attack\"".to_string(),
+                Value::Variant(enum_name) => format!("a variant of {}", enum_name),
+            };
+            let message = format!("`{}` has to be {}", spec.name, expected);
+            Err(match value {
+                FieldValue::Lit(lit) => syn::Error::new_spanned(lit, message),
+                FieldValue::Ident(ident) => syn::Error::new_spanned(ident, message),
+            })
+        }
+    }
+}
+
+// where the trait of a kind lives in rust_101
+fn kind_path(kind: &str) -> TokenStream {
+    match kind {
+        "Weapon" => quote!(::rust_101::basic_oop::weapons::Weapon),
+        _ => quote!(::rust_101::shields::shield::Shield),
+    }
+}
+
+fn definition(item: &Checked) -> TokenStream {
+    let attrs = &item.item.attrs;
+    let name = &item.item.name;
+    let tag = name.to_string();
+    let body = match item.kind.name {
+        "Weapon" => weapon(item),
+        _ => shield(item),
+    };
+    quote! {
+        #(#attrs)*
+        #[derive(
+            Debug,
+            Clone,
+            ::rust_101::registry::serde::Serialize,
+            ::rust_101::registry::serde::Deserialize,
+        )]
+        #[serde(crate = "::rust_101::registry::serde")]
+        pub struct #name;
+
+        impl ::rust_101::registry::TypeTag for #name {
+            const TYPE_TAG: &'static str = #tag;
+        }
+
+        #body
+    }
+}
+
+// without a suffix, `10u8` would not fit the u32 the traits return
+fn number(value: Option<&FieldValue>) -> Option<LitInt> {
+    match value {
+        Some(FieldValue::Lit(Lit::Int(int))) => {
+            let number = int.base10_parse::<u32>().ok()?;
+            Some(LitInt::new(&number.to_string(), int.span()))
+        }
+        _ => None,
+    }
+}
+
+// the given text, or `default` when the field was left out
+fn text(value: Option<&FieldValue>, default: String) -> LitStr {
+    match value {
+        Some(FieldValue::Lit(Lit::Str(text))) => text.clone(),
+        _ => LitStr::new(&default, Span::call_site()),
+    }
+}
+
+fn weapon(item: &Checked) -> TokenStream {
+    let name = &item.item.name;
+    let tag = name.to_string();
+    let damage = number(item.get("damage"));
+    let kind = match item.get("kind") {
+        Some(FieldValue::Ident(kind)) => kind,
+        _ => unreachable!("checked to be a variant"),
+    };
+    let attack = text(item.get("attack"), format!("{} Attack", name));
+    // left out, the trait's own default of melee range is kept
+    let range = number(item.get("range")).map(|range| {
+        quote! {
+            fn range(&self) -> u32 {
+                #range
+            }
+        }
+    });
+
+    quote! {
+        impl ::rust_101::basic_oop::weapons::Weapon for #name {
+            fn name(&self) -> &'static str {
+                #tag
+            }
+
+            fn attack(&self) {
+                println!("{}", #attack);
+            }
+
+            fn damage(&self) -> u32 {
+                #damage
+            }
+
+            fn kind(&self) -> ::rust_101::basic_oop::weapons::DamageKind {
+                ::rust_101::basic_oop::weapons::DamageKind::#kind
+            }
+
+            #range
+        }
+    }
+}
+
+fn shield(item: &Checked) -> TokenStream {
+    let name = &item.item.name;
+    let defense = number(item.get("defense"));
+    let block = text(item.get("block"), format!("{} Block", name));
+
+    quote! {
+        impl ::rust_101::shields::shield::Shield for #name {
+            fn block(&self) {
+                println!("{}", #block);
+            }
+
+            fn defense(&self) -> u32 {
+                #defense
+            }
+        }
+    }
+}
@@ -1,30 +1,183 @@
 use proc_macro::TokenStream;
-use syn::{parse_macro_input, ItemFn};
-use quote::quote;
+use syn::{parse_macro_input, AttributeArgs, DeriveInput};
 
+use contracts::Contract;
+
+mod character;
+mod compute_time;
+mod contracts;
+mod items;
+mod memoize;
+mod retry;
+mod spell;
+mod state_enum;
+mod trace;
+
+// The code most of these macros generate calls into my_macro_runtime,
+// a crate using them depends on both.
+
+// Reports how long a function took, and records it in the metrics of my_macro_runtime, e.g.
+// #[compute_time]
+// fn tick() { ... }
+// #[compute_time(sink = "log", level = "debug", label = "load save", unit = "ms")]
+// fn load() { ... }
+// #[compute_time(report = "table")]
+// fn main() { ... }
+// A sink added with register_sink at runtime is picked with sink = "registered:name".
+// Works on free functions, async functions, methods, and whole impl blocks,
+// where every method is reported as Type::method.
+#[proc_macro_attribute]
+pub fn compute_time(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    compute_time::expand(args, item.into()).into()
+}
+
+// Caches the results of a pure function by its arguments, e.g.
+// #[memoize]
+// fn power(a: i32, b: i32) -> i32 { ... }
+// #[memoize(capacity = 256, ttl = "30s", scope = "global")]
+// fn max_damage(level: u32, weapon: String) -> u32 { ... }
+// capacity is how many results are kept, the least recently used go first (128 by default),
+// ttl drops results older than that, and scope = "global" shares one cache between
+// all threads instead of keeping one per thread.
+// Arguments are taken by value and need Clone + Hash + Eq, the result needs Clone.
+#[proc_macro_attribute]
+pub fn memoize(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    memoize::expand(args, item.into()).into()
+}
+
+// Calls a function returning a Result again when it fails, e.g.
+// #[retry(times = 3, backoff = "exponential", delay = "10ms")]
+// fn open_treasure(key: &str) -> Result<String, ChestError> { ... }
+// #[retry(times = 5, retry_if = "DeliveryError::is_transient", on_attempt = "log_attempt")]
+// fn deliver(&self) -> Result<(), DeliveryError> { ... }
+// times counts every attempt, the first one included (3 by default).
+// backoff is none, fixed, linear or exponential, starting from delay and capped by max_delay.
+// retry_if is a fn(&E) -> bool telling which errors are worth another attempt,
+// on_attempt a fn(&Attempt<E>) called after every failed one.
+// The body runs once per attempt, so it can not move out of the arguments.
+#[proc_macro_attribute]
+pub fn retry(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    retry::expand(args, item.into()).into()
+}
+
+// Prints every call with its arguments and how it returned, indented by call depth, e.g.
+// #[trace]
+// fn open_treasure(#[trace(redact)] key: &str, #[trace(skip)] map: &Map) -> Result<String, String>
+// prints
+// → open_treasure(key = ***)
+//   → unlock(...)
+//   ← unlock: true
+// ← open_treasure: Ok("You got 10$")
+// Arguments are shown with Debug, #[trace(redact)] hides the value and #[trace(skip)] the whole
+// argument. An Err is reported as a failure, a panic is reported too.
+// sink = "stderr" or "log" (at `level`, "debug" by default) instead of stdout.
+#[proc_macro_attribute]
+pub fn trace(attr: TokenStream, item: TokenStream) -> TokenStream {
+    let args = parse_macro_input!(attr as AttributeArgs);
+    trace::expand(args, item.into()).into()
+}
+
+// Checks a condition on the arguments before the body runs, e.g.
+// #[requires(amount > 0)]
+// fn withdraw(&self, amount: u32) -> u32 { ... }
+// A failed check panics, naming the function and the condition.
+// Contracts are only checked with the `contracts` feature of my_macro_lib, then in release
+// builds as well, without it the attributes leave the function as it is.
 #[proc_macro_attribute]
-pub fn compute_time(_attr: TokenStream, item: TokenStream) -> TokenStream {
-    // The parse_macro_input! macro is used to parse the input tokens into an ItemFn, which represents a function.
-    let input = parse_macro_input!(item as ItemFn);
-    // fn_name: The name of the function.
-    // fn_body: The body of the function.
-    // fn_visibility: The visibility (e.g., pub).
-    // fn_signature: The function signature.
-    let fn_name = &input.sig.ident;
-    let fn_body = &input.block;
-    let fn_visibility = &input.vis;
-    let fn_signature = &input.sig;
-
-    let expanded = quote! {
-        #fn_visibility #fn_signature {
-            let start = std::time::Instant::now();
-
-            #fn_body
-
-            let elapsed = start.elapsed();
-            println!("{} took {:?}", stringify!(#fn_name), elapsed);
-        }
-    };
-
-    expanded.into()
+pub fn requires(attr: TokenStream, item: TokenStream) -> TokenStream {
+    contracts::expand(Contract::Requires, attr.into(), item.into()).into()
+}
+
+// Checks a condition on the return value after the body ran, e.g.
+// #[ensures(|ret| *ret == a * b)]
+// fn mul(a: i32, b: i32) -> i32 { ... }
+// The closure gets a reference to the return value and can read the arguments too.
+// Early `return`s and `?` are checked as well.
+#[proc_macro_attribute]
+pub fn ensures(attr: TokenStream, item: TokenStream) -> TokenStream {
+    contracts::expand(Contract::Ensures, attr.into(), item.into()).into()
+}
+
+// Checks a condition before and after the body runs, usually one a method keeps on self, e.g.
+// #[invariant(self.health <= self.max_health)]
+// fn heal(&mut self, amount: u32) -> u32 { ... }
+#[proc_macro_attribute]
+pub fn invariant(attr: TokenStream, item: TokenStream) -> TokenStream {
+    contracts::expand(Contract::Invariant, attr.into(), item.into()).into()
+}
+
+// Builds an Ability from the elements of a spell, e.g.
+// spell!(fire)
+// spell!(fire + wind, power = 3, target = area(2), name = "Firestorm")
+// Elements are fire, water, wind, earth, poison and light, combined with `+`,
+// an unknown or repeated one is an error, and so is mixing the healing water and light with
+// the others. Each element adds its own effects, fire + wind deals two Damage rather than
+// one bigger one. power goes from 1 to 5 and scales the effects, the mana and the cooldown.
+// target is `self`, `single` or `area(radius)`, single by default and area(1) when wind is
+// in the spell. The name is made from the elements when left out.
+// The Ability is the one of the rust_101 crate, named by its full path like items! does.
+#[proc_macro]
+pub fn spell(input: TokenStream) -> TokenStream {
+    let spell = parse_macro_input!(input as spell::Spell);
+    spell::expand(spell).into()
+}
+
+// Implements the Character trait for a struct, e.g.
+// #[derive(Character)]
+// #[character(class = Warrior)]
+// struct Warrior {
+//     #[character(health, max = 100)]
+//     health: u8,
+//     #[character(strength, default = 10)]
+//     strength: u8,
+//     #[character(intelligent)]
+//     intelligent: u8,
+//     #[character(weapon, default = Box::new(Sword))]
+//     weapon: Box<dyn Weapon>,
+// }
+// Every stat needs exactly one field, `default` is what `new` starts it at and `max` caps health.
+// Also generates `new`, `with_stats`, `max_health` and Default.
+// `Character`, `CharacterClass` and `Weapon` have to be in scope where it is derived.
+#[proc_macro_derive(Character, attributes(character))]
+pub fn derive_character(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    character::expand(input).into()
+}
+
+// Display, FromStr and a list of the variants for an enum, e.g.
+// #[derive(StateEnum)]
+// enum CrabbyState {
+//     Resting,
+//     #[display("Collecting {0} treasures")]
+//     Collecting(u32),
+//     #[display("Guarding {place} for {turns} turns")]
+//     Guarding { place: String, turns: u8 },
+// }
+// A variant without #[display] shows as its name followed by its fields, e.g. "Collecting 15".
+// FromStr reads back what Display writes, fields are parsed with their own FromStr.
+// Also generates `VARIANTS`, `variant_name` and `iter`, which fills in fields with their Default.
+#[proc_macro_derive(StateEnum, attributes(display))]
+pub fn derive_state_enum(item: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(item as DeriveInput);
+    state_enum::expand(input).into()
+}
+
+// Defines unit item types with their trait impl and registration, e.g.
+// items! {
+//     Weapon Sword { damage: 10, kind: Slashing }
+//     Weapon Bow { damage: 8, kind: Piercing, range: 6, attack: "Bow Shot" }
+//     Shield WoodShield { defense: 5, block: "Defend by wood shield" }
+// }
+// A Weapon takes damage, kind (a DamageKind variant), and optionally range and attack,
+// a Shield takes defense and optionally block. Unknown, repeated or missing fields are errors.
+// Also generates `register_weapons` / `register_shields` filling the trait's registry.
+// The traits, DamageKind and the registry are those of the rust_101 crate, named by their
+// full path, so items! only makes sense in rust_101 or a crate depending on it.
+#[proc_macro]
+pub fn items(input: TokenStream) -> TokenStream {
+    let items = parse_macro_input!(input as items::Items);
+    items::expand(items).into()
 }
@@ -0,0 +1,204 @@
+use proc_macro2::TokenStream;
+use quote::{quote, ToTokens};
+use syn::{AttributeArgs, FnArg, ItemFn, Lit, LitStr, Pat, ReturnType, Type};
+
+use crate::compute_time::body::wrap_body;
+use crate::compute_time::options::{duration, name_values, positive, string};
+
+const DEFAULT_CAPACITY: u64 = 128;
+
+// The arguments of #[memoize(capacity = 256, ttl = "30s", scope = "global")], all optional.
+// The cache is per thread unless scope = "global" shares one between all threads.
+struct Options {
+    capacity: Option<u64>,
+    // in nanoseconds
+    ttl: Option<u64>,
+    global: Option<bool>,
+}
+
+impl Options {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut options = Options {
+            capacity: None,
+            ttl: None,
+            global: None,
+        };
+
+        for (key, lit) in name_values(
+            args,
+            &["capacity", "ttl", "scope"],
+            "expected `name = value`, e.g. `capacity = 256`",
+        )? {
+            let lit = &lit;
+            match key.as_str() {
+                "capacity" => options.capacity = Some(capacity(lit)?),
+                "ttl" => options.ttl = Some(positive(duration(&string(lit)?)?, lit)?),
+                "scope" => options.global = Some(scope(&string(lit)?)?),
+                _ => unreachable!("name_values only lets the names above through"),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+fn capacity(lit: &Lit) -> syn::Result<u64> {
+    let capacity = match lit {
+        Lit::Int(capacity) => capacity.base10_parse::<u64>()?,
+        other => {
+            return Err(syn::Error::new_spanned(
+                other,
+                "expected a number of results to keep, e.g. `capacity = 256`",
+            ))
+        }
+    };
+    positive(capacity, lit)
+}
+
+fn scope(value: &LitStr) -> syn::Result<bool> {
+    match value.value().as_str() {
+        "thread" => Ok(false),
+        "global" => Ok(true),
+        _ => Err(syn::Error::new_spanned(
+            value,
+            "expected \"thread\" for a cache per thread or \"global\" for one shared cache",
+        )),
+    }
+}
+
+pub fn expand(args: AttributeArgs, item: TokenStream) -> TokenStream {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(error) => return error.to_compile_error(),
+    };
+    let item_fn = match syn::parse2::<ItemFn>(item.clone()) {
+        Ok(item_fn) => item_fn,
+        Err(_) => {
+            return syn::Error::new_spanned(item, "#[memoize] only works on functions")
+                .to_compile_error()
+        }
+    };
+
+    match memoize(&options, item_fn) {
+        Ok(item_fn) => item_fn.into_token_stream(),
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn memoize(options: &Options, mut item_fn: ItemFn) -> syn::Result<ItemFn> {
+    let sig = &item_fn.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "#[memoize] does not work on async functions",
+        ));
+    }
+    // the cache is a static, it can not depend on the type parameters
+    if let Some(param) = sig.generics.type_params().next() {
+        return Err(syn::Error::new_spanned(
+            param,
+            "#[memoize] does not work on generic functions",
+        ));
+    }
+    if let Some(param) = sig.generics.const_params().next() {
+        return Err(syn::Error::new_spanned(
+            param,
+            "#[memoize] does not work on generic functions",
+        ));
+    }
+
+    let mut names = Vec::new();
+    let mut types = Vec::new();
+    for input in sig.inputs.iter() {
+        let typed = match input {
+            FnArg::Typed(typed) => typed,
+            FnArg::Receiver(receiver) => {
+                return Err(syn::Error::new_spanned(
+                    receiver,
+                    "#[memoize] caches by the arguments, it does not work on methods",
+                ))
+            }
+        };
+        let name = match &*typed.pat {
+            Pat::Ident(pat) => &pat.ident,
+            other => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "#[memoize] needs every argument to have a plain name",
+                ))
+            }
+        };
+        if let Type::Reference(_) | Type::ImplTrait(_) = &*typed.ty {
+            return Err(syn::Error::new_spanned(
+                &typed.ty,
+                "#[memoize] keeps a copy of the arguments, take them by value",
+            ));
+        }
+        names.push(name.clone());
+        types.push((*typed.ty).clone());
+    }
+
+    let output = match &sig.output {
+        ReturnType::Default => quote!(()),
+        ReturnType::Type(_, ty) => {
+            if let Type::ImplTrait(_) = &**ty {
+                return Err(syn::Error::new_spanned(
+                    ty,
+                    "#[memoize] keeps a copy of the result, it can not be an impl Trait",
+                ));
+            }
+            ty.to_token_stream()
+        }
+    };
+
+    let label = sig.ident.to_string();
+    let capacity = options.capacity.unwrap_or(DEFAULT_CAPACITY) as usize;
+    let ttl = match options.ttl {
+        Some(nanos) => quote!(Some(::std::time::Duration::from_nanos(#nanos))),
+        None => quote!(None),
+    };
+    let key_type = quote!((#(#types,)*));
+    let runtime = quote!(::my_macro_runtime::memoize);
+
+    let (cache, get, insert) = if options.global.unwrap_or(false) {
+        (
+            quote! {
+                static MEMOIZE_CACHE: #runtime::SharedCache<#key_type, #output> =
+                    #runtime::SharedCache::new(#capacity, #ttl);
+            },
+            quote!(MEMOIZE_CACHE.get(&memoize_key)),
+            quote!(MEMOIZE_CACHE.insert(memoize_key, ::std::clone::Clone::clone(&memoize_value))),
+        )
+    } else {
+        (
+            quote! {
+                ::std::thread_local! {
+                    static MEMOIZE_CACHE: #runtime::LocalCache<#key_type, #output> =
+                        #runtime::LocalCache::new(#capacity, #ttl);
+                }
+            },
+            quote!(MEMOIZE_CACHE.with(|cache| cache.get(&memoize_key))),
+            quote!(MEMOIZE_CACHE.with(|cache| {
+                cache.insert(memoize_key, ::std::clone::Clone::clone(&memoize_value))
+            })),
+        )
+    };
+
+    // the body's `return`s come back here to be cached
+    let body = wrap_body(sig, &item_fn.block, true);
+    item_fn.block = Box::new(syn::parse_quote! {{
+        static MEMOIZE_SITE: #runtime::MemoSite =
+            #runtime::MemoSite::new(#label, module_path!());
+        #cache
+        let memoize_key: #key_type = (#(::std::clone::Clone::clone(&#names),)*);
+        if let ::std::option::Option::Some(memoize_value) = #get {
+            MEMOIZE_SITE.hit();
+            return memoize_value;
+        }
+        MEMOIZE_SITE.miss();
+        let memoize_value = #body;
+        #insert;
+        memoize_value
+    }});
+    Ok(item_fn)
+}
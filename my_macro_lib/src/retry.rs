@@ -0,0 +1,198 @@
+use proc_macro2::{Ident, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{AttributeArgs, ItemFn, Lit, Path, ReturnType, Type};
+
+use crate::compute_time::options::{choice, duration, name_values, positive, string};
+
+const BACKOFFS: &[(&str, &str)] = &[
+    ("none", "None"),
+    ("fixed", "Fixed"),
+    ("linear", "Linear"),
+    ("exponential", "Exponential"),
+];
+
+// in nanoseconds
+const DEFAULT_DELAY: u64 = 10_000_000;
+const DEFAULT_MAX_DELAY: u64 = 30_000_000_000;
+
+// The arguments of #[retry(times = 3, backoff = "exponential", delay = "10ms", max_delay = "1s",
+// retry_if = "is_transient", on_attempt = "log_attempt")], all optional.
+// Without a backoff there is no wait between attempts, unless a delay is given.
+struct Options {
+    times: Option<u64>,
+    backoff: Option<Ident>,
+    // in nanoseconds
+    delay: Option<u64>,
+    max_delay: Option<u64>,
+    retry_if: Option<Path>,
+    on_attempt: Option<Path>,
+}
+
+impl Options {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut options = Options {
+            times: None,
+            backoff: None,
+            delay: None,
+            max_delay: None,
+            retry_if: None,
+            on_attempt: None,
+        };
+
+        for (key, lit) in name_values(
+            args,
+            &[
+                "times",
+                "backoff",
+                "delay",
+                "max_delay",
+                "retry_if",
+                "on_attempt",
+            ],
+            "expected `name = value`, e.g. `times = 3`",
+        )? {
+            let lit = &lit;
+            match key.as_str() {
+                "times" => options.times = Some(times(lit)?),
+                "backoff" => options.backoff = Some(choice(&string(lit)?, BACKOFFS)?),
+                "delay" => options.delay = Some(duration(&string(lit)?)?),
+                "max_delay" => options.max_delay = Some(duration(&string(lit)?)?),
+                "retry_if" => options.retry_if = Some(function(lit)?),
+                "on_attempt" => options.on_attempt = Some(function(lit)?),
+                _ => unreachable!("name_values only lets the names above through"),
+            }
+        }
+
+        Ok(options)
+    }
+
+    // the runtime RetryPolicy
+    fn policy(&self) -> TokenStream {
+        let times = self.times.unwrap_or(3) as u32;
+        let backoff = match (&self.backoff, self.delay) {
+            (Some(backoff), _) => quote!(#backoff),
+            (None, Some(_)) => quote!(Fixed),
+            (None, None) => quote!(None),
+        };
+        let delay = self.delay.unwrap_or(DEFAULT_DELAY);
+        let max_delay = self.max_delay.unwrap_or(DEFAULT_MAX_DELAY);
+
+        quote! {
+            ::my_macro_runtime::retry::RetryPolicy {
+                times: #times,
+                backoff: ::my_macro_runtime::retry::Backoff::#backoff,
+                delay: ::std::time::Duration::from_nanos(#delay),
+                max_delay: ::std::time::Duration::from_nanos(#max_delay),
+            }
+        }
+    }
+}
+
+fn times(lit: &Lit) -> syn::Result<u64> {
+    let times = match lit {
+        Lit::Int(times) => times.base10_parse::<u64>()?,
+        other => {
+            return Err(syn::Error::new_spanned(
+                other,
+                "expected the number of attempts, e.g. `times = 3`",
+            ))
+        }
+    };
+    if times > u32::MAX as u64 {
+        return Err(syn::Error::new_spanned(lit, "too many attempts"));
+    }
+    positive(times, lit)
+}
+
+// the path of a function, written as a string, e.g. "DeliveryError::is_transient"
+fn function(lit: &Lit) -> syn::Result<Path> {
+    string(lit)?.parse::<Path>().map_err(|_| {
+        syn::Error::new_spanned(
+            lit,
+            "expected the path of a function, e.g. \"is_transient\"",
+        )
+    })
+}
+
+pub fn expand(args: AttributeArgs, item: TokenStream) -> TokenStream {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(error) => return error.to_compile_error(),
+    };
+    let item_fn = match syn::parse2::<ItemFn>(item.clone()) {
+        Ok(item_fn) => item_fn,
+        Err(_) => {
+            return syn::Error::new_spanned(item, "#[retry] only works on functions")
+                .to_compile_error()
+        }
+    };
+
+    match retry(&options, item_fn) {
+        Ok(item_fn) => item_fn.into_token_stream(),
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn retry(options: &Options, mut item_fn: ItemFn) -> syn::Result<ItemFn> {
+    let sig = &item_fn.sig;
+    if let Some(asyncness) = &sig.asyncness {
+        return Err(syn::Error::new_spanned(
+            asyncness,
+            "#[retry] does not work on async functions, it waits by blocking the thread",
+        ));
+    }
+    let output = match &sig.output {
+        ReturnType::Type(_, ty) if returns_result(ty) => ty,
+        ReturnType::Type(_, ty) => {
+            return Err(syn::Error::new_spanned(
+                ty,
+                "#[retry] needs a function returning a Result, spelled `Result<T, E>` or \
+                 `io::Result<T>`, other aliases are not recognized",
+            ))
+        }
+        ReturnType::Default => {
+            return Err(syn::Error::new_spanned(
+                sig,
+                "#[retry] needs a function returning a Result",
+            ))
+        }
+    };
+
+    let label = sig.ident.to_string();
+    let policy = options.policy();
+    let retryable = match &options.retry_if {
+        Some(retry_if) => quote!(|error| #retry_if(error)),
+        None => quote!(|_| true),
+    };
+    let on_attempt = match &options.on_attempt {
+        Some(on_attempt) => quote!(|attempt| #on_attempt(attempt)),
+        None => quote!(|_| {}),
+    };
+
+    // the original body runs once per attempt, so it must not move out of the arguments
+    let block = &item_fn.block;
+    item_fn.block = Box::new(syn::parse_quote! {{
+        ::my_macro_runtime::retry::run(
+            #label,
+            &#policy,
+            || -> #output #block,
+            #retryable,
+            #on_attempt,
+        )
+    }});
+    Ok(item_fn)
+}
+
+// Result<T, E> and the aliases named exactly like it, io::Result<T>, fmt::Result and so on.
+// Other names are not guessed at, a SearchResult may well be a plain struct.
+pub fn returns_result(ty: &Type) -> bool {
+    match ty {
+        Type::Path(path) => path
+            .path
+            .segments
+            .last()
+            .map(|segment| segment.ident == "Result")
+            .unwrap_or(false),
+        _ => false,
+    }
+}
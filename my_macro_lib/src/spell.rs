@@ -0,0 +1,334 @@
+use proc_macro2::TokenStream;
+use quote::quote;
+use syn::parse::{Parse, ParseStream};
+use syn::punctuated::Punctuated;
+use syn::{parenthesized, Ident, LitInt, LitStr, Token};
+
+// What an element does to each target, at power 1
+#[derive(Clone, Copy)]
+enum Base {
+    Damage(u32),
+    Heal(u32),
+    Stun { damage: u32, turns: u32 },
+    Poison { damage: u32, turns: u32 },
+    Regenerate { heal: u32, turns: u32 },
+}
+
+impl Base {
+    // a spell either hurts its targets or helps them, never both
+    fn heals(self) -> bool {
+        matches!(self, Base::Heal(_) | Base::Regenerate { .. })
+    }
+}
+
+struct Element {
+    name: &'static str,
+    mana: u32,
+    base: Base,
+}
+
+// The elements a spell can be made of
+const ELEMENTS: &[Element] = &[
+    Element {
+        name: "fire",
+        mana: 10,
+        base: Base::Damage(12),
+    },
+    Element {
+        name: "water",
+        mana: 8,
+        base: Base::Heal(10),
+    },
+    Element {
+        name: "wind",
+        mana: 6,
+        base: Base::Damage(6),
+    },
+    Element {
+        name: "earth",
+        mana: 12,
+        base: Base::Stun {
+            damage: 4,
+            turns: 1,
+        },
+    },
+    Element {
+        name: "poison",
+        mana: 8,
+        base: Base::Poison {
+            damage: 3,
+            turns: 3,
+        },
+    },
+    Element {
+        name: "light",
+        mana: 10,
+        base: Base::Regenerate { heal: 4, turns: 3 },
+    },
+];
+
+const MAX_POWER: u32 = 5;
+// appended to the name of a spell cast above power 1, so each power is its own ability
+const POWER_SUFFIXES: [&str; MAX_POWER as usize] = ["", " II", " III", " IV", " V"];
+
+// Who the spell lands on, `self`, `single` or `area(2)`
+enum Target {
+    Caster,
+    Single,
+    Area(LitInt),
+}
+
+impl Parse for Target {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.peek(Token![self]) {
+            input.parse::<Token![self]>()?;
+            return Ok(Target::Caster);
+        }
+        let name: Ident = input.parse()?;
+        match name.to_string().as_str() {
+            "single" => Ok(Target::Single),
+            "area" => {
+                let content;
+                parenthesized!(content in input);
+                Ok(Target::Area(content.parse()?))
+            }
+            _ => Err(syn::Error::new_spanned(
+                name,
+                "expected `self`, `single` or `area(radius)`",
+            )),
+        }
+    }
+}
+
+// `power = 3`, `target = area(2)` or `name = "Firestorm"`
+enum Setting {
+    Power(Ident, LitInt),
+    Target(Ident, Target),
+    Name(Ident, LitStr),
+}
+
+impl Setting {
+    fn key(&self) -> &Ident {
+        match self {
+            Setting::Power(key, _) | Setting::Target(key, _) | Setting::Name(key, _) => key,
+        }
+    }
+}
+
+impl Parse for Setting {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let key: Ident = input.parse()?;
+        input.parse::<Token![=]>()?;
+        match key.to_string().as_str() {
+            "power" => Ok(Setting::Power(key, input.parse()?)),
+            "target" => Ok(Setting::Target(key, input.parse()?)),
+            "name" => Ok(Setting::Name(key, input.parse()?)),
+            _ => Err(syn::Error::new_spanned(
+                key,
+                "unknown setting, expected one of `power`, `target`, `name`",
+            )),
+        }
+    }
+}
+
+// `fire + wind, power = 3, target = area(2)`
+pub struct Spell {
+    elements: Punctuated<Ident, Token![+]>,
+    settings: Vec<Setting>,
+}
+
+impl Parse for Spell {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        if input.is_empty() {
+            return Err(input.error("expected the elements of the spell, e.g. `fire + wind`"));
+        }
+        let elements = Punctuated::parse_separated_nonempty(input)?;
+        let mut settings = Vec::new();
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            settings.push(input.parse()?);
+        }
+        Ok(Spell { elements, settings })
+    }
+}
+
+pub fn expand(spell: Spell) -> TokenStream {
+    match generate(&spell) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn generate(spell: &Spell) -> syn::Result<TokenStream> {
+    let mut elements: Vec<&Element> = Vec::new();
+    for ident in spell.elements.iter() {
+        let element = ELEMENTS
+            .iter()
+            .find(|element| ident == element.name)
+            .ok_or_else(|| {
+                let names: Vec<&str> = ELEMENTS.iter().map(|element| element.name).collect();
+                syn::Error::new_spanned(
+                    ident,
+                    format!(
+                        "unknown element `{}`, expected one of {}",
+                        ident,
+                        names.join(", ")
+                    ),
+                )
+            })?;
+        if elements.iter().any(|other| other.name == element.name) {
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!(
+                    "`{}` is already in the spell, raise its power instead",
+                    ident
+                ),
+            ));
+        }
+        if let Some(other) = elements
+            .iter()
+            .find(|other| other.base.heals() != element.base.heals())
+        {
+            let (healing, hurting) = if element.base.heals() {
+                (element.name, other.name)
+            } else {
+                (other.name, element.name)
+            };
+            return Err(syn::Error::new_spanned(
+                ident,
+                format!(
+                    "`{}` heals and `{}` hurts, a spell can not do both to its targets",
+                    healing, hurting
+                ),
+            ));
+        }
+        elements.push(element);
+    }
+
+    let mut power = None;
+    let mut target = None;
+    let mut name = None;
+    for setting in spell.settings.iter() {
+        let slot_taken = match setting {
+            Setting::Power(_, lit) => power.replace(power_level(lit)?).is_some(),
+            Setting::Target(_, value) => target.replace(targeting(value)?).is_some(),
+            Setting::Name(_, lit) => name.replace(lit.value()).is_some(),
+        };
+        if slot_taken {
+            return Err(syn::Error::new_spanned(
+                setting.key(),
+                format!("`{}` is given more than once", setting.key()),
+            ));
+        }
+    }
+
+    let power = power.unwrap_or(1);
+    let name = name.unwrap_or_else(|| {
+        let names: Vec<String> = elements
+            .iter()
+            .map(|element| capitalized(element.name))
+            .collect();
+        format!(
+            "{}{}",
+            names.join(" + "),
+            POWER_SUFFIXES[power as usize - 1]
+        )
+    });
+    // wind carries a spell further, to everyone next to the target
+    let targeting = target.unwrap_or_else(|| {
+        if elements.iter().any(|element| element.name == "wind") {
+            quote!(::rust_101::skills::ability::Targeting::Area { radius: 1 })
+        } else {
+            quote!(::rust_101::skills::ability::Targeting::Single)
+        }
+    });
+    let mana = elements.iter().map(|element| element.mana).sum::<u32>() * power;
+    // every extra element and every power above the first needs a turn to recover
+    let cooldown = (elements.len() as u32 - 1) + (power - 1);
+    let effects = elements.iter().map(|element| effects(element.base, power));
+
+    Ok(quote! {
+        ::rust_101::skills::ability::Ability::new(#name, #targeting)
+            .mana(#mana)
+            .cooldown(#cooldown)
+            #(#effects)*
+    })
+}
+
+fn power_level(lit: &LitInt) -> syn::Result<u32> {
+    match lit.base10_parse::<u32>() {
+        Ok(power) if (1..=MAX_POWER).contains(&power) => Ok(power),
+        _ => Err(syn::Error::new_spanned(
+            lit,
+            format!("the power of a spell goes from 1 to {}", MAX_POWER),
+        )),
+    }
+}
+
+fn targeting(target: &Target) -> syn::Result<TokenStream> {
+    match target {
+        Target::Caster => Ok(quote!(::rust_101::skills::ability::Targeting::SelfOnly)),
+        Target::Single => Ok(quote!(::rust_101::skills::ability::Targeting::Single)),
+        Target::Area(lit) => match lit.base10_parse::<u32>() {
+            Ok(radius) if radius > 0 => Ok(quote! {
+                ::rust_101::skills::ability::Targeting::Area { radius: #radius }
+            }),
+            _ => Err(syn::Error::new_spanned(
+                lit,
+                "the radius has to be a whole number above zero",
+            )),
+        },
+    }
+}
+
+// the `.effect(...)` calls of one element, scaled by the power of the spell
+fn effects(base: Base, power: u32) -> TokenStream {
+    let status = |status: TokenStream, turns: u32| {
+        quote! {
+            .effect(::rust_101::skills::ability::Effect::ApplyStatus {
+                status: #status,
+                turns: #turns,
+            })
+        }
+    };
+    match base {
+        Base::Damage(damage) => {
+            let damage = damage * power;
+            quote!(.effect(::rust_101::skills::ability::Effect::Damage(#damage)))
+        }
+        Base::Heal(heal) => {
+            let heal = heal * power;
+            quote!(.effect(::rust_101::skills::ability::Effect::Heal(#heal)))
+        }
+        Base::Stun { damage, turns } => {
+            let damage = damage * power;
+            let stun = status(quote!(::rust_101::combat::status::Status::Stunned), turns);
+            quote!(.effect(::rust_101::skills::ability::Effect::Damage(#damage)) #stun)
+        }
+        Base::Poison { damage, turns } => {
+            let damage = damage * power;
+            status(
+                quote!(::rust_101::combat::status::Status::Poisoned { damage: #damage }),
+                turns,
+            )
+        }
+        Base::Regenerate { heal, turns } => {
+            let heal = heal * power;
+            status(
+                quote!(::rust_101::combat::status::Status::Regenerating { heal: #heal }),
+                turns,
+            )
+        }
+    }
+}
+
+fn capitalized(name: &str) -> String {
+    let mut chars = name.chars();
+    match chars.next() {
+        Some(first) => first.to_uppercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
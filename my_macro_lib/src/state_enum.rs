@@ -0,0 +1,397 @@
+use proc_macro2::TokenStream;
+use quote::{format_ident, quote};
+use syn::{Data, DeriveInput, Fields, Ident, LitStr, Type, Variant};
+
+// One part of a variant's format, the same as my_macro_runtime::enums::Piece
+// but with the field the placeholder stands for
+enum Piece {
+    Text(String),
+    Field(usize),
+}
+
+// A variant's #[display("...")] after reading it
+struct Format {
+    // the format string with every placeholder renamed to the binding of its field
+    display: LitStr,
+    pieces: Vec<Piece>,
+    // the fields the format shows, in field order
+    used: Vec<usize>,
+}
+
+pub fn expand(input: DeriveInput) -> TokenStream {
+    match derive(&input) {
+        Ok(tokens) => tokens,
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn derive(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let name = &input.ident;
+    let variants = match &input.data {
+        Data::Enum(data) => &data.variants,
+        _ => {
+            return Err(syn::Error::new_spanned(
+                name,
+                "#[derive(StateEnum)] only works on enums",
+            ))
+        }
+    };
+
+    let mut names = Vec::new();
+    let mut display_arms = Vec::new();
+    let mut parsers = Vec::new();
+    let mut defaults = Vec::new();
+    for variant in variants {
+        let format = format(variant)?;
+        names.push(variant.ident.to_string());
+        display_arms.push(display_arm(variant, &format));
+        parsers.extend(parser(variant, &format));
+        defaults.push(default_value(variant));
+    }
+    let name_arms = variants.iter().map(|variant| {
+        let ident = &variant.ident;
+        let text = ident.to_string();
+        quote!(Self::#ident { .. } => #text)
+    });
+
+    let enum_name = name.to_string();
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+    Ok(quote! {
+        impl #impl_generics #name #ty_generics #where_clause {
+            pub const VARIANTS: &'static [&'static str] = &[#(#names),*];
+
+            pub fn variant_name(&self) -> &'static str {
+                match self {
+                    #(#name_arms,)*
+                }
+            }
+
+            // every variant once, in declaration order, fields at their default
+            pub fn iter() -> ::std::vec::IntoIter<Self> {
+                ::std::vec![#(#defaults),*].into_iter()
+            }
+        }
+
+        impl #impl_generics ::std::fmt::Display for #name #ty_generics #where_clause {
+            fn fmt(&self, f: &mut ::std::fmt::Formatter) -> ::std::fmt::Result {
+                match self {
+                    #(#display_arms,)*
+                }
+            }
+        }
+
+        impl #impl_generics ::std::str::FromStr for #name #ty_generics #where_clause {
+            type Err = ::my_macro_runtime::enums::ParseVariantError;
+
+            fn from_str(text: &str) -> ::std::result::Result<Self, Self::Err> {
+                #(#parsers)*
+                ::std::result::Result::Err(::my_macro_runtime::enums::ParseVariantError {
+                    enum_name: #enum_name,
+                    input: text.to_string(),
+                    variants: Self::VARIANTS,
+                })
+            }
+        }
+    })
+}
+
+// The variant's #[display("...")], or its name followed by its fields, e.g. "Collecting {0}"
+fn format(variant: &Variant) -> syn::Result<Format> {
+    let mut attrs = variant
+        .attrs
+        .iter()
+        .filter(|attr| attr.path.is_ident("display"));
+    let format = match attrs.next() {
+        Some(attr) => attr.parse_args::<LitStr>().map_err(|_| {
+            syn::Error::new_spanned(
+                attr,
+                "expected a format string, e.g. #[display(\"Collecting {0}\")]",
+            )
+        })?,
+        None => {
+            let mut text = variant.ident.to_string();
+            for (index, field) in variant.fields.iter().enumerate() {
+                match &field.ident {
+                    Some(ident) => text.push_str(&format!(" {{{}}}", ident)),
+                    None => text.push_str(&format!(" {{{}}}", index)),
+                }
+            }
+            LitStr::new(&text, variant.ident.span())
+        }
+    };
+    if let Some(attr) = attrs.next() {
+        return Err(syn::Error::new_spanned(
+            attr,
+            "a variant can only have one #[display]",
+        ));
+    }
+    read_format(variant, &format)
+}
+
+fn read_format(variant: &Variant, format: &LitStr) -> syn::Result<Format> {
+    let source = format.value();
+    let mut display = String::new();
+    let mut pieces = Vec::new();
+    let mut text = String::new();
+    let mut used = Vec::new();
+    let mut next_position = 0;
+
+    let mut chars = source.chars().peekable();
+    while let Some(character) = chars.next() {
+        match character {
+            '{' if chars.peek() == Some(&'{') => {
+                chars.next();
+                display.push_str("{{");
+                text.push('{');
+            }
+            '}' if chars.peek() == Some(&'}') => {
+                chars.next();
+                display.push_str("}}");
+                text.push('}');
+            }
+            '{' => {
+                let mut placeholder = String::new();
+                loop {
+                    match chars.next() {
+                        Some('}') => break,
+                        Some(character) => placeholder.push(character),
+                        None => {
+                            return Err(syn::Error::new_spanned(
+                                format,
+                                "unclosed `{` in the format, write `{{` for a brace",
+                            ))
+                        }
+                    }
+                }
+                let (argument, spec) = match placeholder.split_once(':') {
+                    Some((argument, spec)) => (argument, Some(spec)),
+                    None => (placeholder.as_str(), None),
+                };
+                let field = field_of(variant, format, argument.trim(), &mut next_position)?;
+
+                display.push_str(&format!("{{value_{}", field));
+                if let Some(spec) = spec {
+                    display.push(':');
+                    display.push_str(spec);
+                }
+                display.push('}');
+                if !text.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut text)));
+                }
+                pieces.push(Piece::Field(field));
+                if !used.contains(&field) {
+                    used.push(field);
+                }
+            }
+            '}' => {
+                return Err(syn::Error::new_spanned(
+                    format,
+                    "unmatched `}` in the format, write `}}` for a brace",
+                ))
+            }
+            character => {
+                display.push(character);
+                text.push(character);
+            }
+        }
+    }
+    if !text.is_empty() {
+        pieces.push(Piece::Text(text));
+    }
+    used.sort_unstable();
+
+    Ok(Format {
+        display: LitStr::new(&display, format.span()),
+        pieces,
+        used,
+    })
+}
+
+// `{}` takes the next field, `{1}` a field by position, `{amount}` a field by name
+fn field_of(
+    variant: &Variant,
+    format: &LitStr,
+    argument: &str,
+    next_position: &mut usize,
+) -> syn::Result<usize> {
+    let field = if argument.is_empty() {
+        *next_position += 1;
+        Some(*next_position - 1)
+    } else if let Ok(position) = argument.parse::<usize>() {
+        Some(position)
+    } else {
+        variant
+            .fields
+            .iter()
+            .position(|field| matches!(&field.ident, Some(ident) if ident == argument))
+    };
+
+    match field {
+        Some(field) if field < variant.fields.len() => Ok(field),
+        _ => Err(syn::Error::new_spanned(
+            format,
+            format!(
+                "`{}` has no field `{{{}}}`, it has {}",
+                variant.ident,
+                argument,
+                match &variant.fields {
+                    Fields::Named(fields) => fields
+                        .named
+                        .iter()
+                        .map(|field| format!("`{}`", field.ident.as_ref().unwrap()))
+                        .collect::<Vec<_>>()
+                        .join(", "),
+                    Fields::Unnamed(fields) => format!("{} field(s)", fields.unnamed.len()),
+                    Fields::Unit => "no fields".to_string(),
+                }
+            ),
+        )),
+    }
+}
+
+fn binding(field: usize) -> Ident {
+    format_ident!("value_{}", field)
+}
+
+// Self::Variant(value_0, _) or Self::Variant { amount: value_0, .. } binding only what `used` needs
+fn pattern(variant: &Variant, used: &[usize]) -> TokenStream {
+    let ident = &variant.ident;
+    match &variant.fields {
+        Fields::Unit => quote!(Self::#ident),
+        Fields::Unnamed(fields) => {
+            let bindings = (0..fields.unnamed.len()).map(|field| {
+                if used.contains(&field) {
+                    let binding = binding(field);
+                    quote!(#binding)
+                } else {
+                    quote!(_)
+                }
+            });
+            quote!(Self::#ident(#(#bindings),*))
+        }
+        Fields::Named(fields) => {
+            let bindings = used.iter().map(|&field| {
+                let name = fields.named[field].ident.as_ref().unwrap();
+                let binding = binding(field);
+                quote!(#name: #binding)
+            });
+            quote!(Self::#ident { #(#bindings,)* .. })
+        }
+    }
+}
+
+// Padded like a &str, so `{:>12}` lines the states up
+fn display_arm(variant: &Variant, format: &Format) -> TokenStream {
+    let pattern = pattern(variant, &format.used);
+    if format.used.is_empty() {
+        let text: String = format
+            .pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.as_str(),
+                Piece::Field(_) => "",
+            })
+            .collect();
+        return quote!(#pattern => f.pad(#text));
+    }
+
+    let display = &format.display;
+    let arguments = format.used.iter().map(|&field| {
+        let binding = binding(field);
+        quote!(#binding = #binding)
+    });
+    quote!(#pattern => f.pad(&::std::format!(#display, #(#arguments),*)))
+}
+
+// Reads the variant back from what Display wrote. A variant whose format leaves out
+// one of its fields, or puts two of them side by side, can not be read back and is skipped.
+fn parser(variant: &Variant, format: &Format) -> Option<TokenStream> {
+    let ident = &variant.ident;
+    if variant.fields.is_empty() {
+        let text: String = format
+            .pieces
+            .iter()
+            .map(|piece| match piece {
+                Piece::Text(text) => text.as_str(),
+                Piece::Field(_) => "",
+            })
+            .collect();
+        return Some(quote! {
+            if text == #text {
+                return ::std::result::Result::Ok(Self::#ident);
+            }
+        });
+    }
+
+    let side_by_side = format
+        .pieces
+        .windows(2)
+        .any(|pair| matches!(pair, [Piece::Field(_), Piece::Field(_)]));
+    if format.used.len() != variant.fields.len() || side_by_side {
+        return None;
+    }
+
+    let pieces = format.pieces.iter().map(|piece| match piece {
+        Piece::Text(text) => quote!(::my_macro_runtime::enums::Piece::Text(#text)),
+        Piece::Field(_) => quote!(::my_macro_runtime::enums::Piece::Field),
+    });
+    // where each field's first placeholder is among the placeholders
+    let placeholders: Vec<usize> = format
+        .pieces
+        .iter()
+        .filter_map(|piece| match piece {
+            Piece::Field(field) => Some(*field),
+            Piece::Text(_) => None,
+        })
+        .collect();
+    let bindings: Vec<Ident> = (0..variant.fields.len()).map(binding).collect();
+    let parsed = variant.fields.iter().enumerate().map(|(field, declared)| {
+        let slot = placeholders
+            .iter()
+            .position(|&placeholder| placeholder == field)
+            .unwrap();
+        let ty: &Type = &declared.ty;
+        quote!(fields[#slot].parse::<#ty>())
+    });
+    let value = match &variant.fields {
+        Fields::Named(fields) => {
+            let names = fields
+                .named
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap());
+            quote!(Self::#ident { #(#names: #bindings),* })
+        }
+        _ => quote!(Self::#ident(#(#bindings),*)),
+    };
+
+    Some(quote! {
+        if let ::std::option::Option::Some(fields) =
+            ::my_macro_runtime::enums::split(&[#(#pieces),*], text)
+        {
+            if let (#(::std::result::Result::Ok(#bindings),)*) = (#(#parsed,)*) {
+                return ::std::result::Result::Ok(#value);
+            }
+        }
+    })
+}
+
+fn default_value(variant: &Variant) -> TokenStream {
+    let ident = &variant.ident;
+    match &variant.fields {
+        Fields::Unit => quote!(Self::#ident),
+        Fields::Unnamed(fields) => {
+            let values = fields
+                .unnamed
+                .iter()
+                .map(|_| quote!(::std::default::Default::default()));
+            quote!(Self::#ident(#(#values),*))
+        }
+        Fields::Named(fields) => {
+            let names = fields
+                .named
+                .iter()
+                .map(|field| field.ident.as_ref().unwrap());
+            quote!(Self::#ident { #(#names: ::std::default::Default::default()),* })
+        }
+    }
+}
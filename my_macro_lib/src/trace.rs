@@ -0,0 +1,173 @@
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::{quote, ToTokens};
+use syn::{AttributeArgs, FnArg, ItemFn, Pat, ReturnType};
+
+use crate::compute_time::body::wrap_body;
+use crate::compute_time::options::{choice, name_values, string, LEVELS};
+use crate::retry::returns_result;
+
+const SINKS: &[(&str, &str)] = &[("stdout", "Stdout"), ("stderr", "Stderr"), ("log", "Log")];
+
+// The arguments of #[trace(sink = "log", level = "debug")], both optional
+struct Options {
+    sink: Option<Ident>,
+    level: Option<Ident>,
+}
+
+impl Options {
+    fn parse(args: AttributeArgs) -> syn::Result<Self> {
+        let mut options = Options {
+            sink: None,
+            level: None,
+        };
+
+        for (key, lit) in name_values(
+            args,
+            &["sink", "level"],
+            "expected `name = \"value\"`, e.g. `sink = \"log\"`",
+        )? {
+            match key.as_str() {
+                "sink" => options.sink = Some(choice(&string(&lit)?, SINKS)?),
+                "level" => options.level = Some(choice(&string(&lit)?, LEVELS)?),
+                _ => unreachable!("name_values only lets the names above through"),
+            }
+        }
+
+        Ok(options)
+    }
+}
+
+// How an argument shows up on entry
+enum Shown {
+    Value,
+    Redacted,
+    Skipped,
+}
+
+// Reads and removes the #[trace(skip)] or #[trace(redact)] of an argument
+fn shown(arg: &mut syn::PatType) -> syn::Result<Shown> {
+    let mut shown = Shown::Value;
+    let mut marked = false;
+    let mut error = None;
+
+    arg.attrs.retain(|attr| {
+        if !attr.path.is_ident("trace") {
+            return true;
+        }
+        let result = attr.parse_args::<Ident>().and_then(|how| {
+            if marked {
+                return Err(syn::Error::new_spanned(
+                    &how,
+                    "an argument takes only one #[trace(...)]",
+                ));
+            }
+            marked = true;
+            match how.to_string().as_str() {
+                "skip" => Ok(Shown::Skipped),
+                "redact" => Ok(Shown::Redacted),
+                _ => Err(syn::Error::new_spanned(
+                    &how,
+                    "expected #[trace(skip)] or #[trace(redact)]",
+                )),
+            }
+        });
+        match result {
+            Ok(how) => shown = how,
+            Err(err) => {
+                error.get_or_insert(err);
+            }
+        }
+        false
+    });
+
+    match error {
+        Some(error) => Err(error),
+        None => Ok(shown),
+    }
+}
+
+pub fn expand(args: AttributeArgs, item: TokenStream) -> TokenStream {
+    let options = match Options::parse(args) {
+        Ok(options) => options,
+        Err(error) => return error.to_compile_error(),
+    };
+    let item_fn = match syn::parse2::<ItemFn>(item.clone()) {
+        Ok(item_fn) => item_fn,
+        Err(_) => {
+            return syn::Error::new_spanned(item, "#[trace] only works on functions")
+                .to_compile_error()
+        }
+    };
+
+    match trace(&options, item_fn) {
+        Ok(item_fn) => item_fn.into_token_stream(),
+        Err(error) => error.to_compile_error(),
+    }
+}
+
+fn trace(options: &Options, mut item_fn: ItemFn) -> syn::Result<ItemFn> {
+    // self is left out, the label already tells which method it is
+    let mut args = Vec::new();
+    for input in item_fn.sig.inputs.iter_mut() {
+        let arg = match input {
+            FnArg::Typed(arg) => arg,
+            FnArg::Receiver(_) => continue,
+        };
+        let shown = shown(arg)?;
+        let name = match (&*arg.pat, &shown) {
+            (_, Shown::Skipped) => continue,
+            (Pat::Ident(pat), _) => pat.ident.clone(),
+            (other, _) => {
+                return Err(syn::Error::new_spanned(
+                    other,
+                    "#[trace] shows arguments by name, mark this one #[trace(skip)]",
+                ))
+            }
+        };
+        let label = name.to_string();
+        args.push(match shown {
+            Shown::Redacted => quote!(::my_macro_runtime::trace::Arg::Redacted(#label)),
+            _ => quote!(::my_macro_runtime::trace::Arg::Value(#label, &#name)),
+        });
+    }
+
+    let sig = &item_fn.sig;
+    let label = sig.ident.to_string();
+    let sink = options
+        .sink
+        .clone()
+        .unwrap_or_else(|| Ident::new("Stdout", Span::call_site()));
+    let level = options
+        .level
+        .clone()
+        .unwrap_or_else(|| Ident::new("Debug", Span::call_site()));
+
+    // the body's `return`s come back here to be traced
+    let body = wrap_body(sig, &item_fn.block, true);
+    let exit = match &sig.output {
+        ReturnType::Default => quote!(trace_span.done()),
+        ReturnType::Type(_, ty) if returns_result(ty) => quote! {
+            match &trace_value {
+                ::std::result::Result::Ok(value) => trace_span.ok(value),
+                ::std::result::Result::Err(error) => trace_span.err(error),
+            }
+        },
+        ReturnType::Type(..) => quote!(trace_span.returned(&trace_value)),
+    };
+
+    item_fn.block = Box::new(syn::parse_quote! {{
+        let trace_span = ::my_macro_runtime::trace::Span::enter(
+            ::my_macro_runtime::trace::TraceOptions {
+                label: #label,
+                module: module_path!(),
+                sink: ::my_macro_runtime::trace::TraceSink::#sink,
+                level: ::my_macro_runtime::timing::sink::Level::#level,
+            },
+            &[#(#args),*],
+        );
+        let trace_value = #body;
+        #exit;
+        trace_value
+    }});
+    Ok(item_fn)
+}
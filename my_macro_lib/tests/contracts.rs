@@ -0,0 +1,76 @@
+// The checks are only there with the `contracts` feature, run the tests with and without
+// --features contracts to cover both.
+
+use my_macro_lib::{ensures, invariant, requires};
+
+#[requires(amount > 0)]
+fn withdraw(amount: u32) -> u32 {
+    amount
+}
+
+#[ensures(|ret| *ret < 10)]
+fn roll(value: u32) -> u32 {
+    value
+}
+
+struct Hero {
+    health: u32,
+    max_health: u32,
+}
+
+impl Hero {
+    #[invariant(self.health <= self.max_health)]
+    fn heal(&mut self, amount: u32) {
+        self.health += amount;
+    }
+}
+
+#[test]
+fn contracts_that_hold_change_nothing() {
+    assert_eq!(withdraw(5), 5);
+    assert_eq!(roll(3), 3);
+    let mut hero = Hero {
+        health: 5,
+        max_health: 10,
+    };
+    hero.heal(5);
+    assert_eq!(hero.health, 10);
+}
+
+#[cfg(feature = "contracts")]
+#[test]
+#[should_panic(expected = "precondition of withdraw failed: amount > 0")]
+fn a_broken_precondition_panics() {
+    withdraw(0);
+}
+
+#[cfg(feature = "contracts")]
+#[test]
+#[should_panic(expected = "postcondition of roll failed")]
+fn a_broken_postcondition_panics() {
+    roll(12);
+}
+
+#[cfg(feature = "contracts")]
+#[test]
+#[should_panic(expected = "invariant on exit of heal failed")]
+fn a_broken_invariant_panics() {
+    let mut hero = Hero {
+        health: 5,
+        max_health: 10,
+    };
+    hero.heal(6);
+}
+
+#[cfg(not(feature = "contracts"))]
+#[test]
+fn broken_contracts_go_unchecked_without_the_feature() {
+    assert_eq!(withdraw(0), 0);
+    assert_eq!(roll(12), 12);
+    let mut hero = Hero {
+        health: 5,
+        max_health: 10,
+    };
+    hero.heal(6);
+    assert_eq!(hero.health, 11);
+}
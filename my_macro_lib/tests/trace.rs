@@ -0,0 +1,99 @@
+// What #[trace] writes, read back through the log sink
+
+use std::panic;
+use std::sync::Mutex;
+
+use log::{LevelFilter, Log, Metadata, Record};
+use my_macro_lib::trace;
+
+static LINES: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+struct Collector;
+
+impl Log for Collector {
+    fn enabled(&self, _: &Metadata) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record) {
+        LINES.lock().unwrap().push(record.args().to_string());
+    }
+
+    fn flush(&self) {}
+}
+
+fn take_lines() -> Vec<String> {
+    std::mem::take(&mut *LINES.lock().unwrap())
+}
+
+#[trace(sink = "log")]
+fn open_treasure(
+    #[trace(redact)] key: &str,
+    #[trace(skip)] _map: &[u8],
+    gold: u32,
+) -> Result<u32, String> {
+    if key == "gold key" {
+        Ok(gold)
+    } else {
+        Err("wrong key".to_string())
+    }
+}
+
+#[trace(sink = "log")]
+fn double(value: u32) -> u32 {
+    value * 2
+}
+
+#[trace(sink = "log")]
+fn quadruple(value: u32) -> u32 {
+    double(double(value))
+}
+
+#[trace(sink = "log")]
+fn spring_trap() {
+    panic!("the chest was trapped");
+}
+
+// one test, the logger and its lines are shared by the whole binary
+#[test]
+fn traced_calls_are_written_with_their_arguments_and_how_they_returned() {
+    log::set_logger(&Collector).unwrap();
+    log::set_max_level(LevelFilter::Trace);
+
+    assert_eq!(open_treasure("gold key", &[1, 2], 10), Ok(10));
+    assert!(open_treasure("rusty key", &[], 10).is_err());
+    assert_eq!(
+        take_lines(),
+        vec![
+            "→ open_treasure(key = ***, gold = 10)",
+            "← open_treasure: Ok(10)",
+            "→ open_treasure(key = ***, gold = 10)",
+            "← open_treasure failed: \"wrong key\"",
+        ]
+    );
+
+    assert_eq!(quadruple(3), 12);
+    assert_eq!(
+        take_lines(),
+        vec![
+            "→ quadruple(value = 3)",
+            "  → double(value = 3)",
+            "  ← double: 6",
+            "  → double(value = 6)",
+            "  ← double: 12",
+            "← quadruple: 12",
+        ]
+    );
+
+    assert!(panic::catch_unwind(spring_trap).is_err());
+    assert_eq!(double(1), 2);
+    assert_eq!(
+        take_lines(),
+        vec![
+            "→ spring_trap()",
+            "← spring_trap panicked",
+            "→ double(value = 1)",
+            "← double: 2",
+        ]
+    );
+}
@@ -0,0 +1,39 @@
+// The errors the macros point out, each case next to the compiler output it has to give.
+// TRYBUILD=overwrite cargo test writes the .stderr files anew after a message changed.
+
+#[test]
+fn compute_time() {
+    let cases = trybuild::TestCases::new();
+    cases.pass("tests/ui/compute_time/pass/*.rs");
+    cases.compile_fail("tests/ui/compute_time/fail/*.rs");
+}
+
+#[test]
+fn character() {
+    trybuild::TestCases::new().compile_fail("tests/ui/character/*.rs");
+}
+
+#[test]
+fn state_enum() {
+    trybuild::TestCases::new().compile_fail("tests/ui/state_enum/*.rs");
+}
+
+#[test]
+fn items() {
+    trybuild::TestCases::new().compile_fail("tests/ui/items/*.rs");
+}
+
+#[test]
+fn trace() {
+    trybuild::TestCases::new().compile_fail("tests/ui/trace/*.rs");
+}
+
+#[test]
+fn contracts() {
+    trybuild::TestCases::new().compile_fail("tests/ui/contracts/*.rs");
+}
+
+#[test]
+fn spell() {
+    trybuild::TestCases::new().compile_fail("tests/ui/spell/*.rs");
+}
@@ -0,0 +1,10 @@
+use my_macro_lib::Character;
+
+#[derive(Character)]
+#[character(class = "Warrior")]
+struct Warrior {
+    #[character(health)]
+    health: u8,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use my_macro_lib::Character;
+
+#[derive(Character)]
+enum Hero {
+    Warrior,
+    Mage,
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use my_macro_lib::Character;
+
+#[derive(Character)]
+struct Warrior {
+    #[character(strength, max = 20)]
+    strength: u8,
+}
+
+fn main() {}
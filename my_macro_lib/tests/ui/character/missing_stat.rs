@@ -0,0 +1,11 @@
+use my_macro_lib::Character;
+
+#[derive(Character)]
+struct Warrior {
+    #[character(health, max = 100)]
+    health: u8,
+    #[character(strength)]
+    strength: u8,
+}
+
+fn main() {}
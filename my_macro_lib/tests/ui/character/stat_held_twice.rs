@@ -0,0 +1,11 @@
+use my_macro_lib::Character;
+
+#[derive(Character)]
+struct Warrior {
+    #[character(health)]
+    health: u8,
+    #[character(health)]
+    stamina: u8,
+}
+
+fn main() {}
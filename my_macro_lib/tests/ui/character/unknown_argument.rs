@@ -0,0 +1,9 @@
+use my_macro_lib::Character;
+
+#[derive(Character)]
+struct Warrior {
+    #[character(agility)]
+    agility: u8,
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use my_macro_lib::compute_time;
+
+#[compute_time(threshold = "soon")]
+fn tick() {}
+
+fn main() {}
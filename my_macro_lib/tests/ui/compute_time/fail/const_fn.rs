@@ -0,0 +1,8 @@
+use my_macro_lib::compute_time;
+
+#[compute_time]
+const fn max_health() -> u32 {
+    100
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+use my_macro_lib::compute_time;
+
+struct Hero;
+
+#[compute_time]
+impl Hero {
+    fn attack(&self) -> u32 {
+        5
+    }
+
+    const fn max_health() -> u32 {
+        100
+    }
+}
+
+fn main() {}
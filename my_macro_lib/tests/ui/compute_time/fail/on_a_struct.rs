@@ -0,0 +1,8 @@
+use my_macro_lib::compute_time;
+
+#[compute_time]
+struct Hero {
+    health: u32,
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use my_macro_lib::compute_time;
+
+trait Craft {
+    #[compute_time]
+    fn craft(&self) -> u32;
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use my_macro_lib::compute_time;
+
+#[compute_time(sink = "printer")]
+fn tick() {}
+
+fn main() {}
@@ -0,0 +1,32 @@
+use std::future::Future;
+use std::pin::pin;
+use std::task::{Context, Poll, Waker};
+
+use my_macro_lib::compute_time;
+
+#[compute_time(sink = "none")]
+async fn travel(legs: u32) -> u32 {
+    let mut walked = 0;
+    for _ in 0..legs {
+        walked += leg().await;
+    }
+    walked
+}
+
+async fn leg() -> u32 {
+    3
+}
+
+fn block_on<F: Future>(future: F) -> F::Output {
+    let mut future = pin!(future);
+    let mut context = Context::from_waker(Waker::noop());
+    loop {
+        if let Poll::Ready(value) = future.as_mut().poll(&mut context) {
+            return value;
+        }
+    }
+}
+
+fn main() {
+    assert_eq!(block_on(travel(4)), 12);
+}
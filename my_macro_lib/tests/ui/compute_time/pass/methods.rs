@@ -0,0 +1,55 @@
+use my_macro_lib::compute_time;
+
+struct Blacksmith {
+    forged: u32,
+}
+
+impl Blacksmith {
+    #[compute_time(sink = "none")]
+    fn forged(&self) -> u32 {
+        self.forged
+    }
+
+    #[compute_time(sink = "none")]
+    fn forge(&mut self) -> &mut Self {
+        self.forged += 1;
+        self
+    }
+}
+
+// every method of the block is timed as Blacksmith::method
+#[compute_time(sink = "none")]
+impl Blacksmith {
+    fn new() -> Self {
+        Blacksmith { forged: 0 }
+    }
+
+    fn reset(&mut self) {
+        self.forged = 0;
+    }
+}
+
+trait Craft {
+    fn craft(&mut self) -> u32;
+
+    #[compute_time(sink = "none")]
+    fn craft_twice(&mut self) -> u32 {
+        self.craft() + self.craft()
+    }
+}
+
+impl Craft for Blacksmith {
+    #[compute_time(sink = "none")]
+    fn craft(&mut self) -> u32 {
+        self.forge().forged()
+    }
+}
+
+fn main() {
+    let mut smith = Blacksmith::new();
+    smith.forge().forge();
+    assert_eq!(smith.forged(), 2);
+    assert_eq!(smith.craft_twice(), 7);
+    smith.reset();
+    assert_eq!(smith.forged(), 0);
+}
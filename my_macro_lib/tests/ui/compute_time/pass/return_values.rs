@@ -0,0 +1,41 @@
+use my_macro_lib::compute_time;
+
+#[derive(Debug, PartialEq)]
+struct ChestError;
+
+#[compute_time(sink = "none")]
+fn loot(gold: u32) -> u32 {
+    gold * 2
+}
+
+#[compute_time(sink = "none")]
+fn open(key: &str) -> Result<u32, ChestError> {
+    let gold = unlock(key)?;
+    Ok(gold + 1)
+}
+
+fn unlock(key: &str) -> Result<u32, ChestError> {
+    if key == "gold" {
+        Ok(10)
+    } else {
+        Err(ChestError)
+    }
+}
+
+#[compute_time(sink = "none")]
+fn first_even(values: &[u32]) -> Option<u32> {
+    for value in values {
+        if value % 2 == 0 {
+            return Some(*value);
+        }
+    }
+    None
+}
+
+fn main() {
+    assert_eq!(loot(4), 8);
+    assert_eq!(open("gold"), Ok(11));
+    assert_eq!(open("rust"), Err(ChestError));
+    assert_eq!(first_even(&[1, 3, 4, 6]), Some(4));
+    assert_eq!(first_even(&[1]), None);
+}
@@ -0,0 +1,8 @@
+use my_macro_lib::requires;
+
+#[requires]
+fn withdraw(amount: u32) -> u32 {
+    amount
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use my_macro_lib::ensures;
+
+#[ensures(|ret, other| *ret == other)]
+fn mul(a: i32, b: i32) -> i32 {
+    a * b
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use my_macro_lib::ensures;
+
+#[ensures(a * b)]
+fn mul(a: i32, b: i32) -> i32 {
+    a * b
+}
+
+fn main() {}
@@ -0,0 +1,9 @@
+use my_macro_lib::invariant;
+
+#[invariant(self.health <= self.max_health)]
+struct Crabby {
+    health: u8,
+    max_health: u8,
+}
+
+fn main() {}
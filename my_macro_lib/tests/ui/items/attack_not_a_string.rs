@@ -0,0 +1,7 @@
+use my_macro_lib::items;
+
+items! {
+    Weapon Bow { damage: 8, kind: Piercing, attack: BowShot }
+}
+
+fn main() {}
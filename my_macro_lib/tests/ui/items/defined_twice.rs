@@ -0,0 +1,8 @@
+use my_macro_lib::items;
+
+items! {
+    Shield WoodShield { defense: 5 }
+    Shield WoodShield { defense: 6 }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use my_macro_lib::items;
+
+items! {
+    Shield WoodShield { defense: 5, defense: 6 }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use my_macro_lib::items;
+
+items! {
+    Weapon Sword { damage: 10, kind: Slashing, weight: 4 }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use my_macro_lib::items;
+
+items! {
+    Helmet IronHelmet { defense: 3 }
+}
+
+fn main() {}
@@ -0,0 +1,7 @@
+use my_macro_lib::items;
+
+items! {
+    Weapon Sword { damage: 0, kind: Slashing }
+}
+
+fn main() {}
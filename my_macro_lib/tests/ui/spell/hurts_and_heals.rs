@@ -0,0 +1,5 @@
+use my_macro_lib::spell;
+
+fn main() {
+    let _ = spell!(fire + water);
+}
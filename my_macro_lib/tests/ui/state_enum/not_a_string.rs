@@ -0,0 +1,9 @@
+use my_macro_lib::StateEnum;
+
+#[derive(StateEnum)]
+enum CrabbyState {
+    #[display(Resting)]
+    Resting,
+}
+
+fn main() {}
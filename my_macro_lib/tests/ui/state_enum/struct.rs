@@ -0,0 +1,8 @@
+use my_macro_lib::StateEnum;
+
+#[derive(StateEnum)]
+struct Crabby {
+    health: u8,
+}
+
+fn main() {}
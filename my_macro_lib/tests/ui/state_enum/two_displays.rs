@@ -0,0 +1,10 @@
+use my_macro_lib::StateEnum;
+
+#[derive(StateEnum)]
+enum CrabbyState {
+    #[display("Resting")]
+    #[display("Sleeping")]
+    Resting,
+}
+
+fn main() {}
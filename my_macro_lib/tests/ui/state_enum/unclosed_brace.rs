@@ -0,0 +1,9 @@
+use my_macro_lib::StateEnum;
+
+#[derive(StateEnum)]
+enum CrabbyState {
+    #[display("Collecting {0")]
+    Collecting(u32),
+}
+
+fn main() {}
@@ -0,0 +1,10 @@
+use my_macro_lib::StateEnum;
+
+#[derive(StateEnum)]
+enum CrabbyState {
+    Resting,
+    #[display("Guarding {place} for {days} days")]
+    Guarding { place: String, turns: u8 },
+}
+
+fn main() {}
@@ -0,0 +1,6 @@
+use my_macro_lib::trace;
+
+#[trace(sink = "stderr", sink = "log")]
+fn tick() {}
+
+fn main() {}
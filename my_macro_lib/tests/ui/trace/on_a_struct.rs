@@ -0,0 +1,8 @@
+use my_macro_lib::trace;
+
+#[trace]
+struct Hero {
+    health: u32,
+}
+
+fn main() {}
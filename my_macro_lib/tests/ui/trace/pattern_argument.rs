@@ -0,0 +1,8 @@
+use my_macro_lib::trace;
+
+#[trace]
+fn distance((x, y): (i32, i32)) -> i32 {
+    x.abs() + y.abs()
+}
+
+fn main() {}
@@ -0,0 +1,8 @@
+use my_macro_lib::trace;
+
+#[trace]
+fn open(#[trace(skip)] #[trace(redact)] key: &str) -> bool {
+    key == "gold"
+}
+
+fn main() {}
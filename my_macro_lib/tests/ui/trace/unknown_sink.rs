@@ -0,0 +1,6 @@
+use my_macro_lib::trace;
+
+#[trace(sink = "printer")]
+fn tick() {}
+
+fn main() {}
@@ -0,0 +1,98 @@
+use std::fmt;
+
+// One part of a variant's display format, as seen when parsing it back
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Piece {
+    // written as is, e.g. "Collecting "
+    Text(&'static str),
+    // a placeholder, replaced by one of the variant's fields
+    Field,
+}
+
+// Cuts `input` along the text pieces of a format and returns what stands where
+// each placeholder is, trimmed, or None when the text pieces do not match.
+// A placeholder takes everything up to the next text piece, or to the end.
+pub fn split<'a>(pieces: &[Piece], input: &'a str) -> Option<Vec<&'a str>> {
+    let mut rest = input;
+    let mut fields = Vec::new();
+    let mut pieces = pieces.iter().peekable();
+
+    while let Some(piece) = pieces.next() {
+        match piece {
+            Piece::Text(text) => rest = rest.strip_prefix(text)?,
+            Piece::Field => {
+                let end = match pieces.peek() {
+                    Some(Piece::Text(text)) => rest.find(text)?,
+                    _ => rest.len(),
+                };
+                fields.push(rest[..end].trim());
+                rest = &rest[end..];
+            }
+        }
+    }
+
+    if rest.is_empty() {
+        Some(fields)
+    } else {
+        None
+    }
+}
+
+// What `FromStr` of a #[derive(StateEnum)] enum returns for text that is none of its variants
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseVariantError {
+    pub enum_name: &'static str,
+    pub input: String,
+    pub variants: &'static [&'static str],
+}
+
+impl fmt::Display for ParseVariantError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "`{}` is not a {}, expected one of {}",
+            self.input,
+            self.enum_name,
+            self.variants.join(", ")
+        )
+    }
+}
+
+impl std::error::Error for ParseVariantError {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // "Guarding {place} for {turns} turns"
+    const GUARDING: &[Piece] = &[
+        Piece::Text("Guarding "),
+        Piece::Field,
+        Piece::Text(" for "),
+        Piece::Field,
+        Piece::Text(" turns"),
+    ];
+
+    #[test]
+    fn fields_are_cut_along_the_text_pieces() {
+        assert_eq!(
+            split(GUARDING, "Guarding the bridge for 3 turns"),
+            Some(vec!["the bridge", "3"])
+        );
+    }
+
+    #[test]
+    fn a_trailing_placeholder_takes_the_rest() {
+        let collecting = &[Piece::Text("Collecting "), Piece::Field];
+        assert_eq!(split(collecting, "Collecting  15 "), Some(vec!["15"]));
+        assert_eq!(split(collecting, "Collecting "), Some(vec![""]));
+    }
+
+    #[test]
+    fn text_that_does_not_match_the_format_is_none() {
+        assert_eq!(split(GUARDING, "Resting"), None);
+        assert_eq!(split(GUARDING, "Guarding the bridge"), None);
+        assert_eq!(split(GUARDING, "Guarding the bridge for 3 turns!"), None);
+        assert_eq!(split(&[Piece::Text("Resting")], "Resting"), Some(vec![]));
+    }
+}
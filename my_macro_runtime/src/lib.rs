@@ -0,0 +1,8 @@
+// Runtime support for the macros of my_macro_lib.
+// A proc-macro crate can only export macros, so everything the generated code
+// calls at runtime lives here.
+pub mod enums;
+pub mod memoize;
+pub mod retry;
+pub mod timing;
+pub mod trace;
@@ -0,0 +1,270 @@
+use std::cell::RefCell;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::{Mutex, OnceLock};
+use std::time::{Duration, Instant};
+
+#[derive(Debug)]
+struct Entry<V> {
+    value: V,
+    inserted: Instant,
+    // when it was last used, the lowest one is evicted first
+    stamp: u64,
+}
+
+// Keeps at most `capacity` results, dropping the least recently used one to make room.
+// With a ttl a result older than that counts as missing.
+#[derive(Debug)]
+pub struct LruCache<K, V> {
+    capacity: usize,
+    ttl: Option<Duration>,
+    entries: HashMap<K, Entry<V>>,
+    order: BTreeMap<u64, K>,
+    next_stamp: u64,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LruCache<K, V> {
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            capacity: capacity.max(1),
+            ttl,
+            entries: HashMap::new(),
+            order: BTreeMap::new(),
+            next_stamp: 0,
+        }
+    }
+
+    pub fn get(&mut self, key: &K) -> Option<V> {
+        let entry = self.entries.get_mut(key)?;
+        if let Some(ttl) = self.ttl {
+            if entry.inserted.elapsed() >= ttl {
+                let stamp = entry.stamp;
+                self.entries.remove(key);
+                self.order.remove(&stamp);
+                return None;
+            }
+        }
+
+        self.order.remove(&entry.stamp);
+        entry.stamp = self.next_stamp;
+        self.order.insert(self.next_stamp, key.clone());
+        self.next_stamp += 1;
+        Some(entry.value.clone())
+    }
+
+    pub fn insert(&mut self, key: K, value: V) {
+        if let Some(old) = self.entries.remove(&key) {
+            self.order.remove(&old.stamp);
+        }
+        while self.entries.len() >= self.capacity {
+            match self.order.pop_first() {
+                Some((_, oldest)) => self.entries.remove(&oldest),
+                None => break,
+            };
+        }
+
+        self.order.insert(self.next_stamp, key.clone());
+        self.entries.insert(
+            key,
+            Entry {
+                value,
+                inserted: Instant::now(),
+                stamp: self.next_stamp,
+            },
+        );
+        self.next_stamp += 1;
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    pub fn clear(&mut self) {
+        self.entries.clear();
+        self.order.clear();
+    }
+}
+
+// The cache of a #[memoize] function, one per thread, so no locking
+#[derive(Debug)]
+pub struct LocalCache<K, V> {
+    cache: RefCell<LruCache<K, V>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> LocalCache<K, V> {
+    pub fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            cache: RefCell::new(LruCache::new(capacity, ttl)),
+        }
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.cache.borrow_mut().get(key)
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.cache.borrow_mut().insert(key, value);
+    }
+}
+
+// The cache of a #[memoize(scope = "global")] function, shared by every thread.
+// The lock is not held while the function runs, so two threads asking for the same
+// missing result may both compute it.
+#[derive(Debug)]
+pub struct SharedCache<K, V> {
+    capacity: usize,
+    ttl: Option<Duration>,
+    cache: OnceLock<Mutex<LruCache<K, V>>>,
+}
+
+impl<K: Hash + Eq + Clone, V: Clone> SharedCache<K, V> {
+    pub const fn new(capacity: usize, ttl: Option<Duration>) -> Self {
+        Self {
+            capacity,
+            ttl,
+            cache: OnceLock::new(),
+        }
+    }
+
+    fn cache(&self) -> &Mutex<LruCache<K, V>> {
+        self.cache
+            .get_or_init(|| Mutex::new(LruCache::new(self.capacity, self.ttl)))
+    }
+
+    pub fn get(&self, key: &K) -> Option<V> {
+        self.cache().lock().unwrap().get(key)
+    }
+
+    pub fn insert(&self, key: K, value: V) {
+        self.cache().lock().unwrap().insert(key, value);
+    }
+}
+
+// How often one memoized function found its result in the cache,
+// the generated code keeps one in a static inside the function
+#[derive(Debug)]
+pub struct MemoSite {
+    label: &'static str,
+    module: &'static str,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    listed: AtomicBool,
+}
+
+impl MemoSite {
+    pub const fn new(label: &'static str, module: &'static str) -> Self {
+        Self {
+            label,
+            module,
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            listed: AtomicBool::new(false),
+        }
+    }
+
+    pub fn hit(&'static self) {
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        self.list();
+    }
+
+    pub fn miss(&'static self) {
+        self.misses.fetch_add(1, Ordering::Relaxed);
+        self.list();
+    }
+
+    fn list(&'static self) {
+        if !self.listed.swap(true, Ordering::Relaxed) {
+            sites().lock().unwrap().push(self);
+        }
+    }
+}
+
+fn sites() -> &'static Mutex<Vec<&'static MemoSite>> {
+    static SITES: Mutex<Vec<&'static MemoSite>> = Mutex::new(Vec::new());
+    &SITES
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MemoStats {
+    pub path: String,
+    pub hits: u64,
+    pub misses: u64,
+}
+
+// every memoized function called so far, sorted by path
+pub fn stats() -> Vec<MemoStats> {
+    let mut stats: Vec<MemoStats> = sites()
+        .lock()
+        .unwrap()
+        .iter()
+        .map(|site| MemoStats {
+            path: format!("{}::{}", site.module, site.label),
+            hits: site.hits.load(Ordering::Relaxed),
+            misses: site.misses.load(Ordering::Relaxed),
+        })
+        .collect();
+    stats.sort_by(|a, b| a.path.cmp(&b.path));
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn the_least_recently_used_result_goes_first() {
+        let mut cache = LruCache::new(2, None);
+        cache.insert("sword", 10);
+        cache.insert("bow", 8);
+        // reading the sword makes the bow the oldest
+        assert_eq!(cache.get(&"sword"), Some(10));
+
+        cache.insert("mace", 12);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"bow"), None);
+        assert_eq!(cache.get(&"sword"), Some(10));
+        assert_eq!(cache.get(&"mace"), Some(12));
+    }
+
+    #[test]
+    fn inserting_a_key_again_replaces_it_without_evicting() {
+        let mut cache = LruCache::new(2, None);
+        cache.insert("sword", 10);
+        cache.insert("bow", 8);
+
+        cache.insert("sword", 11);
+
+        assert_eq!(cache.len(), 2);
+        assert_eq!(cache.get(&"sword"), Some(11));
+        assert_eq!(cache.get(&"bow"), Some(8));
+    }
+
+    #[test]
+    fn a_result_older_than_the_ttl_is_missing() {
+        let mut expired = LruCache::new(4, Some(Duration::ZERO));
+        expired.insert("sword", 10);
+        assert_eq!(expired.get(&"sword"), None);
+        // the stale result is dropped, not just hidden
+        assert!(expired.is_empty());
+
+        let mut fresh = LruCache::new(4, Some(Duration::from_secs(60)));
+        fresh.insert("sword", 10);
+        assert_eq!(fresh.get(&"sword"), Some(10));
+    }
+
+    #[test]
+    fn a_capacity_of_zero_still_keeps_one_result() {
+        let mut cache = LruCache::new(0, None);
+        cache.insert("sword", 10);
+        cache.insert("bow", 8);
+
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&"bow"), Some(8));
+    }
+}
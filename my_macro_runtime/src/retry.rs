@@ -0,0 +1,156 @@
+use std::thread;
+use std::time::Duration;
+
+// How long to wait before the next attempt
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Backoff {
+    // right away
+    None,
+    // `delay` every time
+    Fixed,
+    // `delay`, then twice `delay`, then three times...
+    Linear,
+    // `delay`, then doubling every time
+    Exponential,
+}
+
+// What #[retry(...)] was configured with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RetryPolicy {
+    // attempts in total, the first one included
+    pub times: u32,
+    pub backoff: Backoff,
+    pub delay: Duration,
+    // no wait is ever longer than this
+    pub max_delay: Duration,
+}
+
+impl RetryPolicy {
+    // the wait after failed attempt number `attempt`, counting from 1
+    pub fn delay_after(&self, attempt: u32) -> Duration {
+        let delay = match self.backoff {
+            Backoff::None => Duration::ZERO,
+            Backoff::Fixed => self.delay,
+            Backoff::Linear => self.delay.saturating_mul(attempt),
+            Backoff::Exponential => {
+                let factor = 1u32.checked_shl(attempt - 1).unwrap_or(u32::MAX);
+                self.delay.saturating_mul(factor)
+            }
+        };
+        delay.min(self.max_delay)
+    }
+}
+
+// A failed attempt, as handed to the `on_attempt` hook
+#[derive(Debug)]
+pub struct Attempt<'a, E> {
+    pub function: &'static str,
+    // counting from 1
+    pub number: u32,
+    pub times: u32,
+    pub error: &'a E,
+    // None when this was the last attempt or the error is not worth retrying
+    pub retry_in: Option<Duration>,
+}
+
+// Runs `attempt` until it succeeds, fails with an error `retryable` turns down,
+// or the policy runs out of attempts. The last error is returned.
+pub fn run<T, E>(
+    function: &'static str,
+    policy: &RetryPolicy,
+    mut attempt: impl FnMut() -> Result<T, E>,
+    retryable: impl Fn(&E) -> bool,
+    on_attempt: impl Fn(&Attempt<E>),
+) -> Result<T, E> {
+    let times = policy.times.max(1);
+    let mut number = 1;
+    loop {
+        let error = match attempt() {
+            Ok(value) => return Ok(value),
+            Err(error) => error,
+        };
+
+        let retry_in = if number < times && retryable(&error) {
+            Some(policy.delay_after(number))
+        } else {
+            None
+        };
+        on_attempt(&Attempt {
+            function,
+            number,
+            times,
+            error: &error,
+            retry_in,
+        });
+
+        match retry_in {
+            Some(delay) => {
+                if !delay.is_zero() {
+                    thread::sleep(delay);
+                }
+                number += 1;
+            }
+            None => return Err(error),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    fn policy(backoff: Backoff) -> RetryPolicy {
+        RetryPolicy {
+            times: 10,
+            backoff,
+            delay: 5 * MS,
+            max_delay: 100 * MS,
+        }
+    }
+
+    fn delays(policy: &RetryPolicy) -> Vec<Duration> {
+        (1..=6).map(|attempt| policy.delay_after(attempt)).collect()
+    }
+
+    #[test]
+    fn each_backoff_grows_the_delay_its_own_way() {
+        assert_eq!(delays(&policy(Backoff::None)), vec![Duration::ZERO; 6]);
+        assert_eq!(delays(&policy(Backoff::Fixed)), vec![5 * MS; 6]);
+        assert_eq!(
+            delays(&policy(Backoff::Linear)),
+            vec![5 * MS, 10 * MS, 15 * MS, 20 * MS, 25 * MS, 30 * MS]
+        );
+        assert_eq!(
+            delays(&policy(Backoff::Exponential)),
+            vec![5 * MS, 10 * MS, 20 * MS, 40 * MS, 80 * MS, 100 * MS]
+        );
+    }
+
+    #[test]
+    fn a_late_attempt_waits_no_longer_than_the_max_delay() {
+        let exponential = policy(Backoff::Exponential);
+        assert_eq!(exponential.delay_after(40), 100 * MS);
+        assert_eq!(exponential.delay_after(u32::MAX), 100 * MS);
+        assert_eq!(policy(Backoff::Linear).delay_after(u32::MAX), 100 * MS);
+    }
+
+    #[test]
+    fn run_gives_up_on_an_error_not_worth_retrying() {
+        let mut attempts = 0;
+        let result: Result<(), &str> = run(
+            "open_gate",
+            &policy(Backoff::None),
+            || {
+                attempts += 1;
+                Err("locked")
+            },
+            |error| *error != "locked",
+            |_| {},
+        );
+
+        assert_eq!(result, Err("locked"));
+        assert_eq!(attempts, 1);
+    }
+}
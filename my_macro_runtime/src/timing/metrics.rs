@@ -0,0 +1,490 @@
+use std::collections::{BTreeMap, HashMap};
+use std::fmt::Write;
+use std::hash::{Hash, Hasher};
+use std::ptr;
+use std::sync::{Mutex, OnceLock};
+use std::time::Duration;
+
+use super::spans;
+use super::timer::CallSite;
+
+// Every power of two of nanoseconds is split into this many buckets,
+// so a percentile is off by at most 1/8 of its value
+const SUB_BUCKETS: u64 = 8;
+
+// Call durations of one function. Samples are counted in buckets instead of being kept,
+// so a function called millions of times costs no more memory than one called once.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Histogram {
+    count: u64,
+    total: Duration,
+    min: Duration,
+    max: Duration,
+    buckets: BTreeMap<u64, u64>,
+}
+
+impl Histogram {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn record(&mut self, elapsed: Duration) {
+        if self.count == 0 || elapsed < self.min {
+            self.min = elapsed;
+        }
+        if elapsed > self.max {
+            self.max = elapsed;
+        }
+        self.count += 1;
+        self.total += elapsed;
+        *self.buckets.entry(bucket_of(elapsed)).or_insert(0) += 1;
+    }
+
+    // adds the calls of `other`, as if they had been recorded here
+    pub fn merge(&mut self, other: &Histogram) {
+        if other.count == 0 {
+            return;
+        }
+        if self.count == 0 || other.min < self.min {
+            self.min = other.min;
+        }
+        self.max = self.max.max(other.max);
+        self.count += other.count;
+        self.total += other.total;
+        for (bucket, count) in other.buckets.iter() {
+            *self.buckets.entry(*bucket).or_insert(0) += count;
+        }
+    }
+
+    pub fn count(&self) -> u64 {
+        self.count
+    }
+
+    pub fn total(&self) -> Duration {
+        self.total
+    }
+
+    pub fn min(&self) -> Duration {
+        self.min
+    }
+
+    pub fn max(&self) -> Duration {
+        self.max
+    }
+
+    pub fn mean(&self) -> Duration {
+        match self.count {
+            0 => Duration::ZERO,
+            count => Duration::from_nanos((self.total.as_nanos() / count as u128) as u64),
+        }
+    }
+
+    // the duration `percentile`% of the calls stayed under, e.g. 99.0 for p99
+    pub fn percentile(&self, percentile: f64) -> Duration {
+        if self.count == 0 {
+            return Duration::ZERO;
+        }
+
+        let rank = ((percentile / 100.0) * self.count as f64).ceil().max(1.0) as u64;
+        let mut seen = 0;
+        for (bucket, count) in self.buckets.iter() {
+            seen += count;
+            if seen >= rank {
+                return upper_bound(*bucket).clamp(self.min, self.max);
+            }
+        }
+        self.max
+    }
+
+    // the upper bound of every bucket holding calls, with how many calls took at most that long
+    pub fn buckets(&self) -> Vec<(Duration, u64)> {
+        let mut seen = 0;
+        self.buckets
+            .iter()
+            .map(|(bucket, count)| {
+                seen += count;
+                (upper_bound(*bucket), seen)
+            })
+            .collect()
+    }
+}
+
+fn bucket_of(elapsed: Duration) -> u64 {
+    let nanos = elapsed.as_nanos().min(u64::MAX as u128) as u64;
+    if nanos < SUB_BUCKETS {
+        return nanos;
+    }
+    let power = 63 - nanos.leading_zeros() as u64;
+    let sub = (nanos >> (power - 3)) & (SUB_BUCKETS - 1);
+    power * SUB_BUCKETS + sub
+}
+
+fn upper_bound(bucket: u64) -> Duration {
+    if bucket < SUB_BUCKETS {
+        return Duration::from_nanos(bucket);
+    }
+    let power = bucket / SUB_BUCKETS;
+    let sub = bucket % SUB_BUCKETS;
+    let width = 1u64 << (power - 3);
+    Duration::from_nanos((1u64 << power) + (sub + 1) * width - 1)
+}
+
+// The numbers of one function, as shown in the reports
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FunctionMetrics {
+    pub path: String,
+    pub count: u64,
+    pub total: Duration,
+    pub min: Duration,
+    pub max: Duration,
+    pub mean: Duration,
+    pub p50: Duration,
+    pub p90: Duration,
+    pub p99: Duration,
+}
+
+impl FunctionMetrics {
+    fn new(path: &str, histogram: &Histogram) -> Self {
+        Self {
+            path: path.to_string(),
+            count: histogram.count(),
+            total: histogram.total(),
+            min: histogram.min(),
+            max: histogram.max(),
+            mean: histogram.mean(),
+            p50: histogram.percentile(50.0),
+            p90: histogram.percentile(90.0),
+            p99: histogram.percentile(99.0),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReportFormat {
+    Table,
+    Json,
+    Prometheus,
+    // the nested spans, see the spans module
+    CallTree,
+    Folded,
+}
+
+// A call site by its address, the static each timed function keeps
+#[derive(Clone, Copy)]
+struct Site(&'static CallSite);
+
+impl PartialEq for Site {
+    fn eq(&self, other: &Self) -> bool {
+        ptr::eq(self.0, other.0)
+    }
+}
+
+impl Eq for Site {}
+
+impl Hash for Site {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        ptr::hash(self.0, state);
+    }
+}
+
+struct Entry {
+    module: &'static str,
+    label: &'static str,
+    histogram: Histogram,
+}
+
+fn registry() -> &'static Mutex<HashMap<Site, Entry>> {
+    static REGISTRY: OnceLock<Mutex<HashMap<Site, Entry>>> = OnceLock::new();
+    REGISTRY.get_or_init(|| Mutex::new(HashMap::new()))
+}
+
+// every reported #[compute_time] call ends up here, keyed by its call site,
+// the module path and label are only put together for the reports
+pub fn record(
+    call_site: &'static CallSite,
+    module: &'static str,
+    label: &'static str,
+    elapsed: Duration,
+) {
+    let mut registry = registry().lock().unwrap();
+    registry
+        .entry(Site(call_site))
+        .or_insert_with(|| Entry {
+            module,
+            label,
+            histogram: Histogram::new(),
+        })
+        .histogram
+        .record(elapsed);
+}
+
+// functions reported under the same module path and label are added up
+fn by_path() -> BTreeMap<String, Histogram> {
+    let registry = registry().lock().unwrap();
+    let mut by_path: BTreeMap<String, Histogram> = BTreeMap::new();
+    for entry in registry.values() {
+        by_path
+            .entry(format!("{}::{}", entry.module, entry.label))
+            .or_default()
+            .merge(&entry.histogram);
+    }
+    by_path
+}
+
+pub fn histogram(path: &str) -> Option<Histogram> {
+    by_path().remove(path)
+}
+
+// all functions recorded so far, sorted by path
+pub fn snapshot() -> Vec<FunctionMetrics> {
+    by_path()
+        .iter()
+        .map(|(path, histogram)| FunctionMetrics::new(path, histogram))
+        .collect()
+}
+
+pub fn reset() {
+    registry().lock().unwrap().clear();
+}
+
+pub fn report(format: ReportFormat) -> String {
+    match format {
+        ReportFormat::Table => table(&snapshot()),
+        ReportFormat::Json => json(&snapshot()),
+        ReportFormat::Prometheus => prometheus(&by_path()),
+        ReportFormat::CallTree => spans::call_tree(),
+        ReportFormat::Folded => spans::folded(),
+    }
+}
+
+fn table(metrics: &[FunctionMetrics]) -> String {
+    let width = metrics
+        .iter()
+        .map(|metric| metric.path.len())
+        .max()
+        .unwrap_or(0)
+        .max("function".len());
+
+    let mut table = format!(
+        "{:<width$} {:>8} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}\n",
+        "function",
+        "count",
+        "min",
+        "mean",
+        "p50",
+        "p90",
+        "p99",
+        "max",
+        width = width
+    );
+    for metric in metrics {
+        let _ = writeln!(
+            table,
+            "{:<width$} {:>8} {:>12} {:>12} {:>12} {:>12} {:>12} {:>12}",
+            metric.path,
+            metric.count,
+            format!("{:.1?}", metric.min),
+            format!("{:.1?}", metric.mean),
+            format!("{:.1?}", metric.p50),
+            format!("{:.1?}", metric.p90),
+            format!("{:.1?}", metric.p99),
+            format!("{:.1?}", metric.max),
+            width = width
+        );
+    }
+    table
+}
+
+// durations in nanoseconds
+fn json(metrics: &[FunctionMetrics]) -> String {
+    let entries: Vec<String> = metrics
+        .iter()
+        .map(|metric| {
+            format!(
+                "{{\"function\":\"{}\",\"count\":{},\"total_ns\":{},\"min_ns\":{},\"max_ns\":{},\"mean_ns\":{},\"p50_ns\":{},\"p90_ns\":{},\"p99_ns\":{}}}",
+                escape_json(&metric.path),
+                metric.count,
+                metric.total.as_nanos(),
+                metric.min.as_nanos(),
+                metric.max.as_nanos(),
+                metric.mean.as_nanos(),
+                metric.p50.as_nanos(),
+                metric.p90.as_nanos(),
+                metric.p99.as_nanos()
+            )
+        })
+        .collect();
+    format!("[{}]", entries.join(","))
+}
+
+// a histogram per function, in seconds as Prometheus expects,
+// with a bucket for every bound some call fell under
+fn prometheus(histograms: &BTreeMap<String, Histogram>) -> String {
+    let mut text = String::from(
+        "# HELP compute_time_seconds Time spent in functions timed with #[compute_time]\n\
+         # TYPE compute_time_seconds histogram\n",
+    );
+    for (path, histogram) in histograms {
+        let function = escape_label(path);
+        for (bound, count) in histogram.buckets() {
+            let _ = writeln!(
+                text,
+                "compute_time_seconds_bucket{{function=\"{}\",le=\"{}\"}} {}",
+                function,
+                bound.as_secs_f64(),
+                count
+            );
+        }
+        let _ = writeln!(
+            text,
+            "compute_time_seconds_bucket{{function=\"{}\",le=\"+Inf\"}} {}",
+            function,
+            histogram.count()
+        );
+        let _ = writeln!(
+            text,
+            "compute_time_seconds_sum{{function=\"{}\"}} {}",
+            function,
+            histogram.total().as_secs_f64()
+        );
+        let _ = writeln!(
+            text,
+            "compute_time_seconds_count{{function=\"{}\"}} {}",
+            function,
+            histogram.count()
+        );
+    }
+    text
+}
+
+fn escape_json(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for character in text.chars() {
+        match character {
+            '"' => escaped.push_str("\\\""),
+            '\\' => escaped.push_str("\\\\"),
+            '\n' => escaped.push_str("\\n"),
+            character if character.is_control() => {
+                let _ = write!(escaped, "\\u{:04x}", character as u32);
+            }
+            character => escaped.push(character),
+        }
+    }
+    escaped
+}
+
+fn escape_label(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn histogram(samples: &[Duration]) -> Histogram {
+        let mut histogram = Histogram::new();
+        for sample in samples {
+            histogram.record(*sample);
+        }
+        histogram
+    }
+
+    #[test]
+    fn a_duration_lands_in_a_bucket_at_most_an_eighth_wider() {
+        assert_eq!(bucket_of(Duration::from_nanos(5)), 5);
+        assert_eq!(
+            upper_bound(bucket_of(Duration::from_nanos(8))),
+            Duration::from_nanos(8)
+        );
+        // 16 and 17ns share a bucket, 18ns starts the next one
+        assert_eq!(
+            bucket_of(Duration::from_nanos(16)),
+            bucket_of(Duration::from_nanos(17))
+        );
+        assert_eq!(
+            bucket_of(Duration::from_nanos(18)),
+            bucket_of(Duration::from_nanos(17)) + 1
+        );
+
+        for nanos in (0..10_000).chain([1 << 20, 123_456_789, u64::MAX / 2]) {
+            let bound = upper_bound(bucket_of(Duration::from_nanos(nanos))).as_nanos() as u64;
+            assert!(bound >= nanos, "{} above its bucket", nanos);
+            assert!(
+                bound - nanos <= nanos / 8,
+                "{} in a bucket ending at {}",
+                nanos,
+                bound
+            );
+        }
+    }
+
+    #[test]
+    fn percentiles_stay_within_an_eighth_of_the_samples() {
+        let samples: Vec<Duration> = (1..=100).map(Duration::from_micros).collect();
+        let histogram = histogram(&samples);
+
+        for (percentile, expected) in [(50.0, 50), (90.0, 90), (99.0, 99)] {
+            let expected = Duration::from_micros(expected);
+            let actual = histogram.percentile(percentile);
+            assert!(
+                actual >= expected && actual <= expected + expected / 8,
+                "p{} is {:?}",
+                percentile,
+                actual
+            );
+        }
+        // never past the slowest or below the fastest call
+        assert_eq!(histogram.percentile(100.0), Duration::from_micros(100));
+        assert!(histogram.percentile(0.0) >= Duration::from_micros(1));
+        assert_eq!(Histogram::new().percentile(50.0), Duration::ZERO);
+    }
+
+    #[test]
+    fn min_max_and_mean_follow_the_samples() {
+        let mut histogram = histogram(&[
+            Duration::from_millis(3),
+            Duration::from_millis(1),
+            Duration::from_millis(2),
+        ]);
+        assert_eq!(histogram.count(), 3);
+        assert_eq!(histogram.total(), Duration::from_millis(6));
+        assert_eq!(histogram.min(), Duration::from_millis(1));
+        assert_eq!(histogram.max(), Duration::from_millis(3));
+        assert_eq!(histogram.mean(), Duration::from_millis(2));
+
+        histogram.merge(&self::histogram(&[
+            Duration::from_micros(10),
+            Duration::from_millis(9),
+        ]));
+        assert_eq!(histogram.count(), 5);
+        assert_eq!(histogram.min(), Duration::from_micros(10));
+        assert_eq!(histogram.max(), Duration::from_millis(9));
+        assert_eq!(Histogram::new().mean(), Duration::ZERO);
+    }
+
+    #[test]
+    fn prometheus_text_has_cumulative_buckets_a_sum_and_a_count() {
+        let mut histograms = BTreeMap::new();
+        histograms.insert(
+            "game::roll".to_string(),
+            histogram(&[
+                Duration::from_nanos(1_000),
+                Duration::from_nanos(1_000),
+                Duration::from_nanos(3_000),
+            ]),
+        );
+
+        assert_eq!(
+            prometheus(&histograms),
+            "# HELP compute_time_seconds Time spent in functions timed with #[compute_time]\n\
+             # TYPE compute_time_seconds histogram\n\
+             compute_time_seconds_bucket{function=\"game::roll\",le=\"0.000001023\"} 2\n\
+             compute_time_seconds_bucket{function=\"game::roll\",le=\"0.000003071\"} 3\n\
+             compute_time_seconds_bucket{function=\"game::roll\",le=\"+Inf\"} 3\n\
+             compute_time_seconds_sum{function=\"game::roll\"} 0.000005\n\
+             compute_time_seconds_count{function=\"game::roll\"} 3\n"
+        );
+    }
+}
@@ -0,0 +1,4 @@
+pub mod metrics;
+pub mod sink;
+pub mod spans;
+pub mod timer;
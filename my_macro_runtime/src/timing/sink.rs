@@ -0,0 +1,242 @@
+use std::collections::HashMap;
+use std::fmt;
+use std::sync::{Arc, Mutex, OnceLock, RwLock};
+use std::time::Duration;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl From<Level> for log::Level {
+    fn from(level: Level) -> Self {
+        match level {
+            Level::Error => log::Level::Error,
+            Level::Warn => log::Level::Warn,
+            Level::Info => log::Level::Info,
+            Level::Debug => log::Level::Debug,
+            Level::Trace => log::Level::Trace,
+        }
+    }
+}
+
+// How the elapsed time is written out, Auto picks whatever fits like `{:?}` of a Duration
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    Auto,
+    Nanoseconds,
+    Microseconds,
+    Milliseconds,
+    Seconds,
+}
+
+impl Unit {
+    pub fn format(self, elapsed: Duration) -> String {
+        match self {
+            Unit::Auto => format!("{:?}", elapsed),
+            Unit::Nanoseconds => format!("{}ns", elapsed.as_nanos()),
+            Unit::Microseconds => format!("{:.3}µs", elapsed.as_secs_f64() * 1_000_000.0),
+            Unit::Milliseconds => format!("{:.3}ms", elapsed.as_secs_f64() * 1_000.0),
+            Unit::Seconds => format!("{:.3}s", elapsed.as_secs_f64()),
+        }
+    }
+}
+
+// One timed call as handed to the sinks, or several of them when they are flushed together
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Timing {
+    pub label: String,
+    // module the function lives in
+    pub module: &'static str,
+    // the time of all `calls` together
+    pub elapsed: Duration,
+    pub calls: u64,
+    pub slowest: Duration,
+    pub level: Level,
+    pub unit: Unit,
+    pub panicked: bool,
+}
+
+impl fmt::Display for Timing {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let elapsed = self.unit.format(self.elapsed);
+        if self.panicked {
+            write!(f, "{} panicked after {}", self.label, elapsed)
+        } else if self.calls > 1 {
+            let mean = self.elapsed / self.calls.min(u32::MAX as u64) as u32;
+            write!(
+                f,
+                "{} took {} over {} calls, mean {}, slowest {}",
+                self.label,
+                elapsed,
+                self.calls,
+                self.unit.format(mean),
+                self.unit.format(self.slowest)
+            )
+        } else {
+            write!(f, "{} took {}", self.label, elapsed)
+        }
+    }
+}
+
+// Where timings end up, register one with `register_sink` and pick it with
+// #[compute_time(sink = "registered:name")]
+pub trait TimingSink: Send + Sync {
+    fn record(&self, timing: &Timing);
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StdoutSink;
+
+impl TimingSink for StdoutSink {
+    fn record(&self, timing: &Timing) {
+        println!("{}", timing);
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default)]
+pub struct StderrSink;
+
+impl TimingSink for StderrSink {
+    fn record(&self, timing: &Timing) {
+        eprintln!("{}", timing);
+    }
+}
+
+// Hands timings to whatever `log` backend the program installed, at the timing's level
+#[derive(Debug, Clone, Copy, Default)]
+pub struct LogSink;
+
+impl TimingSink for LogSink {
+    fn record(&self, timing: &Timing) {
+        log::log!(target: timing.module, timing.level.into(), "{}", timing);
+    }
+}
+
+// Drops timings, for functions that should only show up in the metrics
+#[derive(Debug, Clone, Copy, Default)]
+pub struct NullSink;
+
+impl TimingSink for NullSink {
+    fn record(&self, _timing: &Timing) {}
+}
+
+// Keeps every timing in memory, mostly for tests
+#[derive(Debug, Default)]
+pub struct MemorySink {
+    timings: Mutex<Vec<Timing>>,
+}
+
+impl MemorySink {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn timings(&self) -> Vec<Timing> {
+        self.timings.lock().unwrap().clone()
+    }
+
+    // hands over everything collected so far and starts over
+    pub fn take(&self) -> Vec<Timing> {
+        std::mem::take(&mut *self.timings.lock().unwrap())
+    }
+}
+
+impl TimingSink for MemorySink {
+    fn record(&self, timing: &Timing) {
+        self.timings.lock().unwrap().push(timing.clone());
+    }
+}
+
+pub const DEFAULT_SINK: &str = "stdout";
+
+fn sinks() -> &'static RwLock<HashMap<String, Arc<dyn TimingSink>>> {
+    static SINKS: OnceLock<RwLock<HashMap<String, Arc<dyn TimingSink>>>> = OnceLock::new();
+    SINKS.get_or_init(|| {
+        let mut sinks: HashMap<String, Arc<dyn TimingSink>> = HashMap::new();
+        sinks.insert(DEFAULT_SINK.to_string(), Arc::new(StdoutSink));
+        sinks.insert("stderr".to_string(), Arc::new(StderrSink));
+        sinks.insert("log".to_string(), Arc::new(LogSink));
+        sinks.insert("none".to_string(), Arc::new(NullSink));
+        RwLock::new(sinks)
+    })
+}
+
+// adds a sink, or replaces the one registered under the same name
+pub fn register_sink(name: &str, sink: Arc<dyn TimingSink>) {
+    sinks().write().unwrap().insert(name.to_string(), sink);
+}
+
+pub fn unregister_sink(name: &str) -> bool {
+    sinks().write().unwrap().remove(name).is_some()
+}
+
+// timings for a sink that is not registered (anymore) go to stdout, so they are never lost silently
+pub fn record(sink: &str, timing: &Timing) {
+    // the lock is let go before recording, a sink may register other sinks
+    let sink = {
+        let sinks = sinks().read().unwrap();
+        sinks.get(sink).or_else(|| sinks.get(DEFAULT_SINK)).cloned()
+    };
+    match sink {
+        Some(sink) => sink.record(timing),
+        None => println!("{}", timing),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn timing(label: &str) -> Timing {
+        Timing {
+            label: label.to_string(),
+            module: module_path!(),
+            elapsed: Duration::from_millis(3),
+            calls: 1,
+            slowest: Duration::from_millis(3),
+            level: Level::Info,
+            unit: Unit::Milliseconds,
+            panicked: false,
+        }
+    }
+
+    #[test]
+    fn a_registered_memory_sink_collects_what_is_recorded_to_it() {
+        let collector = Arc::new(MemorySink::new());
+        register_sink("sink-test-collector", collector.clone());
+
+        record("sink-test-collector", &timing("forge"));
+        record("sink-test-collector", &timing("smelt"));
+        assert!(unregister_sink("sink-test-collector"));
+        record("sink-test-collector", &timing("after"));
+
+        let labels: Vec<String> = collector
+            .take()
+            .into_iter()
+            .map(|timing| timing.label)
+            .collect();
+        assert_eq!(labels, vec!["forge", "smelt"]);
+        assert!(collector.timings().is_empty());
+    }
+
+    #[test]
+    fn timings_are_written_in_their_unit() {
+        assert_eq!(timing("forge").to_string(), "forge took 3.000ms");
+
+        let flushed = Timing {
+            elapsed: Duration::from_millis(9),
+            calls: 3,
+            slowest: Duration::from_millis(5),
+            ..timing("forge")
+        };
+        assert_eq!(
+            flushed.to_string(),
+            "forge took 9.000ms over 3 calls, mean 3.000ms, slowest 5.000ms"
+        );
+    }
+}
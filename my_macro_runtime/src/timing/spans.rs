@@ -0,0 +1,248 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread::{self, ThreadId};
+use std::time::Duration;
+
+// A timed function that has not returned yet
+#[derive(Debug)]
+struct Frame {
+    id: u64,
+    label: &'static str,
+    // time spent in timed functions called from this one
+    children: Duration,
+}
+
+thread_local! {
+    // the timed functions the current thread is in, outermost first
+    static STACK: RefCell<Vec<Frame>> = const { RefCell::new(Vec::new()) };
+}
+
+// ids are unique across threads, a span can never be mistaken for another thread's
+static NEXT_ID: AtomicU64 = AtomicU64::new(0);
+
+// Spans closed on another thread than the one they were opened on, e.g. by a future
+// that moved between threads, are put under this root: their callers are not known there
+pub const DETACHED: &str = "(detached)";
+
+// One function at one place in the call tree
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SpanNode {
+    pub calls: u64,
+    // from entering until returning
+    pub inclusive: Duration,
+    // without the time spent in the timed functions it called
+    pub exclusive: Duration,
+    pub children: BTreeMap<&'static str, SpanNode>,
+}
+
+static TREE: Mutex<BTreeMap<&'static str, SpanNode>> = Mutex::new(BTreeMap::new());
+
+// A span on the stack of the thread that opened it, handed back to `exit`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpanId {
+    id: u64,
+    thread: ThreadId,
+    label: &'static str,
+}
+
+// None while the thread is shutting down, such a span is not tracked
+pub fn enter(label: &'static str) -> Option<SpanId> {
+    let id = NEXT_ID.fetch_add(1, Ordering::Relaxed);
+    STACK
+        .try_with(|stack| {
+            stack.borrow_mut().push(Frame {
+                id,
+                label,
+                children: Duration::ZERO,
+            })
+        })
+        .ok()?;
+    Some(SpanId {
+        id,
+        thread: thread::current().id(),
+        label,
+    })
+}
+
+// Closes the span and, when `recorded`, adds it to the call tree under the spans still
+// open around it. Either way its time counts as spent in a child of the span around it.
+pub fn exit(span: SpanId, elapsed: Duration, recorded: bool) {
+    let path = if span.thread == thread::current().id() {
+        STACK
+            .try_with(|stack| close(&mut stack.borrow_mut(), &span, elapsed))
+            .ok()
+            .flatten()
+    } else {
+        // the stack it sits on belongs to another thread, only its own time is known
+        Some((vec![DETACHED, span.label], elapsed))
+    };
+
+    if let (true, Some((path, exclusive))) = (recorded, path) {
+        let mut tree = TREE.lock().unwrap();
+        let (first, rest) = path.split_first().unwrap();
+        let mut node = tree.entry(first).or_default();
+        for label in rest {
+            node = node.children.entry(label).or_default();
+        }
+        node.calls += 1;
+        node.inclusive += elapsed;
+        node.exclusive += exclusive;
+    }
+}
+
+// takes the span off the stack, with the path to it and its exclusive time
+fn close(
+    stack: &mut Vec<Frame>,
+    span: &SpanId,
+    elapsed: Duration,
+) -> Option<(Vec<&'static str>, Duration)> {
+    // spans normally close innermost first, but a future dropped out of order
+    // must not take its parents down with it
+    let position = stack.iter().rposition(|frame| frame.id == span.id)?;
+    let frame = stack.remove(position);
+    if let Some(parent) = position.checked_sub(1) {
+        stack[parent].children += elapsed;
+    }
+
+    let mut path: Vec<&'static str> = stack[..position].iter().map(|frame| frame.label).collect();
+    path.push(frame.label);
+    Some((path, elapsed.saturating_sub(frame.children)))
+}
+
+// the call tree of every span closed so far, roots are the outermost timed functions
+pub fn tree() -> BTreeMap<&'static str, SpanNode> {
+    TREE.lock().unwrap().clone()
+}
+
+pub fn reset() {
+    TREE.lock().unwrap().clear();
+}
+
+// An indented tree, each function with its calls, inclusive and exclusive times
+pub fn call_tree() -> String {
+    let mut text = String::new();
+    for (label, node) in tree().iter() {
+        write_node(&mut text, label, node, 0);
+    }
+    text
+}
+
+fn write_node(text: &mut String, label: &str, node: &SpanNode, depth: usize) {
+    let _ = writeln!(
+        text,
+        "{:indent$}{} x{}: {:.1?} inclusive, {:.1?} exclusive",
+        "",
+        label,
+        node.calls,
+        node.inclusive,
+        node.exclusive,
+        indent = depth * 2
+    );
+    for (child, child_node) in node.children.iter() {
+        write_node(text, child, child_node, depth + 1);
+    }
+}
+
+// One line per call path with its exclusive time in microseconds, e.g.
+// main;quest;fight 1200
+// which is what flamegraph tools such as inferno-flamegraph read
+pub fn folded() -> String {
+    let mut text = String::new();
+    for (label, node) in tree().iter() {
+        write_folded(&mut text, label.replace(';', ","), node);
+    }
+    text
+}
+
+fn write_folded(text: &mut String, path: String, node: &SpanNode) {
+    let micros = node.exclusive.as_micros();
+    if micros > 0 {
+        let _ = writeln!(text, "{} {}", path, micros);
+    }
+    for (child, child_node) in node.children.iter() {
+        // `;` separates the frames, it can not be part of a label
+        write_folded(
+            text,
+            format!("{};{}", path, child.replace(';', ",")),
+            child_node,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MS: Duration = Duration::from_millis(1);
+
+    // the tree is shared by all tests, each one uses labels of its own
+    fn node(path: &[&'static str]) -> Option<SpanNode> {
+        let tree = tree();
+        let (first, rest) = path.split_first().unwrap();
+        let mut node = tree.get(first)?;
+        for label in rest {
+            node = node.children.get(label)?;
+        }
+        Some(node.clone())
+    }
+
+    #[test]
+    fn nested_spans_split_inclusive_and_exclusive_time() {
+        let outer = enter("spans-test-outer").unwrap();
+        let inner = enter("spans-test-inner").unwrap();
+        exit(inner, 3 * MS, true);
+        exit(outer, 5 * MS, true);
+
+        let outer = node(&["spans-test-outer"]).unwrap();
+        assert_eq!((outer.inclusive, outer.exclusive), (5 * MS, 2 * MS));
+        let inner = node(&["spans-test-outer", "spans-test-inner"]).unwrap();
+        assert_eq!((inner.calls, inner.inclusive), (1, 3 * MS));
+    }
+
+    #[test]
+    fn a_span_closed_out_of_order_leaves_its_parent_open() {
+        let parent = enter("spans-test-parent").unwrap();
+        let first = enter("spans-test-first").unwrap();
+        let second = enter("spans-test-second").unwrap();
+        exit(first, MS, true);
+        exit(second, MS, true);
+        exit(parent, 4 * MS, true);
+
+        // once `first` is gone, `second` sits right under the parent
+        assert!(node(&["spans-test-parent", "spans-test-first"]).is_some());
+        assert!(node(&["spans-test-parent", "spans-test-second"]).is_some());
+        assert_eq!(node(&["spans-test-parent"]).unwrap().exclusive, 2 * MS);
+    }
+
+    #[test]
+    fn a_span_closed_on_another_thread_is_detached() {
+        let moved = enter("spans-test-moved").unwrap();
+        // the other thread has spans of its own, none of them may be closed instead
+        thread::spawn(move || {
+            let local = enter("spans-test-local").unwrap();
+            exit(moved, 2 * MS, true);
+            exit(local, MS, true);
+        })
+        .join()
+        .unwrap();
+
+        let detached = node(&[DETACHED, "spans-test-moved"]).unwrap();
+        assert_eq!((detached.calls, detached.exclusive), (1, 2 * MS));
+        assert_eq!(node(&["spans-test-local"]).unwrap().calls, 1);
+        assert!(node(&["spans-test-local", "spans-test-moved"]).is_none());
+    }
+
+    #[test]
+    fn a_span_that_is_not_recorded_still_counts_for_its_parent() {
+        let parent = enter("spans-test-recorded").unwrap();
+        let child = enter("spans-test-skipped").unwrap();
+        exit(child, 3 * MS, false);
+        exit(parent, 4 * MS, true);
+
+        assert!(node(&["spans-test-recorded", "spans-test-skipped"]).is_none());
+        assert_eq!(node(&["spans-test-recorded"]).unwrap().exclusive, MS);
+    }
+}
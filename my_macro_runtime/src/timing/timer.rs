@@ -0,0 +1,324 @@
+use std::sync::atomic::{AtomicBool, AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use super::metrics::{self, ReportFormat};
+use super::sink::{self, Level, Timing, Unit};
+use super::spans::{self, SpanId};
+
+// What #[compute_time(...)] was configured with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TimerOptions {
+    pub label: &'static str,
+    pub module: &'static str,
+    pub sink: &'static str,
+    pub level: Level,
+    pub unit: Unit,
+    // prints the report of every timed function once this one returns, meant for `main`
+    pub report: Option<ReportFormat>,
+    // faster calls are not reported
+    pub threshold: Duration,
+    // reports one call out of this many, starting with the first
+    pub sample_every: u64,
+    // adds the calls up and reports them together at most this often
+    pub flush_every: Option<Duration>,
+}
+
+// Calls that are waiting to be flushed together
+#[derive(Debug)]
+struct Pending {
+    options: Option<TimerOptions>,
+    calls: u64,
+    total: Duration,
+    slowest: Duration,
+    last_flush: Option<Instant>,
+}
+
+// The state of one timed function, shared by all of its calls.
+// The generated code keeps one in a static inside the function.
+#[derive(Debug)]
+pub struct CallSite {
+    calls: AtomicU64,
+    pending: Mutex<Pending>,
+    flushable: AtomicBool,
+}
+
+impl CallSite {
+    pub const fn new() -> Self {
+        Self {
+            calls: AtomicU64::new(0),
+            pending: Mutex::new(Pending {
+                options: None,
+                calls: 0,
+                total: Duration::ZERO,
+                slowest: Duration::ZERO,
+                last_flush: None,
+            }),
+            flushable: AtomicBool::new(false),
+        }
+    }
+
+    // how many times the function was called so far
+    pub fn calls(&self) -> u64 {
+        self.calls.load(Ordering::Relaxed)
+    }
+
+    fn add(&'static self, options: TimerOptions, elapsed: Duration, interval: Duration) {
+        let now = Instant::now();
+        let mut pending = self.pending.lock().unwrap();
+        pending.options = Some(options);
+        pending.calls += 1;
+        pending.total += elapsed;
+        pending.slowest = pending.slowest.max(elapsed);
+
+        let last_flush = *pending.last_flush.get_or_insert(now);
+        if now.duration_since(last_flush) >= interval {
+            pending.last_flush = Some(now);
+            let timing = Self::take(&mut pending);
+            drop(pending);
+            if let Some((sink, timing)) = timing {
+                sink::record(sink, &timing);
+            }
+        } else if !self.flushable.swap(true, Ordering::Relaxed) {
+            // remembered so `flush_all` can report what is still pending at the end
+            call_sites().lock().unwrap().push(self);
+        }
+    }
+
+    fn flush(&self) {
+        let timing = Self::take(&mut self.pending.lock().unwrap());
+        if let Some((sink, timing)) = timing {
+            sink::record(sink, &timing);
+        }
+    }
+
+    fn take(pending: &mut Pending) -> Option<(&'static str, Timing)> {
+        let options = pending.options?;
+        if pending.calls == 0 {
+            return None;
+        }
+
+        let timing = Timing {
+            label: options.label.to_string(),
+            module: options.module,
+            elapsed: pending.total,
+            calls: pending.calls,
+            slowest: pending.slowest,
+            level: options.level,
+            unit: options.unit,
+            panicked: false,
+        };
+        pending.calls = 0;
+        pending.total = Duration::ZERO;
+        pending.slowest = Duration::ZERO;
+        Some((options.sink, timing))
+    }
+}
+
+impl Default for CallSite {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+fn call_sites() -> &'static Mutex<Vec<&'static CallSite>> {
+    static CALL_SITES: Mutex<Vec<&'static CallSite>> = Mutex::new(Vec::new());
+    &CALL_SITES
+}
+
+// reports the calls still waiting for their periodic flush, e.g. before the program exits
+pub fn flush_all() {
+    let call_sites = call_sites().lock().unwrap().clone();
+    for call_site in call_sites {
+        call_site.flush();
+    }
+}
+
+// Records how long it lived once dropped, the generated code creates one
+// at the start of every timed function.
+// Calls left out by the sampling or faster than the threshold cost a clock read and
+// a counter, they never allocate nor lock, and show up neither in the metrics nor the spans.
+#[derive(Debug)]
+pub struct Timer {
+    options: TimerOptions,
+    call_site: &'static CallSite,
+    sampled: bool,
+    // only sampled calls open a span, the others never touch the span stack
+    span: Option<SpanId>,
+    start: Instant,
+}
+
+impl Timer {
+    pub fn start(options: TimerOptions, call_site: &'static CallSite) -> Self {
+        let call = call_site.calls.fetch_add(1, Ordering::Relaxed) + 1;
+        // calls that are flushed together are all added up, there is nothing to sample
+        let sampled =
+            options.flush_every.is_some() || (call - 1).is_multiple_of(options.sample_every.max(1));
+        Self {
+            options,
+            call_site,
+            sampled,
+            span: sampled.then(|| spans::enter(options.label)).flatten(),
+            start: Instant::now(),
+        }
+    }
+
+    // Whether this call is reported at all: panics always are,
+    // then the sampling and the threshold decide
+    fn reported(&self, elapsed: Duration, panicked: bool) -> bool {
+        panicked || (self.sampled && elapsed >= self.options.threshold)
+    }
+}
+
+impl Drop for Timer {
+    fn drop(&mut self) {
+        let elapsed = self.start.elapsed();
+        let panicked = thread::panicking();
+        let reported = self.reported(elapsed, panicked);
+        if let Some(span) = self.span.take() {
+            spans::exit(span, elapsed, reported);
+        }
+
+        if reported {
+            metrics::record(
+                self.call_site,
+                self.options.module,
+                self.options.label,
+                elapsed,
+            );
+            match self.options.flush_every {
+                Some(interval) if !panicked => self.call_site.add(self.options, elapsed, interval),
+                _ => {
+                    let timing = Timing {
+                        label: self.options.label.to_string(),
+                        module: self.options.module,
+                        elapsed,
+                        calls: 1,
+                        slowest: elapsed,
+                        level: self.options.level,
+                        unit: self.options.unit,
+                        panicked,
+                    };
+                    sink::record(self.options.sink, &timing);
+                }
+            }
+        }
+
+        if let Some(format) = self.options.report {
+            flush_all();
+            println!("{}", metrics::report(format));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::panic;
+    use std::sync::Arc;
+
+    use super::super::sink::{register_sink, MemorySink};
+    use super::*;
+
+    fn options(label: &'static str, sink: &'static str) -> TimerOptions {
+        TimerOptions {
+            label,
+            module: "timer_tests",
+            sink,
+            level: Level::Info,
+            unit: Unit::Auto,
+            report: None,
+            threshold: Duration::ZERO,
+            sample_every: 1,
+            flush_every: None,
+        }
+    }
+
+    fn collector(name: &str) -> Arc<MemorySink> {
+        let collector = Arc::new(MemorySink::new());
+        register_sink(name, collector.clone());
+        collector
+    }
+
+    #[test]
+    fn only_sampled_calls_are_reported_and_measured() {
+        static CALL_SITE: CallSite = CallSite::new();
+        let collector = collector("timer-tests-sampled");
+        let options = TimerOptions {
+            sample_every: 3,
+            ..options("sampled", "timer-tests-sampled")
+        };
+
+        for _ in 0..7 {
+            drop(Timer::start(options, &CALL_SITE));
+        }
+
+        assert_eq!(CALL_SITE.calls(), 7);
+        assert_eq!(collector.take().len(), 3);
+        let histogram = metrics::histogram("timer_tests::sampled").unwrap();
+        assert_eq!(histogram.count(), 3);
+    }
+
+    #[test]
+    fn calls_faster_than_the_threshold_leave_no_trace() {
+        static CALL_SITE: CallSite = CallSite::new();
+        let collector = collector("timer-tests-threshold");
+        let options = TimerOptions {
+            threshold: Duration::from_secs(60),
+            ..options("threshold", "timer-tests-threshold")
+        };
+
+        for _ in 0..5 {
+            drop(Timer::start(options, &CALL_SITE));
+        }
+
+        assert_eq!(CALL_SITE.calls(), 5);
+        assert!(collector.take().is_empty());
+        assert_eq!(metrics::histogram("timer_tests::threshold"), None);
+        assert!(!spans::tree().contains_key("threshold"));
+    }
+
+    #[test]
+    fn a_panicking_call_is_reported_even_when_not_sampled() {
+        static CALL_SITE: CallSite = CallSite::new();
+        let collector = collector("timer-tests-panics");
+        let options = TimerOptions {
+            sample_every: 100,
+            ..options("panics", "timer-tests-panics")
+        };
+
+        drop(Timer::start(options, &CALL_SITE));
+        let result = panic::catch_unwind(|| {
+            let _timer = Timer::start(options, &CALL_SITE);
+            panic!("the second call is not sampled");
+        });
+
+        assert!(result.is_err());
+        let panicked: Vec<bool> = collector
+            .take()
+            .into_iter()
+            .map(|timing| timing.panicked)
+            .collect();
+        assert_eq!(panicked, vec![false, true]);
+    }
+
+    #[test]
+    fn flushed_calls_are_added_up() {
+        static CALL_SITE: CallSite = CallSite::new();
+        let collector = collector("timer-tests-flushed");
+        let options = TimerOptions {
+            flush_every: Some(Duration::from_secs(60)),
+            ..options("flushed", "timer-tests-flushed")
+        };
+
+        for _ in 0..4 {
+            drop(Timer::start(options, &CALL_SITE));
+        }
+        assert!(collector.timings().is_empty());
+        CALL_SITE.flush();
+
+        let timings = collector.take();
+        assert_eq!(timings.len(), 1);
+        assert_eq!(timings[0].calls, 4);
+    }
+}
@@ -0,0 +1,204 @@
+use std::cell::Cell;
+use std::fmt::{self, Write};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::thread;
+
+use crate::timing::sink::Level;
+
+// Where the lines of #[trace] go
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TraceSink {
+    Stdout,
+    Stderr,
+    // the log crate, with the module of the function as target
+    Log,
+}
+
+// What #[trace(...)] was configured with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TraceOptions {
+    pub label: &'static str,
+    pub module: &'static str,
+    pub sink: TraceSink,
+    pub level: Level,
+}
+
+// An argument as it is shown on entry
+pub enum Arg<'a> {
+    Value(&'static str, &'a dyn fmt::Debug),
+    // marked #[trace(redact)], only its name is shown
+    Redacted(&'static str),
+}
+
+static ENABLED: AtomicBool = AtomicBool::new(true);
+
+thread_local! {
+    // how many traced functions the current thread is in
+    static DEPTH: Cell<usize> = const { Cell::new(0) };
+}
+
+// turns every #[trace] on or off at once, they are on unless told otherwise
+pub fn set_enabled(enabled: bool) {
+    ENABLED.store(enabled, Ordering::Relaxed);
+}
+
+pub fn is_enabled() -> bool {
+    ENABLED.load(Ordering::Relaxed)
+}
+
+fn write_line(options: &TraceOptions, depth: usize, line: fmt::Arguments) {
+    let indent = "  ".repeat(depth);
+    match options.sink {
+        TraceSink::Stdout => println!("{}{}", indent, line),
+        TraceSink::Stderr => eprintln!("{}{}", indent, line),
+        TraceSink::Log => log::log!(
+            target: options.module,
+            options.level.into(),
+            "{}{}",
+            indent,
+            line
+        ),
+    }
+}
+
+// the arguments as they are shown on entry, e.g. `gold = 10, key = ***`
+fn render(args: &[Arg]) -> String {
+    let mut shown = String::new();
+    for (index, arg) in args.iter().enumerate() {
+        if index > 0 {
+            shown.push_str(", ");
+        }
+        let _ = match arg {
+            Arg::Value(name, value) => write!(shown, "{} = {:?}", name, value),
+            Arg::Redacted(name) => write!(shown, "{} = ***", name),
+        };
+    }
+    shown
+}
+
+// Writes the entry line when created and the exit line when told how the call ended,
+// or that it panicked when dropped without being told
+pub struct Span {
+    options: TraceOptions,
+    // None when tracing was off on entry
+    depth: Option<usize>,
+}
+
+impl Span {
+    pub fn enter(options: TraceOptions, args: &[Arg]) -> Self {
+        if !is_enabled() {
+            return Self {
+                options,
+                depth: None,
+            };
+        }
+
+        let shown = render(args);
+        let depth = DEPTH.with(|depth| depth.replace(depth.get() + 1));
+        write_line(
+            &options,
+            depth,
+            format_args!("→ {}({})", options.label, shown),
+        );
+        Self {
+            options,
+            depth: Some(depth),
+        }
+    }
+
+    fn exit(mut self, line: fmt::Arguments) {
+        if let Some(depth) = self.depth.take() {
+            DEPTH.with(|current| current.set(depth));
+            write_line(&self.options, depth, line);
+        }
+    }
+
+    // a function without a return value
+    pub fn done(self) {
+        let label = self.options.label;
+        self.exit(format_args!("← {}", label));
+    }
+
+    pub fn returned(self, value: &dyn fmt::Debug) {
+        let label = self.options.label;
+        self.exit(format_args!("← {}: {:?}", label, value));
+    }
+
+    pub fn ok(self, value: &dyn fmt::Debug) {
+        let label = self.options.label;
+        self.exit(format_args!("← {}: Ok({:?})", label, value));
+    }
+
+    pub fn err(self, error: &dyn fmt::Debug) {
+        let label = self.options.label;
+        self.exit(format_args!("← {} failed: {:?}", label, error));
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        if let Some(depth) = self.depth.take() {
+            let _ = DEPTH.try_with(|current| current.set(depth));
+            if thread::panicking() {
+                write_line(
+                    &self.options,
+                    depth,
+                    format_args!("← {} panicked", self.options.label),
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::panic;
+
+    const OPTIONS: TraceOptions = TraceOptions {
+        label: "open_treasure",
+        module: "tests",
+        sink: TraceSink::Stderr,
+        level: Level::Debug,
+    };
+
+    fn depth() -> usize {
+        DEPTH.with(Cell::get)
+    }
+
+    #[test]
+    fn a_redacted_argument_shows_only_its_name() {
+        let shown = render(&[
+            Arg::Value("gold", &10),
+            Arg::Redacted("key"),
+            Arg::Value("name", &"Crabby"),
+        ]);
+        assert_eq!(shown, "gold = 10, key = ***, name = \"Crabby\"");
+    }
+
+    #[test]
+    fn the_depth_follows_nested_calls() {
+        let outer = Span::enter(OPTIONS, &[]);
+        let inner = Span::enter(OPTIONS, &[]);
+        assert_eq!(depth(), 2);
+        inner.returned(&1);
+        assert_eq!(depth(), 1);
+        outer.done();
+        assert_eq!(depth(), 0);
+    }
+
+    #[test]
+    fn the_depth_is_back_to_where_it_was_after_a_panic() {
+        let outer = Span::enter(OPTIONS, &[]);
+        let result = panic::catch_unwind(|| {
+            let _inner = Span::enter(OPTIONS, &[]);
+            let _deeper = Span::enter(OPTIONS, &[]);
+            panic!("the chest was trapped");
+        });
+        assert!(result.is_err());
+        // the spans dropped while unwinding put the depth back to the outer call's
+        assert_eq!(depth(), 1);
+        outer.err(&"trapped");
+        assert_eq!(depth(), 0);
+    }
+}